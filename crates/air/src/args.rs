@@ -3,6 +3,7 @@ use clap::Subcommand;
 use clap::builder::Styles;
 use clap::builder::styling::{AnsiColor, Effects};
 use std::path::PathBuf;
+use workspace::format::LineRange;
 
 use crate::logging;
 
@@ -38,6 +39,9 @@ pub(crate) enum Command {
     /// Start a language server
     LanguageServer(LanguageServerCommand),
 
+    /// Inspect how a file is parsed and formatted
+    Debug(DebugCommand),
+
     /// Generate shell completion scripts
     #[clap(hide = true)]
     GenerateShellCompletion(GenerateShellCompletionCommand),
@@ -61,6 +65,63 @@ pub(crate) struct FormatCommand {
     #[arg(long)]
     pub force: bool,
 
+    /// Verify that formatting didn't change the meaning of the code, by comparing the
+    /// syntax trees of the original and formatted code, and that formatting again is a
+    /// no-op. Files that fail verification are reported as an internal error and left
+    /// unchanged. Enabled by default when the `CI` environment variable is set.
+    #[arg(long, overrides_with = "no_verify")]
+    pub verify: bool,
+
+    /// Disable `--verify`, including in CI.
+    #[arg(long, overrides_with = "verify")]
+    pub no_verify: bool,
+
+    /// When formatting a file panics, write a bug report to a new directory within
+    /// this one. The bug report contains the file, the settings it was formatted with as
+    /// an `air.toml`, the Air version, and the backtrace of the panic, and can be
+    /// attached to an issue.
+    #[arg(long, value_name = "DIR")]
+    pub bug_report: Option<PathBuf>,
+
+    /// Only format the given range of lines, like `10:20`. Line numbers are 1-based and
+    /// inclusive, and each range is widened to the complete expressions that it
+    /// overlaps. Can be supplied multiple times, but only when formatting a single file.
+    #[arg(long, value_name = "LINES")]
+    pub lines: Vec<LineRange>,
+
+    /// Only format files that changed since the given git reference, like `main`. Files
+    /// are compared against the merge base of the reference and `HEAD`, and include
+    /// uncommitted and untracked changes. Defaults to formatting the current directory
+    /// when no paths are provided.
+    #[arg(long, value_name = "REF")]
+    pub changed_since: Option<String>,
+
+    /// Only format files that are staged in the git index. Defaults to formatting the
+    /// current directory when no paths are provided.
+    #[arg(long)]
+    pub staged: bool,
+
+    /// With `--staged`, format the staged contents of each file and write them back to
+    /// the git index, rather than formatting the working tree. The formatting changes
+    /// are also applied to the working tree copy when they don't conflict with unstaged
+    /// changes, and files that can't be updated are reported.
+    #[arg(long)]
+    pub write_index: bool,
+
+    /// Only format the lines that git reports as changed, widened to the complete
+    /// expressions that they overlap. Lines are compared against `--changed-since` when
+    /// supplied, and against `HEAD` otherwise. With `--staged --write-index`, the lines
+    /// of the staged contents are compared against `HEAD` instead.
+    #[arg(long)]
+    pub only_changed_lines: bool,
+
+    /// Keep running and format files as they are saved. The discovered R files are
+    /// formatted once up front, then again whenever they change. Settings are reloaded
+    /// when a configuration file like `air.toml` changes. Defaults to watching the
+    /// current directory when no paths are provided.
+    #[arg(long)]
+    pub watch: bool,
+
     /// Use this option to enable reading from stdin and writing to stdout. This specifies
     /// a file path to associate the standard input with, which is used as the location to
     /// begin searching for configuration files from. The file does not have to exist and
@@ -74,6 +135,41 @@ pub(crate) struct FormatCommand {
 #[derive(Clone, Debug, Parser)]
 pub(crate) struct LanguageServerCommand {}
 
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct DebugCommand {
+    #[command(subcommand)]
+    pub view: DebugView,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum DebugView {
+    /// Print the tree-sitter tree of a file
+    TreeSitter {
+        /// The file to inspect
+        path: PathBuf,
+    },
+
+    /// Print the syntax tree that Air formats, including trivia like whitespace and
+    /// comments
+    Syntax {
+        /// The file to inspect
+        path: PathBuf,
+
+        /// Print the syntax tree as JSON, with the kind and range of each node and
+        /// token, and the text and trivia of each token. Ranges are byte offsets into
+        /// the UTF-8 contents of the file.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the intermediate representation that the formatter prints the code from,
+    /// using the settings that apply to the file
+    Ir {
+        /// The file to inspect
+        path: PathBuf,
+    },
+}
+
 #[derive(Clone, Debug, Parser)]
 pub(crate) struct GenerateShellCompletionCommand {
     /// The shell for which to generate the completion script
@@ -1,3 +1,4 @@
+pub(crate) mod debug;
 pub(crate) mod format;
 pub(crate) mod generate_shell_completion;
 pub(crate) mod language_server;
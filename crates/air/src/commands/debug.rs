@@ -0,0 +1,204 @@
+//! Inspect how Air parses and formats a file
+//!
+//! These are the same views as the `air/viewFile` request of the language server, for
+//! use outside of an editor. The JSON syntax tree is meant for external tooling that
+//! wants to consume Air's lossless syntax tree.
+
+use std::io::Write;
+use std::path::Path;
+
+use air_r_parser::RParserOptions;
+use air_r_syntax::RLanguage;
+use air_r_syntax::RSyntaxNode;
+use air_r_syntax::RSyntaxToken;
+use air_r_syntax::TextRange;
+use biome_rowan::NodeOrToken;
+use biome_rowan::SyntaxTriviaPiece;
+use serde_json::Value;
+use serde_json::json;
+use workspace::discovery::DiscoveredSettings;
+use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::encoding::decode;
+use workspace::resolve::PathResolver;
+
+use crate::ExitStatus;
+use crate::args::DebugCommand;
+use crate::args::DebugView;
+
+pub(crate) fn debug(command: DebugCommand) -> anyhow::Result<ExitStatus> {
+    let (path, view) = match &command.view {
+        DebugView::TreeSitter { path } => (path, View::TreeSitter),
+        DebugView::Syntax { path, json: false } => (path, View::Syntax),
+        DebugView::Syntax { path, json: true } => (path, View::SyntaxJson),
+        DebugView::Ir { path } => (path, View::Ir),
+    };
+
+    // Normalize up front, relative to current working directory
+    let path = fs::normalize_path(path);
+
+    match view_path(&path, view)? {
+        Some(output) => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(output.as_bytes())?;
+            if !output.ends_with('\n') {
+                writeln!(stdout)?;
+            }
+            Ok(ExitStatus::Success)
+        }
+        None => {
+            tracing::error!(
+                "Failed to parse {path}. Can't inspect a file with parse errors.",
+                path = path.display()
+            );
+            Ok(ExitStatus::Error)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum View {
+    TreeSitter,
+    Syntax,
+    SyntaxJson,
+    Ir,
+}
+
+/// The requested view of the file at `path`, or `None` if it has parse errors
+fn view_path(path: &Path, view: View) -> anyhow::Result<Option<String>> {
+    // Files that aren't covered by a project `air.toml` use the user level one, if any
+    let fallback = discover_user_settings()?.unwrap_or_default();
+    let mut resolver = PathResolver::new(fallback);
+
+    for DiscoveredSettings {
+        directory,
+        settings,
+    } in discover_settings(&[path])?
+    {
+        resolver.add(&directory, settings);
+    }
+
+    let settings = &resolver.resolve_or_fallback(path).format;
+
+    let bytes = std::fs::read(path)
+        .map_err(|err| anyhow::anyhow!("Failed to read {path}: {err}", path = path.display()))?;
+    let (source, _) = decode(bytes, settings.encoding)
+        .map_err(|err| anyhow::anyhow!("Failed to read {path}: {err}", path = path.display()))?;
+
+    let options = settings.to_format_options_for_path(&source, path);
+
+    let parser_options =
+        RParserOptions::default().with_minimum_r_version(options.minimum_r_version());
+    let parse = air_r_parser::parse(&source, parser_options);
+
+    match view {
+        View::TreeSitter => Ok(lsp::handlers_ext::view_tree_sitter(&source)),
+        View::Syntax => Ok(lsp::handlers_ext::view_syntax_tree(&parse)),
+        View::SyntaxJson => {
+            if parse.has_error() {
+                return Ok(None);
+            }
+            let json = node_to_json(&parse.syntax());
+            Ok(Some(serde_json::to_string_pretty(&json)?))
+        }
+        View::Ir => lsp::handlers_ext::view_format_tree(&parse, options),
+    }
+}
+
+/// Serialize a node and its descendants
+///
+/// The range of nodes and tokens excludes their trivia, which is attached to tokens
+/// as `leading_trivia` and `trailing_trivia`. Concatenating the trivia and text of all
+/// tokens gives back the original source.
+fn node_to_json(node: &RSyntaxNode) -> Value {
+    let children: Vec<Value> = node
+        .children_with_tokens()
+        .map(|element| match element {
+            NodeOrToken::Node(node) => node_to_json(&node),
+            NodeOrToken::Token(token) => token_to_json(&token),
+        })
+        .collect();
+
+    json!({
+        "kind": format!("{:?}", node.kind()),
+        "range": range_to_json(node.text_trimmed_range()),
+        "children": children,
+    })
+}
+
+fn token_to_json(token: &RSyntaxToken) -> Value {
+    let leading: Vec<Value> = token
+        .leading_trivia()
+        .pieces()
+        .map(trivia_to_json)
+        .collect();
+    let trailing: Vec<Value> = token
+        .trailing_trivia()
+        .pieces()
+        .map(trivia_to_json)
+        .collect();
+
+    json!({
+        "kind": format!("{:?}", token.kind()),
+        "range": range_to_json(token.text_trimmed_range()),
+        "text": token.text_trimmed(),
+        "leading_trivia": leading,
+        "trailing_trivia": trailing,
+    })
+}
+
+fn trivia_to_json(piece: SyntaxTriviaPiece<RLanguage>) -> Value {
+    json!({
+        "kind": format!("{:?}", piece.kind()),
+        "range": range_to_json(piece.text_range()),
+        "text": piece.text(),
+    })
+}
+
+fn range_to_json(range: TextRange) -> Value {
+    json!([u32::from(range.start()), u32::from(range.end())])
+}
+
+#[cfg(test)]
+mod test {
+    use air_r_parser::RParserOptions;
+    use serde_json::Value;
+
+    use crate::commands::debug::node_to_json;
+
+    /// Concatenate the trivia and text of all tokens below `value`
+    fn reconstruct(value: &Value, out: &mut String) {
+        if let Some(children) = value["children"].as_array() {
+            for child in children {
+                reconstruct(child, out);
+            }
+            return;
+        }
+
+        let trivia = |pieces: &Value| -> String {
+            pieces
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|piece| piece["text"].as_str().unwrap())
+                .collect()
+        };
+
+        out.push_str(&trivia(&value["leading_trivia"]));
+        out.push_str(value["text"].as_str().unwrap());
+        out.push_str(&trivia(&value["trailing_trivia"]));
+    }
+
+    #[test]
+    fn test_syntax_json_is_lossless() {
+        let source = "# comment\nx <- 1 # trailing\n\nf(a = 2)\n";
+        let parse = air_r_parser::parse(source, RParserOptions::default());
+        let json = node_to_json(&parse.syntax());
+
+        assert_eq!(json["kind"], "R_ROOT");
+
+        let mut reconstructed = String::new();
+        reconstruct(&json, &mut reconstructed);
+        assert_eq!(reconstructed, source);
+    }
+}
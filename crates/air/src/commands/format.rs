@@ -1,10 +1,20 @@
+use std::path::PathBuf;
+
 use workspace::discovery;
 
 use crate::ExitStatus;
 use crate::args::FormatCommand;
+use crate::commands::format::git::ChangedFiles;
+use crate::commands::format::git::GitChanges;
+use crate::commands::format::paths::FormatPathsOptions;
 
+mod bug_report;
+mod git;
+mod index;
 mod paths;
 mod stdin;
+mod watch;
+mod write;
 
 #[derive(Copy, Clone, Debug)]
 enum FormatMode {
@@ -18,6 +28,7 @@ pub(crate) fn format(command: FormatCommand) -> anyhow::Result<ExitStatus> {
     }
 
     let mode = FormatMode::from_command(&command);
+    let verify = resolve_verify(&command);
 
     let (exclude, include) = if command.force {
         (discovery::Exclude::Nothing, discovery::Include::Everything)
@@ -25,9 +36,48 @@ pub(crate) fn format(command: FormatCommand) -> anyhow::Result<ExitStatus> {
         (discovery::Exclude::Matched, discovery::Include::Matched)
     };
 
+    let changes = match (&command.changed_since, command.staged) {
+        (Some(reference), _) => Some(GitChanges::Since(reference.clone())),
+        (None, true) => Some(GitChanges::Staged {
+            index: command.write_index,
+        }),
+        (None, false) if command.only_changed_lines => Some(GitChanges::Uncommitted),
+        (None, false) => None,
+    };
+
+    let changed = changes
+        .map(|changes| ChangedFiles::new(&changes, command.only_changed_lines))
+        .transpose()?;
+
+    let mut paths = command.paths;
+
+    if (changed.is_some() || command.watch) && paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    if command.watch {
+        return watch::format(paths, exclude, include, verify);
+    }
+
+    if let Some(changed) = &changed
+        && command.write_index
+    {
+        return index::format(paths, exclude, include, changed, verify);
+    }
+
     match command.stdin_file_path {
-        Some(path) => stdin::format(path, mode, exclude, include),
-        None => paths::format(command.paths, mode, exclude, include),
+        Some(path) => stdin::format(path, mode, exclude, include, &command.lines, verify),
+        None => {
+            let options = FormatPathsOptions {
+                exclude,
+                include,
+                changed: changed.as_ref(),
+                lines: &command.lines,
+                verify,
+                bug_report: command.bug_report.as_deref(),
+            };
+            paths::format(paths, mode, &options)
+        }
     }
 }
 
@@ -45,9 +95,112 @@ fn check_argument_consistency(command: &FormatCommand) -> Option<ExitStatus> {
         return Some(ExitStatus::Error);
     }
 
+    if command.stdin_file_path.is_some()
+        && (command.changed_since.is_some() || command.staged || command.only_changed_lines)
+    {
+        tracing::error!(
+            "Can't use `--changed-since`, `--staged`, or `--only-changed-lines` when reading from stdin"
+        );
+        return Some(ExitStatus::Error);
+    }
+
+    if command.changed_since.is_some() && command.staged {
+        tracing::error!("Can't use `--changed-since` and `--staged` together");
+        return Some(ExitStatus::Error);
+    }
+
+    if command.bug_report.is_some()
+        && (command.stdin_file_path.is_some() || command.write_index || command.watch)
+    {
+        tracing::error!(
+            "Can't use `--bug-report` when reading from stdin, or with `--write-index` or `--watch`"
+        );
+        return Some(ExitStatus::Error);
+    }
+
+    if command.write_index {
+        if !command.staged {
+            tracing::error!("Can only use `--write-index` with `--staged`");
+            return Some(ExitStatus::Error);
+        }
+
+        if command.check {
+            tracing::error!("Can't use `--write-index` and `--check` together");
+            return Some(ExitStatus::Error);
+        }
+
+        if !command.lines.is_empty() {
+            tracing::error!("Can't use `--write-index` and `--lines` together");
+            return Some(ExitStatus::Error);
+        }
+    }
+
+    if command.watch {
+        if command.stdin_file_path.is_some() {
+            tracing::error!("Can't use `--watch` when reading from stdin");
+            return Some(ExitStatus::Error);
+        }
+
+        if command.check {
+            tracing::error!("Can't use `--watch` and `--check` together");
+            return Some(ExitStatus::Error);
+        }
+
+        if command.changed_since.is_some()
+            || command.staged
+            || command.only_changed_lines
+            || !command.lines.is_empty()
+        {
+            tracing::error!(
+                "Can't use `--watch` with `--changed-since`, `--staged`, `--only-changed-lines`, or `--lines`"
+            );
+            return Some(ExitStatus::Error);
+        }
+    }
+
+    if !command.lines.is_empty() {
+        if command.only_changed_lines {
+            tracing::error!("Can't use `--lines` and `--only-changed-lines` together");
+            return Some(ExitStatus::Error);
+        }
+
+        let is_single_file = match command.paths.as_slice() {
+            [] => command.stdin_file_path.is_some(),
+            [path] => !path.is_dir(),
+            _ => false,
+        };
+
+        if !is_single_file {
+            tracing::error!("Can only use `--lines` when formatting a single file");
+            return Some(ExitStatus::Error);
+        }
+    }
+
     None
 }
 
+/// Should formatted code be verified with [workspace::format::verify_formatted()]?
+///
+/// Verification is on by default in CI, where a formatter bug would go unnoticed, and
+/// off otherwise, where its cost is more noticeable.
+fn resolve_verify(command: &FormatCommand) -> bool {
+    if command.verify {
+        return true;
+    }
+    if command.no_verify {
+        return false;
+    }
+    is_ci()
+}
+
+/// Are we running in a CI environment?
+///
+/// Most CI providers, like GitHub Actions, set `CI=true`.
+fn is_ci() -> bool {
+    std::env::var_os("CI")
+        .is_some_and(|value| !value.is_empty() && value != "false" && value != "0")
+}
+
 impl FormatMode {
     fn from_command(command: &FormatCommand) -> Self {
         if command.check {
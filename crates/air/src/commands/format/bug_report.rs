@@ -0,0 +1,257 @@
+//! Isolate formatter panics to the file that caused them, and write bug reports
+//!
+//! A panic while formatting one file is caught and reported as an error for that
+//! file, so that the remaining files are still formatted. With `--bug-report <dir>`,
+//! everything needed to reproduce the panic is written to a self-contained directory
+//! that can be attached to an issue.
+
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Once;
+
+use workspace::editorconfig::EditorConfigCache;
+use workspace::format::LineRange;
+use workspace::settings::FormatSettings;
+use workspace::toml::format_options_to_air_toml;
+
+/// A panic caught by [catch_panic()]
+#[derive(Debug)]
+pub(super) struct Panic {
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+}
+
+thread_local! {
+    /// Whether panics on this thread are currently caught by [catch_panic()]
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+
+    /// The last panic caught on this thread, as recorded by the panic hook
+    static CAUGHT: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, catching any panic it raises
+///
+/// The panic message isn't printed by the default panic hook, it is up to the caller
+/// to report the returned [Panic].
+pub(super) fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    install_panic_hook();
+
+    CATCHING.set(true);
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(false);
+
+    result.map_err(|payload| {
+        CAUGHT.take().unwrap_or_else(|| Panic {
+            message: payload_message(payload.as_ref()),
+            location: None,
+            backtrace: String::new(),
+        })
+    })
+}
+
+/// Install a panic hook that records the panics caught by [catch_panic()], including
+/// their backtrace, which is only available from within the hook
+///
+/// Other panics are forwarded to the previous hook.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info| {
+            if !CATCHING.get() {
+                return previous(info);
+            }
+
+            CAUGHT.set(Some(Panic {
+                message: payload_message(info.payload()),
+                location: info.location().map(ToString::to_string),
+                backtrace: Backtrace::force_capture().to_string(),
+            }));
+        }));
+    });
+}
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Box<dyn Any>")
+    }
+}
+
+impl Display for Panic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{message} at {location}", message = self.message),
+            None => write!(f, "{message}", message = self.message),
+        }
+    }
+}
+
+/// Write a bug report for the `panic` raised while formatting `path` into a new
+/// directory within `directory`
+///
+/// The bug report contains a copy of the file, an `air.toml` with the settings that
+/// the file was formatted with, and a `README.md` with the Air version, the command
+/// that reproduces the panic, and the backtrace. Returns the path of the bug report.
+pub(super) fn write_bug_report(
+    directory: &Path,
+    path: &Path,
+    settings: &FormatSettings,
+    editorconfig: &EditorConfigCache,
+    lines: Option<&[LineRange]>,
+    panic: &Panic,
+) -> io::Result<PathBuf> {
+    let source = std::fs::read(path)?;
+
+    // Resolve the settings that depend on the file, like `line-ending = "auto"`
+    let options = settings.to_format_options_for_path_with(
+        &String::from_utf8_lossy(&source),
+        path,
+        &editorconfig.resolve(path),
+    );
+
+    let file_name = path
+        .file_name()
+        .map_or_else(|| PathBuf::from("file.R"), PathBuf::from);
+
+    let report = create_report_directory(directory, &file_name)?;
+
+    std::fs::write(report.join(&file_name), &source)?;
+    std::fs::write(
+        report.join("air.toml"),
+        format_options_to_air_toml(&options, settings),
+    )?;
+
+    let mut command = format!("air format {file_name}", file_name = file_name.display());
+    for line in lines.into_iter().flatten() {
+        command.push_str(&format!(
+            " --lines {start}:{end}",
+            start = line.start(),
+            end = line.end()
+        ));
+    }
+
+    let readme = format!(
+        "\
+# Air bug report
+
+Air panicked while formatting `{path}`. Please attach this directory to an issue at
+<https://github.com/posit-dev/air/issues>, after checking that the file doesn't contain
+anything private.
+
+- Air version: {version}
+- Platform: {os} ({arch})
+
+## Reproduce
+
+From this directory, run:
+
+```sh
+{command}
+```
+
+## Panic
+
+```
+{panic}
+```
+
+## Backtrace
+
+```
+{backtrace}
+```
+",
+        path = path.display(),
+        version = env!("CARGO_PKG_VERSION"),
+        os = std::env::consts::OS,
+        arch = std::env::consts::ARCH,
+        backtrace = panic.backtrace.trim_end(),
+    );
+
+    std::fs::write(report.join("README.md"), readme)?;
+
+    Ok(report)
+}
+
+/// Create a new directory for a bug report within `directory`, named after the file
+///
+/// A numeric suffix is added when several files with the same name panic.
+fn create_report_directory(directory: &Path, file_name: &Path) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(directory)?;
+
+    let name = file_name.to_string_lossy();
+
+    for index in 1.. {
+        let report = if index == 1 {
+            directory.join(name.as_ref())
+        } else {
+            directory.join(format!("{name}-{index}"))
+        };
+
+        match std::fs::create_dir(&report) {
+            Ok(()) => return Ok(report),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    unreachable!("Ran out of bug report directory names")
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::TempDir;
+    use workspace::editorconfig::EditorConfigCache;
+    use workspace::settings::FormatSettings;
+
+    use crate::commands::format::bug_report::catch_panic;
+    use crate::commands::format::bug_report::write_bug_report;
+
+    #[test]
+    fn test_bug_report_reproduces_panic() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        let path = tempdir.join("test.R");
+        std::fs::write(&path, "1 + 1\n")?;
+
+        let panic = catch_panic(|| panic!("oh no")).unwrap_err();
+        assert!(panic.to_string().starts_with("oh no at "));
+
+        // Reports for files with the same name don't overwrite each other
+        let directory = tempdir.join("reports");
+        let settings = FormatSettings::default();
+        let editorconfig = EditorConfigCache::default();
+        let first = write_bug_report(&directory, &path, &settings, &editorconfig, None, &panic)?;
+        let second = write_bug_report(&directory, &path, &settings, &editorconfig, None, &panic)?;
+
+        assert_eq!(first, directory.join("test.R"));
+        assert_eq!(second, directory.join("test.R-2"));
+
+        assert_eq!(std::fs::read_to_string(first.join("test.R"))?, "1 + 1\n");
+        let air_toml = std::fs::read_to_string(first.join("air.toml"))?;
+        assert!(air_toml.contains("[format]"));
+        assert!(air_toml.contains("encoding = \"UTF-8\""));
+
+        let readme = std::fs::read_to_string(first.join("README.md"))?;
+        assert!(readme.contains(env!("CARGO_PKG_VERSION")));
+        assert!(readme.contains("air format test.R"));
+        assert!(readme.contains("oh no"));
+
+        Ok(())
+    }
+}
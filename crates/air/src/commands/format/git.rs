@@ -0,0 +1,359 @@
+//! Restrict `air format` to the files and lines that git reports as changed
+//!
+//! We shell out to the local `git` binary rather than linking a git library, so that
+//! the user's git configuration, worktrees, and index format are all respected.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use workspace::format::LineRange;
+
+/// The changes that git should report
+#[derive(Debug, Clone)]
+pub(crate) enum GitChanges {
+    /// Changes since the merge base of a reference and `HEAD`, including uncommitted
+    /// and untracked changes
+    Since(String),
+    /// Changes that are staged in the git index
+    ///
+    /// With `index`, the changed lines are the ones of the staged copy of each file, as
+    /// the index is formatted. Otherwise, the working tree copy is formatted, so the
+    /// changed lines are the ones of the working tree compared to `HEAD`.
+    Staged { index: bool },
+    /// Uncommitted and untracked changes
+    Uncommitted,
+}
+
+/// The files that git reports as changed, as canonical paths
+///
+/// Deleted files are not part of the set, and renamed files are only known by their
+/// new name.
+#[derive(Debug)]
+pub(crate) struct ChangedFiles {
+    /// The root of the git repository
+    root: PathBuf,
+
+    /// The changed lines of each file, or `None` when the whole file is considered
+    /// changed, like untracked files or when lines weren't requested
+    files: HashMap<PathBuf, Option<Vec<LineRange>>>,
+
+    /// Changed files that no longer exist in the working tree, like files that are
+    /// staged and then removed. These are joined to `root` but not canonicalized.
+    missing: Vec<PathBuf>,
+}
+
+impl ChangedFiles {
+    /// Collect the files with `changes`, along with their changed lines if `lines` is
+    /// set
+    pub(crate) fn new(changes: &GitChanges, lines: bool) -> anyhow::Result<Self> {
+        let root = toplevel()?;
+
+        // The files are compared against `diff_args`, and their lines against
+        // `lines_args`, as these must be the lines of the copy that is formatted
+        let (mut diff_args, mut lines_args, untracked) = match changes {
+            GitChanges::Since(reference) => {
+                let merge_base =
+                    git(&root, &["merge-base", reference, "HEAD"]).with_context(|| {
+                        format!("Failed to find the merge base of `{reference}` and `HEAD`")
+                    })?;
+                let merge_base = String::from_utf8_lossy(&merge_base).trim().to_string();
+                (vec![merge_base.clone()], vec![merge_base], true)
+            }
+            GitChanges::Staged { index: true } => (
+                vec![String::from("--cached")],
+                vec![String::from("--cached")],
+                false,
+            ),
+            GitChanges::Staged { index: false } => (
+                vec![String::from("--cached")],
+                vec![String::from("HEAD")],
+                false,
+            ),
+            GitChanges::Uncommitted => {
+                (vec![String::from("HEAD")], vec![String::from("HEAD")], true)
+            }
+        };
+
+        for args in [&mut diff_args, &mut lines_args] {
+            args.extend(["--find-renames", "--diff-filter=d"].map(String::from));
+        }
+
+        let mut files = HashMap::new();
+
+        let changed = git_diff(&root, &diff_args, &["--name-only", "-z"])?;
+        let mut changed_lines = if lines {
+            let diff = git_diff(&root, &lines_args, &["-U0", "--no-prefix", "--no-color"])?;
+            parse_changed_lines(&root, &String::from_utf8_lossy(&diff))
+        } else {
+            HashMap::new()
+        };
+
+        let (changed, missing) = collect_paths(&root, &changed);
+
+        for path in changed {
+            // Files without a resolved `+++` header, like ones with only a mode change,
+            // aren't in `changed_lines`. Rather than risking skipping their changed
+            // lines, they are formatted entirely.
+            let lines = lines.then(|| changed_lines.remove(&path)).flatten();
+            files.insert(path, lines);
+        }
+
+        if untracked {
+            let untracked = git(
+                &root,
+                &[
+                    "ls-files",
+                    "--others",
+                    "--exclude-standard",
+                    "--full-name",
+                    "-z",
+                ],
+            )?;
+
+            let (untracked, _) = collect_paths(&root, &untracked);
+
+            for path in untracked {
+                files.insert(path, None);
+            }
+        }
+
+        tracing::debug!("Found {n} changed file(s)", n = files.len());
+
+        Ok(Self {
+            root,
+            files,
+            missing,
+        })
+    }
+
+    /// The root of the git repository, as a canonical path
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Is the file at `path` part of the changed files?
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        std::fs::canonicalize(path).is_ok_and(|path| self.files.contains_key(&path))
+    }
+
+    /// The changed files that no longer exist in the working tree
+    pub(crate) fn missing(&self) -> &[PathBuf] {
+        &self.missing
+    }
+
+    /// The changed lines of the file at `path`, or `None` if the whole file is
+    /// considered changed
+    pub(crate) fn lines(&self, path: &Path) -> Option<&[LineRange]> {
+        let path = std::fs::canonicalize(path).ok()?;
+        self.files.get(&path)?.as_deref()
+    }
+}
+
+/// The root of the git repository containing the current working directory, as a
+/// canonical path
+fn toplevel() -> anyhow::Result<PathBuf> {
+    let root = git(Path::new("."), &["rev-parse", "--show-toplevel"])
+        .context("Failed to find a git repository")?;
+    let root = String::from_utf8_lossy(&root);
+    let root = PathBuf::from(root.trim());
+    std::fs::canonicalize(&root).with_context(|| {
+        format!(
+            "Failed to resolve the git repository at '{root}'",
+            root = root.display()
+        )
+    })
+}
+
+/// Collect the NUL separated paths of `output`, which are relative to `root`
+///
+/// Returns the canonical paths of the files that exist on disk, and the paths of the
+/// ones that don't, like files that are staged and then removed from the working tree.
+fn collect_paths(root: &Path, output: &[u8]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut paths = Vec::new();
+    let mut missing = Vec::new();
+
+    for path in output.split(|byte| *byte == b'\0') {
+        if path.is_empty() {
+            continue;
+        }
+
+        let path = root.join(&*String::from_utf8_lossy(path));
+
+        match std::fs::canonicalize(&path) {
+            Ok(path) if path.is_file() => paths.push(path),
+            Ok(_) => {}
+            Err(_) => missing.push(path),
+        }
+    }
+
+    (paths, missing)
+}
+
+/// Parse the added and modified lines of each file of a `-U0 --no-prefix` diff
+///
+/// Only the new side of each hunk is considered. Hunks that only delete lines don't
+/// have any lines to format on the new side, so they are skipped.
+fn parse_changed_lines(root: &Path, diff: &str) -> HashMap<PathBuf, Vec<LineRange>> {
+    let mut changed_lines: HashMap<PathBuf, Vec<LineRange>> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+
+    // Added lines that start with `++` look like file headers, so we only look for
+    // headers between `diff --git` and the first hunk of each file
+    let mut in_header = false;
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_header = true;
+            current = None;
+            continue;
+        }
+
+        if in_header && let Some(path) = line.strip_prefix("+++ ") {
+            // `/dev/null` for deleted files, which aren't part of the diff anyway. Git
+            // appends a tab to paths that contain spaces.
+            let path = unquote_path(path.trim_end_matches('\t'));
+            current = std::fs::canonicalize(root.join(path)).ok();
+
+            // Files that only delete lines have no changed lines, but are still known
+            if let Some(path) = &current {
+                changed_lines.entry(path.clone()).or_default();
+            }
+
+            continue;
+        }
+
+        let Some(hunk) = line.strip_prefix("@@ ") else {
+            continue;
+        };
+        in_header = false;
+
+        let Some(path) = &current else {
+            continue;
+        };
+
+        // `@@ -old_start[,old_count] +new_start[,new_count] @@`
+        let Some(new) = hunk
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix('+'))
+        else {
+            continue;
+        };
+        let (start, count) = match new.split_once(',') {
+            Some((start, count)) => (start.parse(), count.parse()),
+            None => (new.parse(), Ok(1)),
+        };
+        let (Ok(start), Ok(count)) = (start, count) else {
+            continue;
+        };
+
+        if count == 0 {
+            continue;
+        }
+
+        if let Some(lines) = LineRange::new(start, start + count - 1) {
+            changed_lines.entry(path.clone()).or_default().push(lines);
+        }
+    }
+
+    changed_lines
+}
+
+/// Unquote a path of a diff header that git quoted C-style, like `"a\"b.R"`
+///
+/// Git quotes paths that contain special characters, like quotes, backslashes, or
+/// control characters. Non-ASCII characters are also quoted as octal escapes unless
+/// `core.quotePath` is disabled. Paths that aren't quoted are returned as is.
+fn unquote_path(path: &str) -> String {
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut iter = quoted.bytes();
+
+    while let Some(byte) = iter.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let Some(escaped) = iter.next() else {
+            break;
+        };
+
+        let byte = match escaped {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0B,
+            b'f' => 0x0C,
+            b'r' => b'\r',
+            // Octal escapes always have 3 digits, like `\303\251` for `é`
+            b'0'..=b'7' => {
+                let digits = [Some(escaped), iter.next(), iter.next()];
+                digits.into_iter().flatten().fold(0u8, |value, digit| {
+                    value.wrapping_mul(8).wrapping_add(digit.wrapping_sub(b'0'))
+                })
+            }
+            // `\"` and `\\`
+            other => other,
+        };
+
+        bytes.push(byte);
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Run `git diff` with the `diff_args` describing the changes, and the `format_args`
+/// describing the output
+fn git_diff(root: &Path, diff_args: &[String], format_args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let args: Vec<&str> = ["diff", "--no-ext-diff"]
+        .into_iter()
+        .chain(format_args.iter().copied())
+        .chain(diff_args.iter().map(String::as_str))
+        .collect();
+
+    git(root, &args)
+}
+
+/// Run `git` with `args` from `directory`, returning its standard output
+pub(super) fn git(directory: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    // Non-ASCII paths aren't quoted, so that they match the paths on disk
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .context("Failed to run `git`, is it installed?")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git {args}` failed: {stderr}",
+            args = args.join(" "),
+            stderr = String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::unquote_path;
+
+    #[test]
+    fn test_unquote_path() {
+        assert_eq!(unquote_path("R/test.R"), "R/test.R");
+        assert_eq!(unquote_path("\"R/caf\\303\\251.R\""), "R/café.R");
+        assert_eq!(unquote_path("\"R/a\\\"b\\\\c\\td.R\""), "R/a\"b\\c\td.R");
+    }
+}
@@ -0,0 +1,396 @@
+//! Format the staged contents of files in the git index with `--staged --write-index`
+//!
+//! Partially staged files, like ones staged with `git add -p`, have different contents
+//! in the index and in the working tree. Formatting and restaging the working tree copy
+//! would sneak unstaged changes into the commit, so we format the staged blobs instead
+//! and write the formatted blobs back to the index. The same formatting changes are
+//! then merged into the working tree copy, when that can be done without conflicts.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::io::Write;
+use std::io::stderr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use colored::Colorize;
+use fs::has_r_extension;
+use fs::relativize_path;
+use itertools::Itertools;
+use thiserror::Error;
+use workspace::discovery;
+use workspace::discovery::DiscoveredSettings;
+use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::editorconfig::EditorConfigCache;
+use workspace::encoding::decode;
+use workspace::format::FormatSourceError;
+use workspace::format::FormattedSource;
+use workspace::format::VerifyError;
+use workspace::resolve::PathResolver;
+use workspace::settings::FormatSettings;
+
+use crate::ExitStatus;
+use crate::commands::format::git::ChangedFiles;
+use crate::commands::format::git::git;
+use crate::commands::format::paths::discover_paths;
+use crate::commands::format::write::FileStamp;
+use crate::commands::format::write::write_atomic;
+
+#[derive(Error, Debug)]
+enum FormatIndexError {
+    Format(PathBuf, FormatSourceError),
+    Verify(PathBuf, VerifyError),
+    Git(PathBuf, anyhow::Error),
+    Write(PathBuf, io::Error),
+    Ignore(#[from] ignore::Error),
+}
+
+/// A staged file that was skipped, or whose working tree copy was left untouched
+#[derive(Debug)]
+struct SkippedPath {
+    path: PathBuf,
+    reason: SkippedReason,
+}
+
+#[derive(Debug)]
+enum SkippedReason {
+    /// The file has merge conflicts, so it has no single staged blob to format
+    Unmerged,
+    /// The file is staged as something other than a regular file, like a symlink
+    NotRegularFile,
+    /// The unstaged changes of the working tree copy overlap with the formatting changes
+    Conflict,
+    /// The file is staged but was deleted from the working tree
+    Missing,
+}
+
+/// An entry of the git index
+#[derive(Debug)]
+struct IndexEntry {
+    mode: String,
+    object: String,
+}
+
+pub(crate) fn format(
+    paths: Vec<PathBuf>,
+    exclude: discovery::Exclude,
+    include: discovery::Include,
+    changed: &ChangedFiles,
+    verify: bool,
+) -> anyhow::Result<ExitStatus> {
+    // Files that aren't covered by a project `air.toml` use the user level one, if any
+    let fallback = discover_user_settings()?.unwrap_or_default();
+    let mut resolver = PathResolver::new(fallback);
+
+    for DiscoveredSettings {
+        directory,
+        settings,
+    } in discover_settings(&paths)?
+    {
+        resolver.add(&directory, settings);
+    }
+
+    let editorconfig = EditorConfigCache::default();
+
+    let mut skipped = Vec::new();
+    let mut errors = Vec::new();
+
+    // Staged files that were deleted from the working tree can't be discovered, but
+    // their staged contents would be committed unformatted, so we report them
+    let roots: Vec<PathBuf> = paths
+        .iter()
+        .filter_map(|path| std::fs::canonicalize(path).ok())
+        .collect();
+
+    for path in changed.missing() {
+        if has_r_extension(path) && roots.iter().any(|root| path.starts_with(root)) {
+            skipped.push(SkippedPath {
+                path: path.clone(),
+                reason: SkippedReason::Missing,
+            });
+        }
+    }
+
+    let paths = discover_paths(&paths, &resolver, exclude, include, Some(changed));
+
+    for path in paths {
+        let path = match path {
+            Ok(path) => path,
+            Err(err) => {
+                errors.push(FormatIndexError::from(err));
+                continue;
+            }
+        };
+
+        let settings = resolver.resolve_or_fallback(&path);
+
+        match format_index_path(changed, &path, &settings.format, &editorconfig, verify) {
+            Ok(Some(reason)) => skipped.push(SkippedPath { path, reason }),
+            Ok(None) => {}
+            Err(err) => errors.push(err),
+        }
+    }
+
+    for error in &errors {
+        tracing::error!("{error}");
+    }
+
+    inform_skipped(&skipped, &mut stderr().lock())?;
+
+    if errors.is_empty() {
+        Ok(ExitStatus::Success)
+    } else {
+        Ok(ExitStatus::Error)
+    }
+}
+
+fn inform_skipped(paths: &[SkippedPath], f: &mut impl Write) -> io::Result<()> {
+    for skipped in paths.iter().sorted_unstable_by(|x, y| x.path.cmp(&y.path)) {
+        let path = relativize_path(&skipped.path);
+        let path = path.underline();
+
+        match skipped.reason {
+            SkippedReason::Unmerged => writeln!(f, "Skipped {path}: it has merge conflicts")?,
+            SkippedReason::NotRegularFile => {
+                writeln!(f, "Skipped {path}: it isn't staged as a regular file")?;
+            }
+            SkippedReason::Conflict => writeln!(
+                f,
+                "Skipped the working tree copy of {path}: unstaged changes conflict with the formatting changes"
+            )?,
+            SkippedReason::Missing => writeln!(
+                f,
+                "Skipped {path}: it is staged but was deleted from the working tree"
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Format the staged blob of `path`, and write it back to the index
+///
+/// Returns the reason `path` or its working tree copy were left untouched, if any.
+fn format_index_path(
+    changed: &ChangedFiles,
+    path: &Path,
+    settings: &FormatSettings,
+    editorconfig: &EditorConfigCache,
+    verify: bool,
+) -> Result<Option<SkippedReason>, FormatIndexError> {
+    tracing::trace!(
+        "Formatting the staged contents of {path}",
+        path = path.display()
+    );
+
+    let root = changed.root();
+    let git_error = |err| FormatIndexError::Git(path.to_path_buf(), err);
+
+    // Paths in the index are always relative to the root and `/` separated
+    let name = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .join("/");
+
+    let entry = match index_entry(root, &name).map_err(git_error)? {
+        Ok(entry) => entry,
+        Err(reason) => return Ok(Some(reason)),
+    };
+
+    let old = git(root, &["cat-file", "blob", &entry.object]).map_err(git_error)?;
+    let (old, encoding) = decode(old, settings.encoding)
+        .context("Failed to decode the staged contents")
+        .map_err(git_error)?;
+
+    let options = settings.to_format_options_for_path_with(&old, path, &editorconfig.resolve(path));
+
+    let lines = changed.lines(path);
+
+    let new = match lines {
+        Some(lines) => workspace::format::format_source_lines(&old, lines, options.clone()),
+        None => workspace::format::format_source(&old, options.clone()),
+    };
+    let new = new.map_err(|error| FormatIndexError::Format(path.to_path_buf(), error))?;
+
+    let FormattedSource::Changed(new) = new else {
+        return Ok(None);
+    };
+
+    if verify {
+        workspace::format::verify_formatted(&old, &new, options, lines.is_none())
+            .map_err(|error| FormatIndexError::Verify(path.to_path_buf(), error))?;
+    }
+
+    let directory = tempfile::tempdir()
+        .context("Failed to create a temporary directory")
+        .map_err(git_error)?;
+    let old_path = directory.path().join("old");
+    let new_path = directory.path().join("new");
+
+    // Both are encoded like the staged blob, so that the working tree copy can be merged
+    let old = encoding.encode(&old).map_err(|err| git_error(err.into()))?;
+    let new = encoding.encode(&new).map_err(|err| git_error(err.into()))?;
+
+    std::fs::write(&old_path, &old).map_err(|err| git_error(err.into()))?;
+    std::fs::write(&new_path, &new).map_err(|err| git_error(err.into()))?;
+
+    // Write the formatted blob as is, the staged contents have already been through
+    // any clean filters
+    let object = git(
+        root,
+        &[
+            "hash-object",
+            "-w",
+            "--no-filters",
+            &new_path.to_string_lossy(),
+        ],
+    )
+    .map_err(git_error)?;
+    let object = String::from_utf8_lossy(&object).trim().to_string();
+
+    git(
+        root,
+        &[
+            "update-index",
+            "--cacheinfo",
+            &format!("{mode},{object},{name}", mode = entry.mode),
+        ],
+    )
+    .map_err(git_error)?;
+
+    // Stamp the working tree copy before merging, so that changes made while merging
+    // are detected
+    let metadata = std::fs::metadata(path).map_err(|err| git_error(err.into()))?;
+    let stamp = FileStamp::new(&metadata);
+
+    // Apply the formatting changes to the working tree copy as a 3-way merge, so that
+    // unstaged changes are kept. Without any, this is the formatted blob itself.
+    let Some(merged) = merge_file(path, &old_path, &new_path).map_err(git_error)? else {
+        return Ok(Some(SkippedReason::Conflict));
+    };
+
+    let current = std::fs::read(path).map_err(|err| git_error(err.into()))?;
+
+    if merged != current {
+        write_atomic(path, &merged, stamp)
+            .map_err(|err| FormatIndexError::Write(path.to_path_buf(), err))?;
+    }
+
+    Ok(None)
+}
+
+/// Look up the staged entry of `name` in the index
+///
+/// Returns the reason the file can't be formatted if it has merge conflicts or isn't
+/// staged as a regular file.
+fn index_entry(root: &Path, name: &str) -> anyhow::Result<Result<IndexEntry, SkippedReason>> {
+    let output = git(
+        root,
+        &[
+            "ls-files",
+            "--stage",
+            "-z",
+            "--",
+            &format!(":(literal){name}"),
+        ],
+    )?;
+    let output = String::from_utf8_lossy(&output);
+
+    // `<mode> <object> <stage>\t<name>`, with one entry per stage for unmerged files
+    let entries: Vec<&str> = output
+        .split('\0')
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    let entry = match entries.as_slice() {
+        [] => anyhow::bail!("`{name}` isn't in the git index"),
+        [entry] => entry,
+        _ => return Ok(Err(SkippedReason::Unmerged)),
+    };
+
+    let (info, _) = entry
+        .split_once('\t')
+        .with_context(|| format!("Unexpected `git ls-files` output: {entry}"))?;
+
+    let [mode, object, stage] = info.split(' ').collect::<Vec<_>>()[..] else {
+        anyhow::bail!("Unexpected `git ls-files` output: {entry}");
+    };
+
+    if stage != "0" {
+        return Ok(Err(SkippedReason::Unmerged));
+    }
+
+    if !matches!(mode, "100644" | "100755") {
+        return Ok(Err(SkippedReason::NotRegularFile));
+    }
+
+    Ok(Ok(IndexEntry {
+        mode: mode.to_string(),
+        object: object.to_string(),
+    }))
+}
+
+/// Merge the changes from `old` to `new` into the file at `path`
+///
+/// Returns the merged contents, or `None` if the changes conflict.
+fn merge_file(path: &Path, old: &Path, new: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    let output = Command::new("git")
+        .arg("merge-file")
+        .arg("-p")
+        .arg("--quiet")
+        .arg(path)
+        .arg(old)
+        .arg(new)
+        .output()
+        .context("Failed to run `git`, is it installed?")?;
+
+    // The exit status is the number of conflicts, capped at 127. Errors are reported
+    // with a negative status, which is seen as 128 or more (e.g. 255 on Unix).
+    match output.status.code() {
+        Some(0) => Ok(Some(output.stdout)),
+        Some(1..=127) => Ok(None),
+        _ => anyhow::bail!(
+            "`git merge-file` failed: {stderr}",
+            stderr = String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+impl Display for FormatIndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format(path, err) => write!(
+                f,
+                "Failed to format {path}: {err}",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Verify(path, err) => write!(
+                f,
+                "Internal error while formatting the staged contents of {path}: {err}. The file was left unchanged, please report this at https://github.com/posit-dev/air/issues",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Git(path, err) => write!(
+                f,
+                "Failed to format the staged contents of {path}: {err:#}",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Write(path, err) => write!(
+                f,
+                "Failed to write {path}: {err}",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Ignore(err) => write!(
+                f,
+                "Encountered error: {err}",
+                err = err
+                    .io_error()
+                    .map_or_else(|| err.to_string(), std::string::ToString::to_string)
+            ),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io;
+use std::io::Read;
 use std::io::Write;
 use std::io::stderr;
 use std::path::Path;
@@ -15,30 +16,91 @@ use workspace::discovery;
 use workspace::discovery::DiscoveredSettings;
 use workspace::discovery::discover_r_file_paths;
 use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::editorconfig::EditorConfigCache;
+use workspace::encoding::DecodeError;
+use workspace::encoding::EncodeError;
+use workspace::encoding::decode;
 use workspace::format::FormatSourceError;
 use workspace::format::FormattedSource;
+use workspace::format::LineRange;
+use workspace::format::SourceLocation;
+use workspace::format::VerifyError;
+use workspace::format::find_non_ascii_strings;
 use workspace::resolve::PathResolver;
 use workspace::settings::FormatSettings;
 use workspace::settings::Settings;
 
 use crate::ExitStatus;
 use crate::commands::format::FormatMode;
+use crate::commands::format::bug_report::Panic;
+use crate::commands::format::bug_report::catch_panic;
+use crate::commands::format::bug_report::write_bug_report;
+use crate::commands::format::git::ChangedFiles;
+use crate::commands::format::write::FileStamp;
+use crate::commands::format::write::write_atomic;
 
 #[derive(Error, Debug)]
-enum FormatPathError {
+pub(super) enum FormatPathError {
     Format(PathBuf, FormatSourceError),
+    Verify(PathBuf, VerifyError),
+    Decode(PathBuf, DecodeError),
+    Encode(PathBuf, EncodeError),
+    /// A panic while formatting, along with the path of its bug report, if any
+    Panic(PathBuf, Panic, Option<PathBuf>),
     Read(PathBuf, io::Error),
     Write(PathBuf, io::Error),
     Ignore(#[from] ignore::Error),
 }
 
+/// The result of formatting a file
+#[derive(Debug)]
+pub(super) enum FormattedFile {
+    /// The formatted contents, encoded like the original file, along with the state the
+    /// file was read in and its decoded original contents
+    Changed {
+        contents: Vec<u8>,
+        stamp: FileStamp,
+        source: String,
+    },
+    Unchanged,
+}
+
+/// A path that would be reformatted by `air format --check`
+#[derive(Debug)]
+struct ChangedPath {
+    path: PathBuf,
+
+    /// Strings with non-ASCII characters, only reported with `ascii-strings = true`
+    non_ascii_strings: Vec<SourceLocation>,
+}
+
+/// Options that apply to all the files formatted by [format()]
+#[derive(Debug)]
+pub(crate) struct FormatPathsOptions<'a> {
+    pub(crate) exclude: discovery::Exclude,
+    pub(crate) include: discovery::Include,
+
+    /// The files that git reports as changed, when only these should be formatted
+    pub(crate) changed: Option<&'a ChangedFiles>,
+
+    /// The lines supplied with `--lines`, if any
+    pub(crate) lines: &'a [LineRange],
+
+    pub(crate) verify: bool,
+
+    /// The directory to write bug reports to when formatting panics, if any
+    pub(crate) bug_report: Option<&'a Path>,
+}
+
 pub(crate) fn format(
     paths: Vec<PathBuf>,
     mode: FormatMode,
-    exclude: discovery::Exclude,
-    include: discovery::Include,
+    options: &FormatPathsOptions,
 ) -> anyhow::Result<ExitStatus> {
-    let mut resolver = PathResolver::new(Settings::default());
+    // Files that aren't covered by a project `air.toml` use the user level one, if any
+    let fallback = discover_user_settings()?.unwrap_or_default();
+    let mut resolver = PathResolver::new(fallback);
 
     for DiscoveredSettings {
         directory,
@@ -48,9 +110,11 @@ pub(crate) fn format(
         resolver.add(&directory, settings);
     }
 
+    let editorconfig = EditorConfigCache::default();
+
     match mode {
         FormatMode::Write => {
-            let errors = format_paths_write(&paths, &resolver, exclude, include);
+            let errors = format_paths_write(&paths, &resolver, &editorconfig, options);
 
             for error in &errors {
                 tracing::error!("{error}");
@@ -63,7 +127,7 @@ pub(crate) fn format(
             }
         }
         FormatMode::Check => {
-            let (paths, errors) = format_paths_check(&paths, &resolver, exclude, include);
+            let (paths, errors) = format_paths_check(&paths, &resolver, &editorconfig, options);
 
             for error in &errors {
                 tracing::error!("{error}");
@@ -84,13 +148,15 @@ pub(crate) fn format(
     }
 }
 
-fn inform_changed(paths: &[PathBuf], f: &mut impl Write) -> io::Result<()> {
-    for path in paths.iter().sorted_unstable() {
-        writeln!(
-            f,
-            "Would reformat: {path}",
-            path = relativize_path(path).underline()
-        )?;
+fn inform_changed(paths: &[ChangedPath], f: &mut impl Write) -> io::Result<()> {
+    for changed in paths.iter().sorted_unstable_by(|x, y| x.path.cmp(&y.path)) {
+        let path = relativize_path(&changed.path);
+
+        writeln!(f, "Would reformat: {path}", path = path.underline())?;
+
+        for SourceLocation { line, column } in &changed.non_ascii_strings {
+            writeln!(f, "  {path}:{line}:{column}: Non-ASCII character in string")?;
+        }
     }
     Ok(())
 }
@@ -98,17 +164,34 @@ fn inform_changed(paths: &[PathBuf], f: &mut impl Write) -> io::Result<()> {
 fn format_paths_write<P: AsRef<Path>>(
     paths: &[P],
     resolver: &PathResolver<Settings>,
-    exclude: discovery::Exclude,
-    include: discovery::Include,
+    editorconfig: &EditorConfigCache,
+    options: &FormatPathsOptions,
 ) -> Vec<FormatPathError> {
-    let paths = discover_r_file_paths(paths, resolver, discovery::Mode::Format, exclude, include);
+    let FormatPathsOptions {
+        exclude,
+        include,
+        changed,
+        lines,
+        verify,
+        bug_report,
+    } = *options;
+
+    let paths = discover_paths(paths, resolver, exclude, include, changed);
 
     paths
         .into_iter()
         .filter_map(|path| match path {
             Ok(path) => {
                 let settings = resolver.resolve_or_fallback(&path);
-                match format_path(&path, &settings.format) {
+                let lines = lines_to_format(&path, lines, changed);
+                match catch_format_path(
+                    &path,
+                    &settings.format,
+                    editorconfig,
+                    lines,
+                    verify,
+                    bug_report,
+                ) {
                     Ok(formatted) => match write_path(&path, formatted) {
                         Ok(()) => None,
                         Err(err) => Some(FormatPathError::Write(path, err)),
@@ -124,18 +207,35 @@ fn format_paths_write<P: AsRef<Path>>(
 fn format_paths_check<P: AsRef<Path>>(
     paths: &[P],
     resolver: &PathResolver<Settings>,
-    exclude: discovery::Exclude,
-    include: discovery::Include,
-) -> (Vec<PathBuf>, Vec<FormatPathError>) {
-    let paths = discover_r_file_paths(paths, resolver, discovery::Mode::Format, exclude, include);
+    editorconfig: &EditorConfigCache,
+    options: &FormatPathsOptions,
+) -> (Vec<ChangedPath>, Vec<FormatPathError>) {
+    let FormatPathsOptions {
+        exclude,
+        include,
+        changed,
+        lines,
+        verify,
+        bug_report,
+    } = *options;
+
+    let paths = discover_paths(paths, resolver, exclude, include, changed);
 
     paths
         .into_iter()
         .filter_map(|path| match path {
             Ok(path) => {
                 let settings = resolver.resolve_or_fallback(&path);
-                match format_path(&path, &settings.format) {
-                    Ok(file) => check_path(&path, file).map(Ok),
+                let lines = lines_to_format(&path, lines, changed);
+                match catch_format_path(
+                    &path,
+                    &settings.format,
+                    editorconfig,
+                    lines,
+                    verify,
+                    bug_report,
+                ) {
+                    Ok(file) => check_path(&path, file, &settings.format).map(Ok),
                     Err(err) => Some(Err(err)),
                 }
             }
@@ -147,38 +247,166 @@ fn format_paths_check<P: AsRef<Path>>(
         })
 }
 
-fn format_path<P: AsRef<Path>>(
+/// Discover the R files of `paths`, only keeping the `changed` ones when supplied
+pub(super) fn discover_paths<P: AsRef<Path>>(
+    paths: &[P],
+    resolver: &PathResolver<Settings>,
+    exclude: discovery::Exclude,
+    include: discovery::Include,
+    changed: Option<&ChangedFiles>,
+) -> Vec<Result<PathBuf, ignore::Error>> {
+    let paths = discover_r_file_paths(paths, resolver, discovery::Mode::Format, exclude, include);
+
+    let Some(changed) = changed else {
+        return paths;
+    };
+
+    paths
+        .into_iter()
+        .filter(|path| match path {
+            Ok(path) => changed.contains(path),
+            Err(_) => true,
+        })
+        .collect()
+}
+
+/// The lines to format in `path`, or `None` to format the whole file
+///
+/// Lines supplied with `--lines` take precedence over the changed lines reported by git.
+fn lines_to_format<'lines>(
+    path: &Path,
+    lines: &'lines [LineRange],
+    changed: Option<&'lines ChangedFiles>,
+) -> Option<&'lines [LineRange]> {
+    if !lines.is_empty() {
+        return Some(lines);
+    }
+    changed.and_then(|changed| changed.lines(path))
+}
+
+pub(super) fn format_path<P: AsRef<Path>>(
     path: P,
     settings: &FormatSettings,
-) -> std::result::Result<FormattedSource, FormatPathError> {
+    editorconfig: &EditorConfigCache,
+    lines: Option<&[LineRange]>,
+    verify: bool,
+) -> std::result::Result<FormattedFile, FormatPathError> {
     let path = path.as_ref();
     tracing::trace!("Formatting {path}", path = path.display());
 
-    let old = std::fs::read_to_string(path)
-        .map_err(|error| FormatPathError::Read(path.to_path_buf(), error))?;
+    let read_error = |error| FormatPathError::Read(path.to_path_buf(), error);
+
+    // Stamp the file before reading it, so that changes made while reading are detected
+    let mut file = std::fs::File::open(path).map_err(read_error)?;
+    let stamp = FileStamp::new(&file.metadata().map_err(read_error)?);
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(read_error)?;
+
+    let (old, encoding) = decode(bytes, settings.encoding)
+        .map_err(|error| FormatPathError::Decode(path.to_path_buf(), error))?;
+
+    let options = settings.to_format_options_for_path_with(&old, path, &editorconfig.resolve(path));
+
+    let new = match lines {
+        Some(lines) => workspace::format::format_source_lines(&old, lines, options.clone()),
+        None => workspace::format::format_source(&old, options.clone()),
+    };
+    let new = new.map_err(|error| FormatPathError::Format(path.to_path_buf(), error))?;
+
+    let FormattedSource::Changed(new) = new else {
+        return Ok(FormattedFile::Unchanged);
+    };
+
+    if verify {
+        // Formatting a range of lines is only idempotent for those lines
+        workspace::format::verify_formatted(&old, &new, options, lines.is_none())
+            .map_err(|error| FormatPathError::Verify(path.to_path_buf(), error))?;
+    }
+
+    let contents = encoding
+        .encode(&new)
+        .map_err(|error| FormatPathError::Encode(path.to_path_buf(), error))?
+        .into_owned();
 
-    let options = settings.to_format_options(&old);
+    Ok(FormattedFile::Changed {
+        contents,
+        stamp,
+        source: old,
+    })
+}
 
-    let new = workspace::format::format_source(&old, options)
-        .map_err(|error| FormatPathError::Format(path.to_path_buf(), error))?;
+/// Format `path` like [format_path()], reporting panics as [FormatPathError::Panic]
+///
+/// A panic only fails this file, so that the remaining files are still formatted. With
+/// `bug_report`, a bug report is written for the panic within that directory.
+fn catch_format_path(
+    path: &Path,
+    settings: &FormatSettings,
+    editorconfig: &EditorConfigCache,
+    lines: Option<&[LineRange]>,
+    verify: bool,
+    bug_report: Option<&Path>,
+) -> std::result::Result<FormattedFile, FormatPathError> {
+    let panic = match catch_panic(|| format_path(path, settings, editorconfig, lines, verify)) {
+        Ok(result) => return result,
+        Err(panic) => panic,
+    };
+
+    let report = bug_report.and_then(|directory| {
+        match write_bug_report(directory, path, settings, editorconfig, lines, &panic) {
+            Ok(report) => Some(report),
+            Err(err) => {
+                tracing::error!(
+                    "Failed to write a bug report for {path}: {err}",
+                    path = relativize_path(path).underline()
+                );
+                None
+            }
+        }
+    });
 
-    Ok(new)
+    Err(FormatPathError::Panic(path.to_path_buf(), panic, report))
 }
 
 /// Returns `Ok(())` if the format results were successfully written back, otherwise
 /// returns an error
-fn write_path<P: AsRef<Path>>(path: P, formatted: FormattedSource) -> io::Result<()> {
+///
+/// The file is replaced atomically, see [write_atomic()].
+pub(super) fn write_path<P: AsRef<Path>>(path: P, formatted: FormattedFile) -> io::Result<()> {
     match formatted {
-        FormattedSource::Changed(changed) => std::fs::write(path, &changed),
-        FormattedSource::Unchanged => Ok(()),
+        FormattedFile::Changed {
+            contents, stamp, ..
+        } => write_atomic(path.as_ref(), &contents, stamp),
+        FormattedFile::Unchanged => Ok(()),
     }
 }
 
-/// Returns `Some(path)` if a change occurred, otherwise returns `None`
-fn check_path<P: AsRef<Path>>(path: P, formatted: FormattedSource) -> Option<PathBuf> {
+/// Returns `Some(changed)` if a change occurred, otherwise returns `None`
+///
+/// With `ascii-strings = true`, the location of the strings with non-ASCII characters
+/// is also reported, as those can't always be spotted in the diff.
+fn check_path<P: AsRef<Path>>(
+    path: P,
+    formatted: FormattedFile,
+    settings: &FormatSettings,
+) -> Option<ChangedPath> {
     match formatted {
-        FormattedSource::Changed(_) => Some(path.as_ref().to_path_buf()),
-        FormattedSource::Unchanged => None,
+        FormattedFile::Changed { source, .. } => {
+            let path = path.as_ref().to_path_buf();
+
+            let non_ascii_strings = if settings.ascii_strings.is_escape() {
+                find_non_ascii_strings(&source)
+            } else {
+                Vec::new()
+            };
+
+            Some(ChangedPath {
+                path,
+                non_ascii_strings,
+            })
+        }
+        FormattedFile::Unchanged => None,
     }
 }
 
@@ -190,11 +418,37 @@ impl Display for FormatPathError {
                 "Failed to format {path}: {err}",
                 path = relativize_path(path).underline(),
             ),
+            Self::Verify(path, err) => write!(
+                f,
+                "Internal error while formatting {path}: {err}. The file was left unchanged, please report this at https://github.com/posit-dev/air/issues",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Panic(path, panic, Some(report)) => write!(
+                f,
+                "Internal error while formatting {path}: Air panicked: {panic}. The file was left unchanged, please report this at https://github.com/posit-dev/air/issues and attach the bug report written to {report}",
+                path = relativize_path(path).underline(),
+                report = relativize_path(report).underline(),
+            ),
+            Self::Panic(path, panic, None) => write!(
+                f,
+                "Internal error while formatting {path}: Air panicked: {panic}. The file was left unchanged, please report this at https://github.com/posit-dev/air/issues, using `--bug-report <dir>` to collect the details",
+                path = relativize_path(path).underline(),
+            ),
             Self::Read(path, err) => write!(
                 f,
                 "Failed to read {path}: {err}",
                 path = relativize_path(path).underline(),
             ),
+            Self::Decode(path, err) => write!(
+                f,
+                "Failed to read {path}: {err}",
+                path = relativize_path(path).underline(),
+            ),
+            Self::Encode(path, err) => write!(
+                f,
+                "Failed to format {path}: {err}",
+                path = relativize_path(path).underline(),
+            ),
             Self::Ignore(err) => {
                 if let ignore::Error::WithPath { path, .. } = err {
                     write!(
@@ -10,8 +10,15 @@ use thiserror::Error;
 use workspace::discovery;
 use workspace::discovery::DiscoveredSettings;
 use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::editorconfig::EditorConfigCache;
+use workspace::encoding::DecodeError;
+use workspace::encoding::EncodeError;
+use workspace::encoding::decode;
 use workspace::format::FormatSourceError;
 use workspace::format::FormattedSource;
+use workspace::format::LineRange;
+use workspace::format::VerifyError;
 use workspace::resolve::PathResolver;
 use workspace::settings::FormatSettings;
 use workspace::settings::Settings;
@@ -21,16 +28,19 @@ use crate::commands::format::FormatMode;
 
 #[derive(Debug)]
 enum FormattedStdin {
-    /// Stdin was formatted.
-    Changed(String),
+    /// Stdin was formatted. The formatted code is encoded like stdin.
+    Changed(Vec<u8>),
     /// Stdin was unchanged.
-    Unchanged(String),
+    Unchanged(Vec<u8>),
 }
 
 #[derive(Error, Debug)]
 enum FormatStdinError {
     Format(FormatSourceError),
+    Verify(VerifyError),
     Read(io::Error),
+    Decode(DecodeError),
+    Encode(EncodeError),
     Write(io::Error),
 }
 
@@ -39,11 +49,15 @@ pub(crate) fn format(
     mode: FormatMode,
     exclude: discovery::Exclude,
     include: discovery::Include,
+    lines: &[LineRange],
+    verify: bool,
 ) -> anyhow::Result<ExitStatus> {
     // Normalize up front, relative to current working directory
     let path = fs::normalize_path(path);
 
-    let mut resolver = PathResolver::new(Settings::default());
+    // Files that aren't covered by a project `air.toml` use the user level one, if any
+    let fallback = discover_user_settings()?.unwrap_or_default();
+    let mut resolver = PathResolver::new(fallback);
 
     for DiscoveredSettings {
         directory,
@@ -53,40 +67,65 @@ pub(crate) fn format(
         resolver.add(&directory, settings);
     }
 
+    let editorconfig = EditorConfigCache::default();
+
     match mode {
-        FormatMode::Write => match format_stdin_write(&path, &resolver, exclude, include) {
-            Ok(()) => Ok(ExitStatus::Success),
-            Err(error) => {
-                tracing::error!("{error}");
-                Ok(ExitStatus::Error)
-            }
-        },
-        FormatMode::Check => match format_stdin_check(&path, &resolver, exclude, include) {
-            Ok(changed) => {
-                if changed {
-                    Ok(ExitStatus::Failure)
-                } else {
-                    Ok(ExitStatus::Success)
+        FormatMode::Write => {
+            match format_stdin_write(
+                &path,
+                &resolver,
+                &editorconfig,
+                exclude,
+                include,
+                lines,
+                verify,
+            ) {
+                Ok(()) => Ok(ExitStatus::Success),
+                Err(error) => {
+                    tracing::error!("{error}");
+                    Ok(ExitStatus::Error)
                 }
             }
-            Err(error) => {
-                tracing::error!("{error}");
-                Ok(ExitStatus::Error)
+        }
+        FormatMode::Check => {
+            match format_stdin_check(
+                &path,
+                &resolver,
+                &editorconfig,
+                exclude,
+                include,
+                lines,
+                verify,
+            ) {
+                Ok(changed) => {
+                    if changed {
+                        Ok(ExitStatus::Failure)
+                    } else {
+                        Ok(ExitStatus::Success)
+                    }
+                }
+                Err(error) => {
+                    tracing::error!("{error}");
+                    Ok(ExitStatus::Error)
+                }
             }
-        },
+        }
     }
 }
 
 fn format_stdin_write<P: AsRef<Path>>(
     path: P,
     resolver: &PathResolver<Settings>,
+    editorconfig: &EditorConfigCache,
     exclude: discovery::Exclude,
     include: discovery::Include,
+    lines: &[LineRange],
+    verify: bool,
 ) -> Result<(), FormatStdinError> {
     let settings = resolver.resolve_or_fallback(&path);
 
-    let formatted = if is_stdin_formattable(path, settings, exclude, include) {
-        format_stdin(&settings.format)?
+    let formatted = if is_stdin_formattable(&path, settings, exclude, include) {
+        format_stdin(path, &settings.format, editorconfig, lines, verify)?
     } else {
         asis_stdin()?
     };
@@ -98,24 +137,27 @@ fn format_stdin_write<P: AsRef<Path>>(
 
     std::io::stdout()
         .lock()
-        .write_all(buffer.as_bytes())
+        .write_all(&buffer)
         .map_err(FormatStdinError::Write)
 }
 
 fn format_stdin_check<P: AsRef<Path>>(
     path: P,
     resolver: &PathResolver<Settings>,
+    editorconfig: &EditorConfigCache,
     exclude: discovery::Exclude,
     include: discovery::Include,
+    lines: &[LineRange],
+    verify: bool,
 ) -> Result<bool, FormatStdinError> {
     let settings = resolver.resolve_or_fallback(&path);
 
-    if !is_stdin_formattable(path, settings, exclude, include) {
+    if !is_stdin_formattable(&path, settings, exclude, include) {
         // Don't even attempt to read from stdin, we know nothing will change
         return Ok(false);
     }
 
-    let formatted = format_stdin(&settings.format)?;
+    let formatted = format_stdin(path, &settings.format, editorconfig, lines, verify)?;
 
     match formatted {
         FormattedStdin::Changed(_) => Ok(true),
@@ -123,16 +165,45 @@ fn format_stdin_check<P: AsRef<Path>>(
     }
 }
 
-fn format_stdin(settings: &FormatSettings) -> Result<FormattedStdin, FormatStdinError> {
+/// Formats stdin as if it were the contents of `path`
+///
+/// `path` is used to look up the `.editorconfig` properties that apply to stdin. Stdin
+/// is decoded with the `encoding` of `settings`, and the formatted code is encoded the
+/// same way. When `lines` are supplied, only those lines are formatted. When `verify`
+/// is set, the formatted code is checked with [workspace::format::verify_formatted()].
+fn format_stdin<P: AsRef<Path>>(
+    path: P,
+    settings: &FormatSettings,
+    editorconfig: &EditorConfigCache,
+    lines: &[LineRange],
+    verify: bool,
+) -> Result<FormattedStdin, FormatStdinError> {
     tracing::trace!("Formatting stdin");
 
-    let old = read_stdin().map_err(FormatStdinError::Read)?;
-    let options = settings.to_format_options(&old);
-    let new = workspace::format::format_source(&old, options).map_err(FormatStdinError::Format)?;
+    let bytes = read_stdin().map_err(FormatStdinError::Read)?;
+    let (old, encoding) =
+        decode(bytes.clone(), settings.encoding).map_err(FormatStdinError::Decode)?;
+
+    let path = path.as_ref();
+    let options = settings.to_format_options_for_path_with(&old, path, &editorconfig.resolve(path));
+    let new = if lines.is_empty() {
+        workspace::format::format_source(&old, options.clone())
+    } else {
+        workspace::format::format_source_lines(&old, lines, options.clone())
+    };
+    let new = new.map_err(FormatStdinError::Format)?;
+
+    if verify && let FormattedSource::Changed(new) = &new {
+        workspace::format::verify_formatted(&old, new, options, lines.is_empty())
+            .map_err(FormatStdinError::Verify)?;
+    }
 
     match new {
-        FormattedSource::Changed(new) => Ok(FormattedStdin::Changed(new)),
-        FormattedSource::Unchanged => Ok(FormattedStdin::Unchanged(old)),
+        FormattedSource::Changed(new) => {
+            let new = encoding.encode(&new).map_err(FormatStdinError::Encode)?;
+            Ok(FormattedStdin::Changed(new.into_owned()))
+        }
+        FormattedSource::Unchanged => Ok(FormattedStdin::Unchanged(bytes)),
     }
 }
 
@@ -147,9 +218,9 @@ fn asis_stdin() -> Result<FormattedStdin, FormatStdinError> {
 /// Read from stdin
 ///
 /// Blocks until EOF is received!
-fn read_stdin() -> io::Result<String> {
-    let mut out = String::new();
-    io::stdin().lock().read_to_string(&mut out)?;
+fn read_stdin() -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    io::stdin().lock().read_to_end(&mut out)?;
     Ok(out)
 }
 
@@ -200,10 +271,11 @@ fn is_stdin_formattable<P: AsRef<Path>>(
 
     match include {
         discovery::Include::Matched => {
-            // `default_include` must include it.
+            // `include` or `default_include` must include it.
             // No need for `IS_DIRECTORY` since includes are only applicable for files.
             match workspace::discovery::any_include_matched_path(
                 path,
+                settings.format.include.as_ref(),
                 settings.format.default_include.as_ref(),
             ) {
                 Some(glob) => {
@@ -237,7 +309,13 @@ impl Display for FormatStdinError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Format(error) => write!(f, "Failed to format stdin: {error}"),
+            Self::Verify(error) => write!(
+                f,
+                "Internal error while formatting stdin: {error}. Please report this at https://github.com/posit-dev/air/issues"
+            ),
             Self::Read(error) => write!(f, "Failed to read from stdin: {error}"),
+            Self::Decode(error) => write!(f, "Failed to read from stdin: {error}"),
+            Self::Encode(error) => write!(f, "Failed to format stdin: {error}"),
             Self::Write(error) => write!(f, "Failed to write to stdout: {error}"),
         }
     }
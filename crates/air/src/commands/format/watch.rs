@@ -0,0 +1,373 @@
+//! Continuously format files as they are saved with `air format --watch`
+//!
+//! This brings format-on-save to editors without language server support. Like the
+//! language server, the settings are rediscovered whenever a configuration file
+//! changes, so that edits to an `air.toml` apply to the next save.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::io::stderr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use colored::Colorize;
+use fs::has_r_extension;
+use fs::relativize_path;
+use notify::EventKind;
+use notify::RecursiveMode;
+use notify::Watcher;
+use notify::event::ModifyKind;
+use workspace::description::is_description;
+use workspace::discovery;
+use workspace::discovery::DiscoveredSettings;
+use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::editorconfig::EditorConfigCache;
+use workspace::editorconfig::is_editorconfig;
+use workspace::resolve::PathResolver;
+use workspace::settings::Settings;
+use workspace::toml::is_air_toml;
+
+use crate::ExitStatus;
+use crate::commands::format::paths::FormatPathError;
+use crate::commands::format::paths::FormattedFile;
+use crate::commands::format::paths::discover_paths;
+use crate::commands::format::paths::format_path;
+use crate::commands::format::paths::write_path;
+
+/// How long to wait for more events after one is received
+///
+/// Editors often emit several events for a single save, like when writing to a
+/// temporary file and renaming it over the original, so we wait for these to settle
+/// before formatting.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub(crate) fn format(
+    paths: Vec<PathBuf>,
+    exclude: discovery::Exclude,
+    include: discovery::Include,
+    verify: bool,
+) -> anyhow::Result<ExitStatus> {
+    // Normalize up front, so that paths match the ones reported by the watcher
+    let paths: Vec<PathBuf> = paths.iter().map(fs::normalize_path).collect();
+
+    let mut resolver = build_resolver(&paths)?;
+    let mut editorconfig = EditorConfigCache::default();
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    let mut watched = HashSet::new();
+
+    for path in &paths {
+        if path.is_dir() {
+            watch(&mut watcher, &mut watched, path, RecursiveMode::Recursive);
+        } else if let Some(parent) = path.parent() {
+            // Watch the parent directory rather than the file itself, as editors that
+            // save by renaming a temporary file over the original would otherwise
+            // detach the watch
+            watch(
+                &mut watcher,
+                &mut watched,
+                parent,
+                RecursiveMode::NonRecursive,
+            );
+        }
+    }
+
+    watch_settings(&mut watcher, &mut watched, &resolver);
+
+    // Format everything once up front, then only the files that change
+    let mut discovered = discover(&paths, &resolver, exclude, include);
+    format_all(discovered.values(), &resolver, &editorconfig, verify);
+
+    writeln!(
+        stderr().lock(),
+        "Watching for changes, press Ctrl-C to stop..."
+    )?;
+
+    while let Ok(event) = receiver.recv() {
+        let mut changes = Changes::default();
+        changes.collect(event);
+
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            changes.collect(event);
+        }
+
+        let settings_changed = changes
+            .paths
+            .iter()
+            .any(|path| is_settings(path, &resolver));
+
+        if settings_changed {
+            tracing::trace!("Rebuilding settings after a configuration file changed");
+
+            match build_resolver(&paths) {
+                Ok(new) => {
+                    resolver = new;
+                    editorconfig = EditorConfigCache::default();
+                    watch_settings(&mut watcher, &mut watched, &resolver);
+                }
+                Err(error) => tracing::error!("Failed to reload settings:\n{error}"),
+            }
+        }
+
+        // Rediscovering walks all of `paths`, so it is only done when settings changes
+        // may affect `exclude` or `include`, or when new R files may have appeared.
+        // Otherwise the changed paths are looked up among the discovered files.
+        let created = changes.created.iter().any(|path| {
+            has_r_extension(path)
+                && !std::fs::canonicalize(path).is_ok_and(|path| discovered.contains_key(&path))
+        });
+
+        if settings_changed || created {
+            discovered = discover(&paths, &resolver, exclude, include);
+        }
+
+        let changed = changes
+            .paths
+            .iter()
+            .filter_map(|path| std::fs::canonicalize(path).ok())
+            .filter_map(|path| discovered.get(&path));
+        format_all(changed, &resolver, &editorconfig, verify);
+    }
+
+    Ok(ExitStatus::Success)
+}
+
+/// Discover the settings that apply to `paths`, falling back to the user level
+/// `air.toml`
+fn build_resolver(paths: &[PathBuf]) -> anyhow::Result<PathResolver<Settings>> {
+    let fallback = discover_user_settings()?.unwrap_or_default();
+    let mut resolver = PathResolver::new(fallback);
+
+    for DiscoveredSettings {
+        directory,
+        settings,
+    } in discover_settings(paths)?
+    {
+        resolver.add(&directory, settings);
+    }
+
+    Ok(resolver)
+}
+
+/// Discover the R files to format, logging any errors
+///
+/// Returns a map from the canonical path of each file to its discovered path. The
+/// watcher may report canonical paths, like `/private/var` rather than `/var` on macOS,
+/// so changed paths are canonicalized before being looked up.
+fn discover(
+    paths: &[PathBuf],
+    resolver: &PathResolver<Settings>,
+    exclude: discovery::Exclude,
+    include: discovery::Include,
+) -> HashMap<PathBuf, PathBuf> {
+    discover_paths(paths, resolver, exclude, include, None)
+        .into_iter()
+        .filter_map(|path| match path {
+            Ok(path) => Some(path),
+            Err(err) => {
+                tracing::error!("{err}", err = FormatPathError::from(err));
+                None
+            }
+        })
+        .filter_map(|path| Some((std::fs::canonicalize(&path).ok()?, path)))
+        .collect()
+}
+
+/// Format the files at `paths`, reporting the ones that were reformatted
+fn format_all<'path>(
+    paths: impl Iterator<Item = &'path PathBuf>,
+    resolver: &PathResolver<Settings>,
+    editorconfig: &EditorConfigCache,
+    verify: bool,
+) {
+    for path in paths {
+        let settings = resolver.resolve_or_fallback(path);
+
+        let result =
+            format_path(path, &settings.format, editorconfig, None, verify).and_then(|formatted| {
+                let is_changed = matches!(formatted, FormattedFile::Changed { .. });
+                write_path(path, formatted)
+                    .map(|()| is_changed)
+                    .map_err(|err| FormatPathError::Write(path.clone(), err))
+            });
+
+        match result {
+            Ok(true) => inform_reformatted(path),
+            Ok(false) => {}
+            Err(err) => tracing::error!("{err}"),
+        }
+    }
+}
+
+fn inform_reformatted(path: &Path) {
+    let path = relativize_path(path);
+    let _ = writeln!(
+        stderr().lock(),
+        "Reformatted: {path}",
+        path = path.underline()
+    );
+}
+
+/// The paths reported by the watcher while debouncing
+#[derive(Debug, Default)]
+struct Changes {
+    /// Paths that may have new contents
+    paths: HashSet<PathBuf>,
+    /// Paths that were created or renamed, which may be files that aren't discovered yet
+    created: HashSet<PathBuf>,
+}
+
+impl Changes {
+    /// Collect the paths of an `event` that may have new contents
+    fn collect(&mut self, event: notify::Result<notify::Event>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                tracing::error!("Failed to watch for changes: {error}");
+                return;
+            }
+        };
+
+        if matches!(event.kind, EventKind::Access(_) | EventKind::Remove(_)) {
+            return;
+        }
+
+        let created = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+        );
+
+        for path in event.paths {
+            if is_ignored(&path) {
+                continue;
+            }
+            if created {
+                self.created.insert(path.clone());
+            }
+            self.paths.insert(path);
+        }
+    }
+}
+
+/// Is `path` never relevant to formatting?
+///
+/// Events within `.git/` and for the temporary files that formatted files are written
+/// to before being renamed over the originals are frequent, so they are dropped early.
+fn is_ignored(path: &Path) -> bool {
+    if path
+        .components()
+        .any(|component| component.as_os_str() == ".git")
+    {
+        return true;
+    }
+
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(".air-") && name.ends_with(".tmp"))
+}
+
+/// Is `path` a configuration file that the settings of `resolver` depend on?
+///
+/// Mirrors the configuration files that the language server watches, along with the
+/// `.Rbuildignore` files used by `respect-rbuildignore`.
+fn is_settings(path: &Path, resolver: &PathResolver<Settings>) -> bool {
+    is_air_toml(path)
+        || is_description(path)
+        || is_editorconfig(path)
+        || path.ends_with(".Rbuildignore")
+        || std::iter::once(resolver.fallback())
+            .chain(resolver.items().iter().map(|item| item.value()))
+            .any(|settings| settings.extended.iter().any(|extended| extended == path))
+}
+
+/// Watch the directories holding the configuration files of `resolver`
+///
+/// These may live outside of the watched paths, like an `air.toml` in a parent
+/// directory, or a file inherited through `extend`.
+fn watch_settings(
+    watcher: &mut impl Watcher,
+    watched: &mut HashSet<PathBuf>,
+    resolver: &PathResolver<Settings>,
+) {
+    for item in resolver.items() {
+        watch(watcher, watched, item.path(), RecursiveMode::NonRecursive);
+    }
+
+    let extended = std::iter::once(resolver.fallback())
+        .chain(resolver.items().iter().map(|item| item.value()))
+        .flat_map(|settings| settings.extended.iter());
+
+    for path in extended {
+        if let Some(parent) = path.parent() {
+            watch(watcher, watched, parent, RecursiveMode::NonRecursive);
+        }
+    }
+}
+
+/// Watch `path`, unless it is already watched
+///
+/// Failures are logged rather than propagated, so that the remaining paths are still
+/// watched.
+fn watch(
+    watcher: &mut impl Watcher,
+    watched: &mut HashSet<PathBuf>,
+    path: &Path,
+    mode: RecursiveMode,
+) {
+    if !watched.insert(path.to_path_buf()) {
+        return;
+    }
+
+    if let Err(error) = watcher.watch(path, mode) {
+        tracing::error!(
+            "Failed to watch {path}: {error}",
+            path = relativize_path(path).underline()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use notify::Event;
+    use notify::EventKind;
+    use notify::event::CreateKind;
+    use notify::event::DataChange;
+    use notify::event::ModifyKind;
+
+    use crate::commands::format::watch::Changes;
+
+    #[test]
+    fn test_changes_ignore_git_and_temporary_files() {
+        let mut changes = Changes::default();
+
+        let modified = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        changes.collect(Ok(Event::new(modified)
+            .add_path(PathBuf::from("project/R/code.R"))
+            .add_path(PathBuf::from("project/.git/index"))));
+
+        let created = EventKind::Create(CreateKind::File);
+        changes.collect(Ok(Event::new(created)
+            .add_path(PathBuf::from("project/R/new.R"))
+            .add_path(PathBuf::from("project/R/.air-a1b2c3.tmp"))));
+
+        let mut paths: Vec<_> = changes.paths.into_iter().collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("project/R/code.R"),
+                PathBuf::from("project/R/new.R")
+            ]
+        );
+
+        let created: Vec<_> = changes.created.into_iter().collect();
+        assert_eq!(created, vec![PathBuf::from("project/R/new.R")]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Atomic writes of formatted files
+//!
+//! Formatted contents are written to a temporary file next to the original, which is
+//! then renamed over it. An interrupted `air format` leaves either the original or the
+//! formatted file behind, never a truncated one.
+
+use std::fs::Metadata;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// The size and modification time of a file when it was read
+///
+/// Used to detect files that were modified concurrently, between reading and writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub(super) fn new(metadata: &Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+/// Atomically replace the contents of the file at `path`, which was read at `stamp`
+///
+/// - Symlinks are written through, so the target is formatted and the symlink is
+///   kept as is.
+///
+/// - The permissions of the original file are preserved. The modification time is
+///   not, as build tools rely on it to notice that the contents changed.
+///
+/// - If the file was modified since it was read, it is left untouched and an error is
+///   returned, so that the concurrent changes aren't lost.
+pub(super) fn write_atomic(path: &Path, contents: &[u8], stamp: FileStamp) -> io::Result<()> {
+    let target = std::fs::canonicalize(path)?;
+
+    let Some(directory) = target.parent() else {
+        return Err(io::Error::other("The file has no parent directory"));
+    };
+
+    let metadata = std::fs::metadata(&target)?;
+
+    let mut file = tempfile::Builder::new()
+        .prefix(".air-")
+        .suffix(".tmp")
+        .tempfile_in(directory)?;
+
+    file.write_all(contents)?;
+    file.as_file().set_permissions(metadata.permissions())?;
+
+    // Check as late as possible, right before the rename. The temporary file is removed
+    // when dropped.
+    if FileStamp::new(&std::fs::metadata(&target)?) != stamp {
+        return Err(io::Error::other(
+            "The file was modified while it was being formatted, so it was left unchanged",
+        ));
+    }
+
+    file.persist(&target).map_err(|err| err.error)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::TempDir;
+
+    use crate::commands::format::write::FileStamp;
+    use crate::commands::format::write::write_atomic;
+
+    #[test]
+    fn test_write_atomic_detects_concurrent_changes() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let path = tempdir.path().join("test.R");
+
+        std::fs::write(&path, "1+1\n")?;
+        let stamp = FileStamp::new(&std::fs::metadata(&path)?);
+
+        // Modified in between, with a different size
+        std::fs::write(&path, "1+1\n2+2\n")?;
+
+        assert!(write_atomic(&path, b"1 + 1\n", stamp).is_err());
+        assert_eq!(std::fs::read_to_string(&path)?, "1+1\n2+2\n");
+
+        // No leftover temporary file
+        assert_eq!(std::fs::read_dir(tempdir.path())?.count(), 1);
+
+        let stamp = FileStamp::new(&std::fs::metadata(&path)?);
+        write_atomic(&path, b"1 + 1\n2 + 2\n", stamp)?;
+        assert_eq!(std::fs::read_to_string(&path)?, "1 + 1\n2 + 2\n");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_preserves_mode_and_symlinks() -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = TempDir::new()?;
+        let target = tempdir.path().join("target.R");
+        let link = tempdir.path().join("link.R");
+
+        std::fs::write(&target, "1+1\n")?;
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))?;
+        std::os::unix::fs::symlink(&target, &link)?;
+
+        let stamp = FileStamp::new(&std::fs::metadata(&link)?);
+        write_atomic(&link, b"1 + 1\n", stamp)?;
+
+        assert!(std::fs::symlink_metadata(&link)?.is_symlink());
+        assert_eq!(std::fs::read_to_string(&target)?, "1 + 1\n");
+        assert_eq!(
+            std::fs::metadata(&target)?.permissions().mode() & 0o777,
+            0o755
+        );
+
+        Ok(())
+    }
+}
@@ -22,5 +22,6 @@ pub fn run(args: Args) -> anyhow::Result<ExitStatus> {
         }
         Command::Format(command) => commands::format::format(command),
         Command::LanguageServer(command) => commands::language_server::language_server(command),
+        Command::Debug(command) => commands::debug::debug(command),
     }
 }
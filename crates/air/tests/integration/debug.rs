@@ -0,0 +1,87 @@
+use std::process::Command;
+
+use tempfile::TempDir;
+
+use crate::helpers::CommandExt;
+use crate::helpers::binary_path;
+
+#[test]
+fn test_debug_syntax_json() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let path = "test.R";
+    std::fs::write(directory.join(path), "x <- 1 # comment\n")?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("debug")
+        .arg("syntax")
+        .arg("--json")
+        .arg(path)
+        .run();
+
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_str(&output.stdout)?;
+    assert_eq!(json["kind"], "R_ROOT");
+    assert!(json["children"].is_array());
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_ir_uses_settings() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let path = "test.R";
+    std::fs::write(directory.join(path), "tribble(a,b)\n")?;
+
+    let ir = || {
+        Command::new(binary_path())
+            .current_dir(directory)
+            .arg("debug")
+            .arg("ir")
+            .arg(path)
+            .run()
+    };
+
+    let default = ir();
+    assert!(default.status.success());
+
+    // Skipped calls are formatted verbatim, which changes the IR
+    std::fs::write(
+        directory.join("air.toml"),
+        "[format]\nskip = [\"tribble\"]\n",
+    )?;
+
+    let skipped = ir();
+    assert!(skipped.status.success());
+    assert_ne!(default.stdout, skipped.stdout);
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_parse_error() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let path = "test.R";
+    std::fs::write(directory.join(path), "1 +\n")?;
+
+    for view in ["tree-sitter", "syntax", "ir"] {
+        let output = Command::new(binary_path())
+            .current_dir(directory)
+            .arg("debug")
+            .arg(view)
+            .arg(path)
+            .run();
+
+        assert!(!output.status.success());
+        assert!(output.stderr.contains("Failed to parse"));
+    }
+
+    Ok(())
+}
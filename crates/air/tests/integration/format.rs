@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::path::Path;
 use std::process::Command;
 use std::process::Stdio;
@@ -6,6 +7,8 @@ use tempfile::TempDir;
 
 use crate::helpers::CommandExt;
 use crate::helpers::binary_path;
+use crate::helpers::git;
+use crate::helpers::isolate_user_config;
 use crate::helpers::path_root;
 use crate::helpers::relative_path_fixtures;
 
@@ -347,6 +350,30 @@ fn test_check_output_format() {
     );
 }
 
+#[test]
+fn test_check_reports_non_ascii_strings() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    // `ascii-strings` defaults to `true` in packages, even without an `air.toml`
+    std::fs::write(directory.join("DESCRIPTION"), "Package: test\n")?;
+
+    let test_path = "test.R";
+    std::fs::write(directory.join(test_path), "x <- \"café\"\n# é\n")?;
+
+    insta::assert_snapshot!(
+        Command::new(binary_path())
+            .current_dir(directory)
+            .arg("format")
+            .arg(test_path)
+            .arg("--check")
+            .run()
+            .normalize_os_path_separator()
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_stdin_cant_supply_paths() -> anyhow::Result<()> {
     let directory = TempDir::new()?;
@@ -541,6 +568,43 @@ indent-width = 4
     Ok(())
 }
 
+#[test]
+fn test_stdin_uses_encoding() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let air_path = "air.toml";
+    let air_contents = r#"
+[format]
+encoding = "latin1"
+"#;
+    std::fs::write(directory.join(air_path), air_contents)?;
+
+    let mut command = Command::new(binary_path());
+    command
+        .current_dir(directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .arg("format")
+        .arg("--stdin-file-path")
+        .arg("test.R");
+
+    let _user_config = isolate_user_config(&mut command);
+    let mut child = command.spawn()?;
+
+    // `é` is a single `0xE9` byte in latin1, which isn't valid UTF-8
+    let mut stdin = child.stdin.take().unwrap();
+    std::thread::spawn(move || stdin.write_all(b"x<-\"caf\xE9\"\n"));
+
+    let output = child.wait_with_output()?;
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"x <- \"caf\xE9\"\n");
+
+    Ok(())
+}
+
 #[test]
 fn test_stdin_empty_input() -> anyhow::Result<()> {
     let directory = TempDir::new()?;
@@ -852,3 +916,410 @@ fn test_force_overrides_default_excludes_with_stdin_check() -> anyhow::Result<()
 
     Ok(())
 }
+
+#[test]
+fn test_user_config_is_the_fallback() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let config_directory = directory.join("config");
+    std::fs::create_dir(&config_directory)?;
+    std::fs::write(
+        config_directory.join("air.toml"),
+        "[format]\nassignment-style = \"equal\"\n",
+    )?;
+
+    let scratch_directory = directory.join("scratch");
+    std::fs::create_dir(&scratch_directory)?;
+
+    let project_directory = directory.join("project");
+    std::fs::create_dir(&project_directory)?;
+    std::fs::write(project_directory.join("air.toml"), "")?;
+
+    let contents = "x <- 1\n";
+    std::fs::write(scratch_directory.join("test.R"), contents)?;
+    std::fs::write(project_directory.join("test.R"), contents)?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .env("AIR_CONFIG_DIR", &config_directory)
+        .arg("format")
+        .arg("scratch")
+        .arg("project")
+        .run();
+
+    assert!(output.status.success());
+
+    // Without a project `air.toml`, the user level one is used
+    assert_eq!(
+        std::fs::read_to_string(scratch_directory.join("test.R"))?,
+        "x = 1\n"
+    );
+
+    // A project `air.toml` takes precedence over the user level one
+    assert_eq!(
+        std::fs::read_to_string(project_directory.join("test.R"))?,
+        contents
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_staged_only_formats_staged_files() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    let contents = "1+1\n";
+    std::fs::write(directory.join("staged.R"), contents)?;
+    std::fs::write(directory.join("unstaged.R"), contents)?;
+    std::fs::write(directory.join("removed.R"), contents)?;
+
+    git(directory, &["add", "staged.R", "removed.R"]);
+    std::fs::remove_file(directory.join("removed.R"))?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--staged")
+        .run();
+
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(directory.join("staged.R"))?,
+        "1 + 1\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(directory.join("unstaged.R"))?,
+        contents
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_changed_since_only_formats_changed_files() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    let contents = "1+1\n";
+    std::fs::create_dir(directory.join("R"))?;
+    std::fs::write(directory.join("R").join("unchanged.R"), contents)?;
+    std::fs::write(directory.join("R").join("renamed.R"), contents)?;
+    std::fs::write(directory.join("R").join("deleted.R"), contents)?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Initial commit"]);
+
+    git(directory, &["checkout", "-b", "feature"]);
+    git(directory, &["mv", "R/renamed.R", "R/new-name.R"]);
+    git(directory, &["rm", "R/deleted.R"]);
+    std::fs::write(directory.join("R").join("committed.R"), contents)?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Feature"]);
+
+    // Uncommitted and untracked files are considered too
+    std::fs::write(directory.join("R").join("untracked.R"), contents)?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--changed-since")
+        .arg("main")
+        .arg("R")
+        .run();
+
+    assert!(output.status.success());
+
+    for path in ["new-name.R", "committed.R", "untracked.R"] {
+        assert_eq!(
+            std::fs::read_to_string(directory.join("R").join(path))?,
+            "1 + 1\n"
+        );
+    }
+    assert_eq!(
+        std::fs::read_to_string(directory.join("R").join("unchanged.R"))?,
+        contents
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_lines_only_formats_the_given_lines() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    std::fs::write(directory.join("test.R"), "1+1\n2+2\n3+3\n4+4\n")?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("test.R")
+        .arg("--lines")
+        .arg("2:2")
+        .arg("--lines")
+        .arg("4:4")
+        .run();
+
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(directory.join("test.R"))?,
+        "1+1\n2 + 2\n3+3\n4 + 4\n"
+    );
+
+    // Line ranges are ambiguous with multiple files
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg(".")
+        .arg("--lines")
+        .arg("1:1")
+        .run();
+
+    assert!(!output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn test_only_changed_lines_formats_changed_expressions() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    std::fs::write(directory.join("test.R"), "1+1\n2+2\n3+3\n")?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Initial commit"]);
+
+    std::fs::write(directory.join("test.R"), "1+1\nf(a,\nb)\n3+3\n")?;
+    std::fs::write(directory.join("untracked.R"), "5+5\n")?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--only-changed-lines")
+        .run();
+
+    assert!(output.status.success());
+
+    // The changed call is formatted as a whole, untouched lines are left alone, and
+    // untracked files are formatted entirely
+    assert_eq!(
+        std::fs::read_to_string(directory.join("test.R"))?,
+        "1+1\nf(a, b)\n3+3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(directory.join("untracked.R"))?,
+        "5 + 5\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_only_changed_lines_with_staged_uses_working_tree_lines() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    std::fs::write(directory.join("test.R"), "1+1\n2+2\n3+3\n")?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Initial commit"]);
+
+    // Stage a change at the end, then add unstaged lines above it, which shift the
+    // staged change further down in the working tree
+    std::fs::write(directory.join("test.R"), "1+1\n2+2\nf(a,\nb)\n")?;
+    git(directory, &["add", "."]);
+    std::fs::write(directory.join("test.R"), "x<-1\ny<-2\n1+1\n2+2\nf(a,\nb)\n")?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--staged")
+        .arg("--only-changed-lines")
+        .run();
+
+    assert!(output.status.success());
+
+    // The working tree is formatted, so its own changed lines are used rather than the
+    // line numbers of the staged copy
+    assert_eq!(
+        std::fs::read_to_string(directory.join("test.R"))?,
+        "x <- 1\ny <- 2\n1+1\n2+2\nf(a, b)\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_only_changed_lines_with_non_ascii_file_name() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    // Git quotes non-ASCII paths in diff headers by default
+    std::fs::write(directory.join("café.R"), "1+1\n2+2\n")?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Initial commit"]);
+
+    std::fs::write(directory.join("café.R"), "1+1\n2+2\n3+3\n")?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--only-changed-lines")
+        .run();
+
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(directory.join("café.R"))?,
+        "1+1\n2+2\n3 + 3\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_write_index_formats_staged_contents() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    git(directory, &["init"]);
+
+    std::fs::write(directory.join("partial.R"), "x <- 1\ny <- 2\n")?;
+    std::fs::write(directory.join("conflict.R"), "a <- 1\n")?;
+    git(directory, &["add", "."]);
+    git(directory, &["commit", "-m", "Initial commit"]);
+
+    // Stage some changes, then make more unstaged changes on top of them
+    std::fs::write(directory.join("partial.R"), "x<-1\ny <- 2\n")?;
+    std::fs::write(directory.join("conflict.R"), "a<-1\n")?;
+    std::fs::write(directory.join("deleted.R"), "b<-1\n")?;
+    git(directory, &["add", "."]);
+    std::fs::write(directory.join("partial.R"), "x<-1\ny <- 2\nz<-3\n")?;
+    std::fs::write(directory.join("conflict.R"), "a<-2\n")?;
+    std::fs::remove_file(directory.join("deleted.R"))?;
+
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg("--staged")
+        .arg("--write-index")
+        .arg("--no-color")
+        .run();
+
+    assert!(output.status.success());
+
+    // The staged contents are formatted, unstaged changes are neither formatted nor
+    // staged
+    assert_eq!(git(directory, &["show", ":partial.R"]), "x <- 1\ny <- 2\n");
+    assert_eq!(git(directory, &["show", ":conflict.R"]), "a <- 1\n");
+
+    // The formatting changes are merged into the working tree when they don't
+    // conflict with the unstaged changes, and conflicting files are reported
+    assert_eq!(
+        std::fs::read_to_string(directory.join("partial.R"))?,
+        "x <- 1\ny <- 2\nz<-3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(directory.join("conflict.R"))?,
+        "a<-2\n"
+    );
+    assert!(
+        output
+            .stderr
+            .contains("Skipped the working tree copy of conflict.R")
+    );
+
+    // Staged files that were deleted from the working tree are reported
+    assert_eq!(git(directory, &["show", ":deleted.R"]), "b<-1\n");
+    assert!(
+        output
+            .stderr
+            .contains("Skipped deleted.R: it is staged but was deleted from the working tree")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_watch_formats_saved_files() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    std::fs::write(directory.join("existing.R"), "1+1\n")?;
+
+    let mut command = Command::new(binary_path());
+    command
+        .current_dir(directory)
+        .arg("format")
+        .arg("--watch")
+        .stderr(Stdio::null());
+
+    let _user_config = isolate_user_config(&mut command);
+    let mut child = command.spawn()?;
+
+    // Polls until `path` has the `expected` contents, or gives up after a while
+    let wait_for = |path: &Path, expected: &str| {
+        for _ in 0..100 {
+            if std::fs::read_to_string(path).is_ok_and(|contents| contents == expected) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        false
+    };
+
+    // Existing files are formatted up front
+    let existing = wait_for(&directory.join("existing.R"), "1 + 1\n");
+
+    // New files and changes to `air.toml` are picked up
+    std::fs::write(directory.join("air.toml"), "[format]\nindent-width = 4\n")?;
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    std::fs::write(directory.join("new.R"), "function(){\n1\n}\n")?;
+    let new = wait_for(&directory.join("new.R"), "function() {\n    1\n}\n");
+
+    child.kill()?;
+    child.wait()?;
+
+    assert!(existing);
+    assert!(new);
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_formats_as_usual() -> anyhow::Result<()> {
+    let directory = TempDir::new()?;
+    let directory = directory.path();
+
+    let path = "test.R";
+    std::fs::write(directory.join(path), "x = 1\nif (x) y\nlist(\"a\" = 1)\n")?;
+    std::fs::write(
+        directory.join("air.toml"),
+        "[format]\nassignment-style = \"arrow\"\nnormalize-names = true\n",
+    )?;
+
+    // Intentional rewrites, like autobracing, pass verification
+    let output = Command::new(binary_path())
+        .current_dir(directory)
+        .arg("format")
+        .arg(path)
+        .arg("--verify")
+        .run();
+
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(directory.join(path))?,
+        "x <- 1\nif (x) {\n  y\n}\nlist(a = 1)\n"
+    );
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 pub mod binary;
 pub mod command_ext;
 pub mod fixtures;
+pub mod git;
 
 pub use binary::*;
 pub use command_ext::*;
 pub use fixtures::*;
+pub use git::*;
@@ -3,6 +3,8 @@ use std::io::Write;
 use std::process::Command;
 use std::process::ExitStatus;
 
+use tempfile::TempDir;
+
 pub trait CommandExt {
     /// Executes the command as a child process, waiting for it to finish and collecting all of its output.
     ///
@@ -58,7 +60,8 @@ impl Output {
 
 impl CommandExt for Command {
     fn run(&mut self) -> Output {
-        // Augment `std::process::Output` with the arguments
+        let _user_config = isolate_user_config(self);
+
         let output = self.output().unwrap();
 
         // Augment `std::process::Output` with the arguments
@@ -66,6 +69,8 @@ impl CommandExt for Command {
     }
 
     fn run_with_stdin(&mut self, stdin: String) -> Output {
+        let _user_config = isolate_user_config(self);
+
         let mut child = self.spawn().unwrap();
 
         let mut handle = child.stdin.take().unwrap();
@@ -80,6 +85,25 @@ impl CommandExt for Command {
     }
 }
 
+/// Point `AIR_CONFIG_DIR` at an empty temporary directory, so that the user level
+/// `air.toml` of the machine running the tests is never picked up
+///
+/// Tests that set `AIR_CONFIG_DIR` themselves are left alone. The returned directory
+/// must be kept alive until the command has finished.
+pub fn isolate_user_config(command: &mut Command) -> Option<TempDir> {
+    let is_set = command
+        .get_envs()
+        .any(|(name, value)| name == "AIR_CONFIG_DIR" && value.is_some());
+
+    if is_set {
+        return None;
+    }
+
+    let directory = TempDir::new().unwrap();
+    command.env("AIR_CONFIG_DIR", directory.path());
+    Some(directory)
+}
+
 impl Output {
     fn from_command_output_and_arguments(
         output: std::process::Output,
@@ -0,0 +1,31 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Run `git` with `args` in `directory`, returning its standard output and panicking on
+/// failure
+///
+/// Identity and signing are configured inline so that tests don't depend on the
+/// user's git configuration.
+pub fn git(directory: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(directory)
+        .args(["-c", "user.name=air", "-c", "user.email=air@example.com"])
+        .args([
+            "-c",
+            "commit.gpgsign=false",
+            "-c",
+            "init.defaultBranch=main",
+        ])
+        .args(args)
+        .output()
+        .expect("Failed to run `git`");
+
+    assert!(
+        output.status.success(),
+        "`git {args}` failed: {stderr}",
+        args = args.join(" "),
+        stderr = String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
@@ -6,6 +6,7 @@
 /// Resolves problems with:
 /// - Compilation times, by only having 1 integration test binary
 /// - Dead code analysis of integration test helpers https://github.com/rust-lang/rust/issues/46379
+mod debug;
 mod format;
 mod generate_shell_completion;
 mod help;
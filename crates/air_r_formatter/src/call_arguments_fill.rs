@@ -0,0 +1,115 @@
+use crate::call_arguments_table::is_literal;
+use crate::comments_ext::call_identifier;
+use crate::prelude::*;
+use crate::r::auxiliary::call_arguments::FormatRCallArguments;
+use air_r_syntax::{AnyRExpression, RCall, RCallArguments};
+
+use biome_formatter::{format_args, write};
+use biome_rowan::AstSeparatedList;
+
+/// Maximum width of an element of a vector laid out in fill mode
+///
+/// Filling long elements produces ragged lines that are harder to read than one
+/// element per line.
+const MAX_FILL_ELEMENT_WIDTH: usize = 20;
+
+impl FormatRCallArguments {
+    /// Formats the arguments of a `c()` call with `vector-layout = "fill"`
+    ///
+    /// When the vector doesn't fit on a single line, as many elements as fit are
+    /// packed on each line:
+    ///
+    /// ```r
+    /// x <- c(
+    ///   1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+    ///   21, 22, 23, 24, 25
+    /// )
+    /// ```
+    ///
+    /// Like for other calls, a persistent line break before the first element
+    /// forces the vector to expand, but elements are still packed on each line.
+    pub(crate) fn fmt_fill(&self, node: &RCallArguments, f: &mut RFormatter) -> FormatResult<()> {
+        let l_token = node.l_paren_token()?;
+        let r_token = node.r_paren_token()?;
+        let items = node.items();
+
+        let expand = f.options().persistent_line_breaks().is_respect()
+            && items
+                .first()
+                .and_then(|item| item.ok())
+                .is_some_and(|item| get_lines_before(item.syntax()) > 0);
+
+        let elements = format_with(|f| {
+            let mut fill = f.fill();
+
+            for element in items.elements() {
+                let node = element.node()?;
+                let separator = element.trailing_separator()?;
+                fill.entry(
+                    &soft_line_break_or_space(),
+                    &format_args![node.format(), separator.format()],
+                );
+            }
+
+            fill.finish()
+        });
+
+        write!(
+            f,
+            [group(&format_args![
+                l_token.format(),
+                soft_block_indent(&elements),
+                r_token.format()
+            ])
+            .should_expand(expand)]
+        )
+    }
+}
+
+/// Can the arguments of this call be laid out in fill mode?
+///
+/// Only `c()` calls whose arguments are all short unnamed literals or identifiers
+/// are eligible. Comments and empty lines between elements are respected by
+/// falling back to the usual layout.
+pub(crate) fn is_fill_vector(node: &RCallArguments) -> bool {
+    let Some(call) = node.parent::<RCall>() else {
+        return false;
+    };
+
+    let is_c = call_identifier(&call)
+        .ok()
+        .flatten()
+        .and_then(|identifier| identifier.name_token().ok())
+        .is_some_and(|token| token.text_trimmed() == "c");
+    if !is_c {
+        return false;
+    }
+
+    let items = node.items();
+    if items.is_empty() || items.syntax().has_comments_descendants() {
+        return false;
+    }
+
+    // A trailing separator would be followed by a hole
+    if items.trailing_separator().is_some() {
+        return false;
+    }
+
+    items.iter().all(|item| {
+        let Ok(item) = item else {
+            return false;
+        };
+
+        if item.name_clause().is_some() || get_lines_before(item.syntax()) > 1 {
+            return false;
+        }
+
+        let Some(value) = item.value() else {
+            return false;
+        };
+
+        let is_element = is_literal(&value) || matches!(value, AnyRExpression::RIdentifier(_));
+        is_element
+            && usize::from(value.syntax().text_trimmed_range().len()) <= MAX_FILL_ELEMENT_WIDTH
+    })
+}
@@ -1,12 +1,16 @@
+use crate::comments::format_r_leading_comments;
+use crate::comments_ext::call_identifier;
 use crate::r::auxiliary::call_arguments::FormatRCallArguments;
 use crate::{prelude::*, r::auxiliary::argument::fmt_argument_fields};
 use air_r_syntax::{
-    AnyRExpression, AnyRValue, RArgument, RArgumentList, RCallArguments, RDoubleValue,
-    RIntegerValue, RLanguage, RSyntaxToken, RUnaryExpression,
+    AnyRArgumentName, AnyRExpression, AnyRValue, RArgument, RArgumentList, RCall, RCallArguments,
+    RDoubleValue, RIntegerValue, RLanguage, RSyntaxKind, RSyntaxToken, RUnaryExpression,
 };
 
 use biome_formatter::{FormatElement, RemoveSoftLinesBuffer, format_args, write};
 use biome_rowan::{AstSeparatedElement, AstSeparatedList};
+use itertools::Itertools;
+use settings::ColumnAlignment;
 
 const DOT_WIDTH: usize = 1;
 
@@ -62,19 +66,52 @@ impl FormatRCallArguments {
         }
 
         // Get table and alignment info
-        let table = match build_table(&args, f)? {
+        let table = match build_table(&args, self.grid(), f)? {
             Some(table) => table,
             None => return Ok(None),
         };
+
+        let grid = self.grid().is_some();
+
+        if grid && !grid_rows_fit(node, &table, f) {
+            return Ok(None);
+        }
+
         let rows = table.rows;
         let column_info = table.cols;
 
+        // Values and separators are memoized because grids write them in both a
+        // flat and a grid layout, and tokens can only be formatted once
+        let cells: Vec<Vec<_>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|arg_data| {
+                        let value =
+                            format_with(move |f: &mut RFormatter| arg_data.fmt_value(f)).memoized();
+                        let separator = arg_data
+                            .separator
+                            .as_ref()
+                            .map(|sep| sep.format().memoized());
+                        (value, separator)
+                    })
+                    .collect()
+            })
+            .collect();
+
         // Format with alignment
         let formatted_table = format_with(|f| {
-            for (row_i, row) in rows.iter().enumerate() {
-                for (col_j, arg_data) in row.iter().enumerate() {
+            for (row_i, (row, row_cells)) in rows.iter().zip(&cells).enumerate() {
+                for (col_j, (arg_data, (value, separator))) in row.iter().zip(row_cells).enumerate()
+                {
                     if col_j == 0 && row_i > 0 {
                         match get_lines_before(arg_data.node.syntax()) {
+                            _ if grid => {
+                                // Grids are only printed when their group breaks,
+                                // where soft line breaks are line breaks. Hard line
+                                // breaks would prevent the flat layout.
+                                write!(f, [soft_line_break()])?
+                            }
                             0 | 1 => {
                                 // Rows are separated with hard line breaks because the
                                 // lines of arguments in table calls should never be
@@ -90,44 +127,20 @@ impl FormatRCallArguments {
                     }
 
                     let (left_pad, right_pad) = if column_info[col_j].max_width > 0 {
-                        column_info[col_j].padding(&arg_data.kind)
+                        let alignment = self
+                            .table_alignment()
+                            .and_then(|alignment| alignment.get(col_j));
+                        column_info[col_j].padding(&arg_data.kind, alignment)
                     } else {
                         // For empty columns don't add any incompressible whitespace
                         (0, 0)
                     };
 
-                    // Add left padding for right-aligned values
+                    // Add left padding for right-aligned and centered values
                     write_spaces(left_pad, f)?;
 
                     // Format the value
-                    match &arg_data.kind {
-                        ArgKind::Other { text } => {
-                            let arg_syntax = arg_data.node.syntax();
-
-                            // Suppression comments do nothing inside a table
-                            f.comments().mark_suppression_checked(arg_syntax);
-
-                            // We've formatted the argument without comments, so
-                            // we're in charge of formatting them
-                            format_leading_comments(arg_syntax).fmt(f)?;
-
-                            // 0-length arguments are holes. Don't print them
-                            // because a `text("")` after a `Space` will prevent
-                            // the latter from being considered trailing by the
-                            // printer, and won't be removed if trailing.
-                            if !text.is_empty() {
-                                write!(f, [dynamic_text(text, 0.into())])?;
-                            }
-
-                            format_trailing_comments(arg_syntax).fmt(f)?;
-                        }
-
-                        ArgKind::Numeric { .. } => {
-                            // For numeric types, format the node directly. This
-                            // handles comments as well.
-                            write!(f, [arg_data.node.format()])?;
-                        }
-                    }
+                    write!(f, [value])?;
 
                     // Add right padding (but not for the last item)
                     if !arg_data.is_last_in_list {
@@ -135,8 +148,8 @@ impl FormatRCallArguments {
                     }
 
                     // Format comma
-                    if let Some(sep) = &arg_data.separator {
-                        write!(f, [space(), sep.format()])?;
+                    if let Some(separator) = separator {
+                        write!(f, [space(), separator])?;
                     } else if !arg_data.is_last_in_list {
                         // Syntactic invariant: All arguments except the last one have a separator
                         return Err(FormatError::SyntaxError);
@@ -150,6 +163,42 @@ impl FormatRCallArguments {
             Ok(())
         });
 
+        if grid {
+            // The flat layout of the data, used when it fits on the line
+            let flat = format_with(|f| {
+                for (value, separator) in cells.iter().flatten() {
+                    write!(f, [value])?;
+                    if let Some(separator) = separator {
+                        write!(f, [separator, space()])?;
+                    }
+                }
+                Ok(())
+            });
+
+            // Let the printer choose between the flat layout and the grid, since
+            // only the printer knows where the call starts. A persistent line
+            // break before the first argument forces the grid.
+            let expanded = f.options().persistent_line_breaks().is_respect()
+                && node
+                    .items()
+                    .first()
+                    .and_then(|item| item.ok())
+                    .is_some_and(|item| get_lines_before(item.syntax()) > 0);
+
+            write!(
+                f,
+                [group(&format_args![
+                    l_token.format(),
+                    if_group_fits_on_line(&flat),
+                    if_group_breaks(&soft_block_indent(&formatted_table)),
+                    r_token.format()
+                ])
+                .should_expand(expanded)]
+            )?;
+
+            return Ok(Some(()));
+        }
+
         // Copied from `Format` method for `FormatAllArgsBrokenOut`
         let remaining = format_with(|f| {
             if table.remaining.is_empty() {
@@ -190,12 +239,16 @@ impl FormatRCallArguments {
     }
 }
 
-fn build_table(args: &RArgumentList, f: &mut RFormatter) -> FormatResult<Option<TableInfo>> {
+fn build_table(
+    args: &RArgumentList,
+    grid: Option<usize>,
+    f: &mut RFormatter,
+) -> FormatResult<Option<TableInfo>> {
     // Take a snapshot of the formatter buffer to restore it on exit. We're
     // eagerly formatting table cells in the buffer and need to undo that work.
     let snapshot = f.snapshot();
 
-    let table = build_table_impl(args, f);
+    let table = build_table_impl(args, grid, f);
 
     f.restore_snapshot(snapshot);
     table
@@ -205,7 +258,14 @@ fn build_table(args: &RArgumentList, f: &mut RFormatter) -> FormatResult<Option<
 // alignment. This involves finding the integer and fractional widths of numeric
 // arguments, and formatting other arguments in a flat layout to get the width of
 // the final printed text.
-fn build_table_impl(args: &RArgumentList, f: &mut RFormatter) -> FormatResult<Option<TableInfo>> {
+//
+// Rows are usually delimited by the existing line breaks. With `grid`, rows
+// are instead made of `grid` arguments each, regardless of line breaks.
+fn build_table_impl(
+    args: &RArgumentList,
+    grid: Option<usize>,
+    f: &mut RFormatter,
+) -> FormatResult<Option<TableInfo>> {
     let mut cols: Vec<ColumnInfo> = Vec::new();
     let mut rows: Vec<Vec<ArgData>> = Vec::new();
     let mut current_row = Vec::new();
@@ -240,10 +300,15 @@ fn build_table_impl(args: &RArgumentList, f: &mut RFormatter) -> FormatResult<Op
             arg_separator.map_or(0, get_lines_before_token)
         };
 
+        let is_new_row = match grid {
+            Some(width) => current_row.len() == width,
+            None => lines_before > 0,
+        };
+
         // Push new row if any. Empty lines are not rows. Note empty lines still
         // get formatted as part of trivia in the formatting pass. We just don't
         // want to consider empty lines as rows in our table info.
-        if lines_before > 0 && !current_row.is_empty() {
+        if is_new_row && !current_row.is_empty() {
             rows.push(current_row);
             current_row = Vec::new();
         }
@@ -303,12 +368,72 @@ fn build_table_impl(args: &RArgumentList, f: &mut RFormatter) -> FormatResult<Op
     }))
 }
 
+impl ArgData {
+    /// Format the value of the argument, without padding or separator
+    fn fmt_value(&self, f: &mut RFormatter) -> FormatResult<()> {
+        match &self.kind {
+            ArgKind::Other { text } => {
+                let arg_syntax = self.node.syntax();
+
+                // Suppression comments do nothing inside a table
+                f.comments().mark_suppression_checked(arg_syntax);
+
+                // We've formatted the argument without comments, so
+                // we're in charge of formatting them
+                format_r_leading_comments(arg_syntax).fmt(f)?;
+
+                // 0-length arguments are holes. Don't print them
+                // because a `text("")` after a `Space` will prevent
+                // the latter from being considered trailing by the
+                // printer, and won't be removed if trailing.
+                if !text.is_empty() {
+                    write!(f, [dynamic_text(text, 0.into())])?;
+                }
+
+                format_trailing_comments(arg_syntax).fmt(f)
+            }
+
+            ArgKind::Numeric { .. } => {
+                // For numeric types, format the node directly. This
+                // handles comments as well.
+                write!(f, [self.node.format()])
+            }
+        }
+    }
+}
+
 impl ColumnInfo {
-    fn padding(&self, kind: &ArgKind) -> (usize, usize) {
+    /// Width of the column once its arguments are padded
+    fn width(&self) -> usize {
         if self.has_decimal {
-            self.decimal_padding(kind)
+            let decimal_width = self.max_integer_part + DOT_WIDTH + self.max_fractional_part;
+            self.max_value_width.max(decimal_width)
         } else {
-            self.simple_padding(kind)
+            self.max_value_width
+        }
+    }
+
+    fn padding(&self, kind: &ArgKind, alignment: Option<ColumnAlignment>) -> (usize, usize) {
+        match alignment {
+            Some(alignment) => self.aligned_padding(kind, alignment),
+            None if self.has_decimal => self.decimal_padding(kind),
+            None => self.simple_padding(kind),
+        }
+    }
+
+    fn aligned_padding(&self, kind: &ArgKind, alignment: ColumnAlignment) -> (usize, usize) {
+        // Columns with a user supplied alignment: All arguments are padded to
+        // `max_width` regardless of their kind. Decimal points are not aligned.
+        let padding = self.max_value_width.saturating_sub(kind.width());
+
+        match alignment {
+            ColumnAlignment::Left => (0, padding),
+            ColumnAlignment::Right => (padding, 0),
+            ColumnAlignment::Center => {
+                // Extra space goes to the right when padding can't be split evenly
+                let left = padding / 2;
+                (left, padding - left)
+            }
         }
     }
 
@@ -509,3 +634,262 @@ fn write_spaces(count: usize, f: &mut RFormatter) -> FormatResult<()> {
     }
     Ok(())
 }
+
+/// Parameters of `matrix()`, in order
+const MATRIX_PARAMETERS: [&str; 5] = ["data", "nrow", "ncol", "byrow", "dimnames"];
+
+/// Parameters of `array()`, in order
+const ARRAY_PARAMETERS: [&str; 3] = ["data", "dim", "dimnames"];
+
+/// Number of elements per row of a grid for the data of a `matrix()` or `array()`
+///
+/// Returns `Some` when `node` is a `c()` call of literals supplied as the data of a
+/// `matrix()` or `array()` call whose dimensions are literals too, such as
+/// `matrix(c(1, 0, 0, 1), nrow = 2, byrow = TRUE)`. With `byrow = TRUE`, each row of
+/// the grid is a row of the matrix. Otherwise R fills matrices and arrays column by
+/// column, so each row of the grid is a column of the matrix.
+pub(crate) fn matrix_grid_width(node: &RCall) -> Option<usize> {
+    if !is_call_to(node, "c") {
+        return None;
+    }
+
+    let arguments = node.arguments().ok()?;
+
+    // Comments can't be laid out in a grid
+    if arguments.syntax().has_comments_descendants() {
+        return None;
+    }
+
+    let items = arguments.items();
+    for item in items.iter() {
+        let item = item.ok()?;
+        if item.name_clause().is_some() || !item.value().is_some_and(|value| is_literal(&value)) {
+            return None;
+        }
+    }
+
+    // Find the call that `node` is an argument of
+    let argument = node.syntax().parent().and_then(RArgument::cast)?;
+    let list = argument.syntax().parent().and_then(RArgumentList::cast)?;
+    let parent = list
+        .syntax()
+        .parent()
+        .and_then(|arguments| arguments.parent())
+        .and_then(RCall::cast)?;
+
+    let width = if is_call_to(&parent, "matrix") {
+        let [data, nrow, ncol, byrow, _] = match_arguments(&list, MATRIX_PARAMETERS)?;
+
+        if data?.syntax() != argument.syntax() {
+            return None;
+        }
+
+        let nrow = literal_dimension(nrow)?;
+        let ncol = literal_dimension(ncol)?;
+
+        let byrow = match byrow.map(|byrow| byrow.value()) {
+            None => false,
+            Some(Some(AnyRExpression::RTrueExpression(_))) => true,
+            Some(Some(AnyRExpression::RFalseExpression(_))) => false,
+            Some(_) => return None,
+        };
+
+        if byrow {
+            ncol.or_else(|| nrow.map(|nrow| items.len().div_ceil(nrow)))?
+        } else {
+            nrow.or_else(|| ncol.map(|ncol| items.len().div_ceil(ncol)))?
+        }
+    } else if is_call_to(&parent, "array") {
+        let [data, dim, _] = match_arguments(&list, ARRAY_PARAMETERS)?;
+
+        if data?.syntax() != argument.syntax() {
+            return None;
+        }
+
+        // The first dimension determines the number of elements per row
+        match dim?.value()? {
+            AnyRExpression::RCall(dim) if is_call_to(&dim, "c") => {
+                let first = dim.arguments().ok()?.items().iter().next()?.ok()?;
+                if first.name_clause().is_some() {
+                    return None;
+                }
+                literal_integer(&first.value()?)?
+            }
+            dim => literal_integer(&dim)?,
+        }
+    } else {
+        return None;
+    };
+
+    // A single row isn't a grid
+    if width == 0 || width >= items.len() {
+        return None;
+    }
+
+    Some(width)
+}
+
+/// Do the rows of the grid for the data of a `matrix()` or `array()` fit?
+///
+/// Grids whose rows don't fit within the line width fall back to the usual layout.
+/// Since the final indentation isn't known while formatting, it is approximated by
+/// the nesting of calls, subsets, and braced expressions. This only depends on the
+/// syntax tree, so the choice is stable from one formatting pass to the next.
+/// Whether the data is laid out flat or as a grid is left to the printer.
+fn grid_rows_fit(node: &RCallArguments, table: &TableInfo, f: &RFormatter) -> bool {
+    let line_width = usize::from(f.options().line_width().value());
+    let indent_width = usize::from(f.options().indent_width().value());
+
+    let depth = node
+        .syntax()
+        .ancestors()
+        .skip(1)
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                RSyntaxKind::R_CALL_ARGUMENTS
+                    | RSyntaxKind::R_SUBSET_ARGUMENTS
+                    | RSyntaxKind::R_SUBSET2_ARGUMENTS
+                    | RSyntaxKind::R_BRACED_EXPRESSIONS
+            )
+        })
+        .count();
+    let indent = depth * indent_width;
+
+    // Cells are padded to the width of their column and followed by ` ,`, except
+    // for the last one. Cells are separated by a space.
+    let row_width = table
+        .rows
+        .iter()
+        .map(|row| {
+            let cells: usize = row
+                .iter()
+                .enumerate()
+                .map(|(j, cell)| {
+                    if cell.is_last_in_list {
+                        table.cols[j].width()
+                    } else {
+                        table.cols[j].width() + 2
+                    }
+                })
+                .sum();
+            cells + row.len() - 1
+        })
+        .max()
+        .unwrap_or(0);
+
+    // Rows are indented within `c(`
+    indent + indent_width + row_width <= line_width
+}
+
+fn is_call_to(node: &RCall, name: &str) -> bool {
+    call_identifier(node)
+        .ok()
+        .flatten()
+        .and_then(|identifier| identifier.name_token().ok())
+        .is_some_and(|token| token.text_trimmed() == name)
+}
+
+pub(crate) fn is_literal(value: &AnyRExpression) -> bool {
+    match value {
+        AnyRExpression::AnyRValue(value) => !matches!(value, AnyRValue::RBogusValue(_)),
+        AnyRExpression::RTrueExpression(_)
+        | AnyRExpression::RFalseExpression(_)
+        | AnyRExpression::RInfExpression(_)
+        | AnyRExpression::RNanExpression(_)
+        | AnyRExpression::RNaExpression(_) => true,
+        AnyRExpression::RUnaryExpression(unary) => {
+            unary
+                .operator()
+                .is_ok_and(|operator| matches!(operator.text_trimmed(), "+" | "-"))
+                && unary.argument().is_ok_and(|argument| {
+                    matches!(
+                        argument,
+                        AnyRExpression::AnyRValue(
+                            AnyRValue::RIntegerValue(_) | AnyRValue::RDoubleValue(_)
+                        ) | AnyRExpression::RInfExpression(_)
+                    )
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Matches the arguments of a call to `parameters`, like R does
+///
+/// Arguments are matched by exact name, then by unique partial name, then by
+/// position. Returns `None` if some arguments can't be matched, which might happen
+/// with named arguments passed through `...` or with invalid calls.
+fn match_arguments<const N: usize>(
+    list: &RArgumentList,
+    parameters: [&str; N],
+) -> Option<[Option<RArgument>; N]> {
+    let mut matched: [Option<RArgument>; N] = std::array::from_fn(|_| None);
+    let mut positional = Vec::new();
+
+    for argument in list.iter() {
+        let argument = argument.ok()?;
+
+        let Some(name_clause) = argument.name_clause() else {
+            positional.push(argument);
+            continue;
+        };
+
+        let AnyRArgumentName::RIdentifier(name) = name_clause.name().ok()? else {
+            return None;
+        };
+        let name = name.name_token().ok()?;
+        let name = name.text_trimmed();
+
+        let index = match parameters.iter().position(|parameter| *parameter == name) {
+            Some(index) => index,
+            None => {
+                let mut candidates = parameters
+                    .iter()
+                    .positions(|parameter| parameter.starts_with(name));
+                match (candidates.next(), candidates.next()) {
+                    (Some(index), None) => index,
+                    _ => return None,
+                }
+            }
+        };
+
+        if matched[index].replace(argument).is_some() {
+            return None;
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    for slot in matched.iter_mut().filter(|slot| slot.is_none()) {
+        *slot = positional.next();
+    }
+
+    if positional.next().is_some() {
+        return None;
+    }
+
+    Some(matched)
+}
+
+/// Returns `Some(None)` when the dimension is not supplied, and `None` when it is
+/// supplied but isn't a literal
+fn literal_dimension(argument: Option<RArgument>) -> Option<Option<usize>> {
+    match argument {
+        None => Some(None),
+        Some(argument) => literal_integer(&argument.value()?).map(Some),
+    }
+}
+
+fn literal_integer(value: &AnyRExpression) -> Option<usize> {
+    match value {
+        AnyRExpression::AnyRValue(AnyRValue::RIntegerValue(value)) => {
+            let token = value.value_token().ok()?;
+            token.text_trimmed().trim_end_matches('L').parse().ok()
+        }
+        AnyRExpression::AnyRValue(AnyRValue::RDoubleValue(value)) => {
+            let token = value.value_token().ok()?;
+            token.text_trimmed().parse().ok()
+        }
+        _ => None,
+    }
+}
@@ -9,8 +9,10 @@ use air_r_syntax::RLanguage;
 use air_r_syntax::RParenthesizedExpression;
 use air_r_syntax::RRepeatStatement;
 use air_r_syntax::RSyntaxKind;
+use air_r_syntax::RSyntaxNode;
 use air_r_syntax::RSyntaxToken;
 use air_r_syntax::RWhileStatement;
+use biome_formatter::FormatRefWithRule;
 use biome_formatter::comments::CommentKind;
 use biome_formatter::comments::CommentPlacement;
 use biome_formatter::comments::CommentStyle;
@@ -22,6 +24,7 @@ use biome_formatter::write;
 use biome_rowan::AstNode;
 use biome_rowan::SyntaxTriviaPieceComments;
 
+use crate::comments_wrap::WrappedParagraph;
 use crate::prelude::*;
 
 pub type RComments = Comments<RLanguage>;
@@ -37,12 +40,220 @@ impl FormatRule<SourceComment<RLanguage>> for FormatRLeadingComment {
         comment: &SourceComment<RLanguage>,
         f: &mut Formatter<Self::Context>,
     ) -> FormatResult<()> {
+        if f.options().section_comments().is_pad()
+            && let Some(section) = SectionComment::parse(comment.piece().text())
+        {
+            return fmt_section_comment(comment, &section, f);
+        }
+
         // It seems like this is only worth customizing for multi-line comments
         // that need to be aligned together
         write!(f, [comment.piece().as_piece()])
     }
 }
 
+/// Formats the leading comments of `node`
+///
+/// Like [format_leading_comments()], but with `wrap-comments = true`, the comments of
+/// a paragraph that needs to be rewrapped are formatted as one. The first comment of
+/// the paragraph is replaced by the rewrapped paragraph, and the remaining comments
+/// are removed along with the line breaks that follow them.
+pub(crate) fn format_r_leading_comments(node: &RSyntaxNode) -> FormatRLeadingComments<'_> {
+    FormatRLeadingComments { node }
+}
+
+pub(crate) struct FormatRLeadingComments<'a> {
+    node: &'a RSyntaxNode,
+}
+
+impl Format<RFormatContext> for FormatRLeadingComments<'_> {
+    fn fmt(&self, f: &mut Formatter<RFormatContext>) -> FormatResult<()> {
+        if !f.options().wrap_comments().is_wrap() {
+            return write!(f, [format_leading_comments(self.node)]);
+        }
+
+        let comments = f.context().comments().clone();
+        let leading_comments = comments.leading_comments(self.node);
+
+        let mut index = 0;
+
+        while index < leading_comments.len() {
+            let comment = &leading_comments[index];
+
+            let paragraph = WrappedParagraph::find(comment, f)
+                .filter(|paragraph| paragraph.starts(&leading_comments[index..]));
+
+            let formatted = match paragraph {
+                Some(paragraph) => {
+                    write!(f, [paragraph])?;
+                    &leading_comments[index..index + paragraph.len()]
+                }
+                None => {
+                    write!(f, [FormatRefWithRule::new(comment, FormatRLeadingComment)])?;
+                    std::slice::from_ref(comment)
+                }
+            };
+
+            // The line break of the last comment separates the paragraph from what
+            // follows, the removed comments don't write any
+            let last = &formatted[formatted.len() - 1];
+            match last.lines_after() {
+                0 | 1 => write!(f, [hard_line_break()])?,
+                _ => write!(f, [empty_line()])?,
+            }
+
+            for comment in formatted {
+                comment.mark_formatted();
+            }
+
+            index += formatted.len();
+        }
+
+        Ok(())
+    }
+}
+
+/// An RStudio-style section comment, like `# Load data ----`
+///
+/// RStudio treats comments ending in four or more `-`, `=`, or `#` as section
+/// headers. Comments without a label, like `# ----`, are plain dividers and aren't
+/// treated as sections. Neither are roxygen comments.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SectionComment<'a> {
+    /// The comment text without its trailing run of `fill` characters, like
+    /// `# Load data`
+    label: &'a str,
+
+    /// The trailing character, one of `-`, `=`, or `#`
+    fill: char,
+}
+
+impl<'a> SectionComment<'a> {
+    /// The minimum number of trailing characters that make a section comment
+    const MIN_FILL: usize = 4;
+
+    pub(crate) fn parse(text: &'a str) -> Option<Self> {
+        if text.starts_with("#'") {
+            return None;
+        }
+
+        let text = text.trim_end();
+        let fill = text.chars().next_back()?;
+        if !matches!(fill, '-' | '=' | '#') {
+            return None;
+        }
+
+        let label = text.trim_end_matches(fill);
+        if text.len() - label.len() < Self::MIN_FILL {
+            return None;
+        }
+
+        let label = label.trim_end();
+        if label.trim_start_matches('#').trim().is_empty() {
+            return None;
+        }
+
+        Some(Self { label, fill })
+    }
+
+    /// Pads the section comment with its `fill` character, up to `width`
+    ///
+    /// Always uses at least [Self::MIN_FILL] characters so that the comment remains
+    /// a section comment, even if the label is too long to fit within `width`.
+    fn padded(&self, width: usize) -> String {
+        let label_width = self.label.chars().count() + 1;
+        let count = width.saturating_sub(label_width).max(Self::MIN_FILL);
+
+        let mut out = String::with_capacity(self.label.len() + 1 + count);
+        out.push_str(self.label);
+        out.push(' ');
+        out.extend(std::iter::repeat_n(self.fill, count));
+        out
+    }
+}
+
+/// Formats a section comment with `section-comments = "pad"`
+///
+/// Only comments on their own line are padded, as a trailing comment following
+/// code can't be padded to the line width. Each section is preceded by a blank
+/// line, unless it is the first thing in the file or directly follows an opening
+/// delimiter, like `{`.
+fn fmt_section_comment(
+    comment: &SourceComment<RLanguage>,
+    section: &SectionComment,
+    f: &mut Formatter<RFormatContext>,
+) -> FormatResult<()> {
+    let piece = comment.piece();
+    let token = piece.as_piece().token();
+
+    // Own line comments are always part of the leading trivia of the next token
+    let previous_comment = token
+        .leading_trivia()
+        .pieces()
+        .take_while(|other| other.text_range() != piece.text_range())
+        .any(|other| other.is_comments());
+
+    let previous_token = token.prev_token();
+
+    let own_line = comment.lines_before() > 0 || (previous_token.is_none() && !previous_comment);
+    if !own_line {
+        return write!(f, [piece.as_piece()]);
+    }
+
+    let follows_opening_delimiter = !previous_comment
+        && previous_token.is_none_or(|previous| {
+            matches!(
+                previous.kind(),
+                RSyntaxKind::L_CURLY
+                    | RSyntaxKind::L_PAREN
+                    | RSyntaxKind::L_BRACK
+                    | RSyntaxKind::L_BRACK2
+                    | RSyntaxKind::COMMA
+            )
+        });
+
+    // Comments separated by 2+ lines are already preceded by an empty line
+    if comment.lines_before() == 1 && !follows_opening_delimiter {
+        write!(f, [empty_line()])?;
+    }
+
+    let indent = own_line_comment_indent(&token, f);
+    let width = usize::from(f.options().line_width().value()).saturating_sub(indent);
+
+    write!(
+        f,
+        [dynamic_text(
+            &section.padded(width),
+            piece.text_range().start()
+        )]
+    )
+}
+
+/// The approximate indentation of an own line comment that belongs to `token`
+///
+/// Section comments and long comment blocks are almost always found at the top level
+/// or within `{`, so only the nesting of braced expressions is accounted for.
+pub(crate) fn own_line_comment_indent(
+    token: &RSyntaxToken,
+    f: &Formatter<RFormatContext>,
+) -> usize {
+    let Some(parent) = token.parent() else {
+        return 0;
+    };
+
+    let mut depth = parent
+        .ancestors()
+        .filter(|node| node.kind() == RSyntaxKind::R_BRACED_EXPRESSIONS)
+        .count();
+
+    // A comment before `{` is outside of the braced expression
+    if token.kind() == RSyntaxKind::L_CURLY {
+        depth = depth.saturating_sub(1);
+    }
+
+    depth * usize::from(f.options().indent_width().value())
+}
+
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
 pub struct RCommentStyle;
 
@@ -8,6 +8,7 @@ use biome_rowan::SyntaxResult;
 use comments::{Directive, FormatDirective};
 use settings::Skip;
 use settings::Table;
+use settings::TableAlignment;
 
 use crate::RFormatter;
 use crate::comments::RComments;
@@ -24,6 +25,12 @@ pub trait CommentsExt {
 
     /// Does this node contain a `# fmt: table` directive?
     fn has_table_directive(&self, node: &SyntaxNode<Self::Language>) -> bool;
+
+    /// The column alignment requested by a `# fmt: table align=` directive, if any
+    fn table_alignment_directive(
+        &self,
+        node: &SyntaxNode<Self::Language>,
+    ) -> Option<TableAlignment>;
 }
 
 impl CommentsExt for RComments {
@@ -42,7 +49,26 @@ impl CommentsExt for RComments {
             return false;
         }
 
-        directives(self, node).any(|d| matches!(d, Directive::Format(FormatDirective::Table)))
+        directives(self, node).any(|d| {
+            matches!(
+                d,
+                Directive::Format(FormatDirective::Table | FormatDirective::TableAlign(_))
+            )
+        })
+    }
+
+    fn table_alignment_directive(
+        &self,
+        node: &SyntaxNode<Self::Language>,
+    ) -> Option<TableAlignment> {
+        if !can_have_directive(node) {
+            return None;
+        }
+
+        directives(self, node).find_map(|d| match d {
+            Directive::Format(FormatDirective::TableAlign(alignment)) => Some(alignment),
+            _ => None,
+        })
     }
 }
 
@@ -92,6 +118,22 @@ pub(crate) fn in_table_setting(node: &RCall, f: &RFormatter) -> SyntaxResult<boo
     in_setting(node, f.options().table(), pred)
 }
 
+/// The column alignment of this function call in the `table-alignment` `air.toml` setting
+pub(crate) fn table_alignment_setting(
+    node: &RCall,
+    f: &RFormatter,
+) -> SyntaxResult<Option<TableAlignment>> {
+    let Some(alignments) = f.options().table_alignment() else {
+        return Ok(None);
+    };
+    let Some(name) = call_identifier(node)? else {
+        return Ok(None);
+    };
+
+    let name = name.name_token()?;
+    Ok(alignments.get(name.text_trimmed()).cloned())
+}
+
 fn in_setting<T, F>(node: &RCall, options: Option<T>, pred: F) -> SyntaxResult<bool>
 where
     F: Fn(RIdentifier, T) -> SyntaxResult<bool>,
@@ -100,12 +142,20 @@ where
         return Ok(false);
     };
 
+    Ok(match call_identifier(node)? {
+        Some(node) => pred(node, options)?,
+        None => false,
+    })
+}
+
+/// The identifier naming the function of a call, like `fn` in `fn()` or `pkg::fn()`
+pub(crate) fn call_identifier(node: &RCall) -> SyntaxResult<Option<RIdentifier>> {
     Ok(match node.function()? {
-        AnyRExpression::RIdentifier(node) => pred(node, options)?,
+        AnyRExpression::RIdentifier(node) => Some(node),
         AnyRExpression::RNamespaceExpression(node) => match node.right()? {
-            AnyRSelector::RIdentifier(node) => pred(node, options)?,
-            _ => false,
+            AnyRSelector::RIdentifier(node) => Some(node),
+            _ => None,
         },
-        _ => false,
+        _ => None,
     })
 }
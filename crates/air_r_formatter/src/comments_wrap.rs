@@ -0,0 +1,443 @@
+use air_r_syntax::RLanguage;
+use air_r_syntax::RSyntaxToken;
+use biome_formatter::comments::SourceComment;
+use biome_formatter::write;
+use biome_rowan::SyntaxTriviaPieceComments;
+
+use crate::comments::SectionComment;
+use crate::comments::own_line_comment_indent;
+use crate::prelude::*;
+
+/// Roxygen tags whose content is code rather than prose
+const ROXYGEN_CODE_TAGS: &[&str] = &[
+    "@eval",
+    "@evalNamespace",
+    "@evalRd",
+    "@examples",
+    "@examplesIf",
+    "@includeRmd",
+    "@rawNamespace",
+    "@rawRd",
+    "@usage",
+];
+
+/// A paragraph of own line comments that is rewrapped with `wrap-comments = true`
+///
+/// Standalone comment blocks are wrapped one paragraph at a time, and only when one
+/// of the lines of the paragraph exceeds the line width. The rewrapped paragraph is
+/// written in place of its first comment, and the remaining comments of the paragraph
+/// are removed, see [crate::comments::FormatRLeadingComments].
+pub(crate) struct WrappedParagraph {
+    /// The comments of the paragraph, in source order
+    comments: Vec<SyntaxTriviaPieceComments<RLanguage>>,
+
+    /// The rewrapped lines of the paragraph
+    lines: Vec<String>,
+}
+
+impl WrappedParagraph {
+    /// Finds the paragraph that starts at `comment`, if it needs to be rewrapped
+    ///
+    /// Returns `None` if `comment` is a trailing comment that follows code on the same
+    /// line, if it isn't the first comment of its paragraph, or if the paragraph
+    /// already fits within the line width.
+    pub(crate) fn find(
+        comment: &SourceComment<RLanguage>,
+        f: &Formatter<RFormatContext>,
+    ) -> Option<Self> {
+        let piece = comment.piece();
+        let token = piece.as_piece().token();
+
+        let block = CommentBlock::find(&token, piece)?;
+
+        let indent = own_line_comment_indent(&token, f);
+        let width = usize::from(f.options().line_width().value()).saturating_sub(indent);
+
+        let lines: Vec<CommentLine> = block
+            .comments
+            .iter()
+            .map(|comment| CommentLine::parse(comment.text()))
+            .collect();
+
+        let paragraph = paragraph_of(&lines, block.index)?;
+
+        if paragraph.start != block.index {
+            return None;
+        }
+
+        let paragraph_lines = &lines[paragraph.clone()];
+
+        if paragraph_lines.iter().all(|line| line.width() <= width) {
+            return None;
+        }
+
+        let first = &paragraph_lines[0];
+        let words = paragraph_lines
+            .iter()
+            .flat_map(|line| line.content.split_whitespace());
+        let wrapped = wrap_words(first.prefix, first.separator, words, width);
+
+        Some(Self {
+            comments: block.comments[paragraph].to_vec(),
+            lines: wrapped,
+        })
+    }
+
+    /// The number of comments of the paragraph
+    pub(crate) fn len(&self) -> usize {
+        self.comments.len()
+    }
+
+    /// Are the comments of the paragraph the first comments of `comments`?
+    pub(crate) fn starts(&self, comments: &[SourceComment<RLanguage>]) -> bool {
+        self.comments.len() <= comments.len()
+            && self
+                .comments
+                .iter()
+                .zip(comments)
+                .all(|(paragraph, comment)| paragraph.text_range() == comment.piece().text_range())
+    }
+}
+
+impl Format<RFormatContext> for WrappedParagraph {
+    fn fmt(&self, f: &mut Formatter<RFormatContext>) -> FormatResult<()> {
+        let start = self.comments[0].text_range().start();
+
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                write!(f, [hard_line_break()])?;
+            }
+            write!(f, [dynamic_text(line, start)])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A block of consecutive own line comments, not separated by empty lines
+struct CommentBlock {
+    comments: Vec<SyntaxTriviaPieceComments<RLanguage>>,
+
+    /// The index of the comment being formatted within `comments`
+    index: usize,
+}
+
+impl CommentBlock {
+    /// Finds the comment block that `comment` belongs to
+    ///
+    /// Returns `None` if `comment` isn't part of the leading trivia of `token`, i.e.
+    /// if it is a trailing comment that follows code on the same line.
+    fn find(token: &RSyntaxToken, comment: &SyntaxTriviaPieceComments<RLanguage>) -> Option<Self> {
+        let mut comments = Vec::new();
+        let mut index = None;
+        let mut newlines = 0;
+
+        for piece in token.leading_trivia().pieces() {
+            if piece.is_newline() {
+                newlines += 1;
+                continue;
+            }
+
+            let Some(piece) = piece.as_comments() else {
+                continue;
+            };
+
+            // An empty line ends the block
+            if newlines > 1 {
+                if index.is_some() {
+                    break;
+                }
+                comments.clear();
+            }
+            newlines = 0;
+
+            if piece.text_range() == comment.text_range() {
+                index = Some(comments.len());
+            }
+            comments.push(piece);
+        }
+
+        index.map(|index| Self { comments, index })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    /// A line that is never wrapped, and never merged with its neighbours
+    Verbatim,
+
+    /// A line that starts a new paragraph, like a roxygen tag
+    Start,
+
+    /// A line of prose
+    Text,
+}
+
+#[derive(Debug)]
+struct CommentLine<'a> {
+    /// The full text of the comment
+    text: &'a str,
+
+    /// The leading `#`s of the comment, including the `'` of roxygen comments
+    prefix: &'a str,
+
+    /// The space between the prefix and the content, if any
+    separator: &'a str,
+
+    /// The content of the comment, following the prefix and separator
+    content: &'a str,
+
+    kind: LineKind,
+}
+
+impl<'a> CommentLine<'a> {
+    fn parse(text: &'a str) -> Self {
+        let mut prefix_len = text.len() - text.trim_start_matches('#').len();
+        if text[prefix_len..].starts_with('\'') {
+            prefix_len += 1;
+        }
+        let (prefix, rest) = text.split_at(prefix_len);
+
+        let (separator, content) = match rest.strip_prefix(' ') {
+            Some(content) => (" ", content),
+            None => ("", rest),
+        };
+
+        let kind = if is_verbatim(text, content) {
+            LineKind::Verbatim
+        } else if content.starts_with('@') {
+            LineKind::Start
+        } else {
+            LineKind::Text
+        };
+
+        Self {
+            text,
+            prefix,
+            separator,
+            content,
+            kind,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// The roxygen tag of this line, like `@param`
+    fn tag(&self) -> Option<&'a str> {
+        if !self.content.starts_with('@') {
+            return None;
+        }
+        self.content.split_whitespace().next()
+    }
+}
+
+/// Should this comment line be left untouched?
+fn is_verbatim(text: &str, content: &str) -> bool {
+    let trimmed = content.trim();
+
+    // Empty lines separate paragraphs
+    if trimmed.is_empty() {
+        return true;
+    }
+
+    // Shebangs and section comments
+    if content.starts_with('!') || SectionComment::parse(text).is_some() {
+        return true;
+    }
+
+    // Directives, such as `# fmt: skip`
+    if ["fmt:", "nolint", "styler:"]
+        .iter()
+        .any(|directive| trimmed.starts_with(directive))
+    {
+        return true;
+    }
+
+    // URLs can't be broken
+    if trimmed.contains("://") {
+        return true;
+    }
+
+    // Markdown headings, tables, and Quarto chunk options (`#| label: x`)
+    if trimmed.starts_with(['#', '|']) {
+        return true;
+    }
+
+    is_list_item(trimmed) || looks_like_code(content)
+}
+
+/// Does this line start a markdown list item, like `- item` or `1. item`?
+fn is_list_item(trimmed: &str) -> bool {
+    if ["- ", "* ", "+ "]
+        .iter()
+        .any(|bullet| trimmed.starts_with(bullet))
+    {
+        return true;
+    }
+
+    let number = trimmed.trim_start_matches(|c: char| c.is_ascii_digit());
+    number.len() < trimmed.len() && (number.starts_with(". ") || number.starts_with(") "))
+}
+
+/// Does this line look like R code rather than prose?
+fn looks_like_code(content: &str) -> bool {
+    // Indented content is typically code or preformatted text
+    if content.starts_with("  ") {
+        return true;
+    }
+
+    let trimmed = content.trim();
+
+    if ["<-", "->", "|>", "%>%"]
+        .iter()
+        .any(|operator| trimmed.contains(operator))
+    {
+        return true;
+    }
+
+    if trimmed.ends_with(['{', '}', '(', ';']) {
+        return true;
+    }
+
+    // A line that starts with a call, like `library(dplyr)`
+    let name = trimmed.trim_start_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '.');
+    name.len() < trimmed.len() && name.starts_with('(')
+}
+
+/// The range of the paragraph of `lines` that contains the line at `index`
+///
+/// Returns `None` if the line at `index` is verbatim.
+///
+/// A paragraph starts at a verbatim line, a line that starts a paragraph (like a
+/// roxygen tag), or a change of prefix, and continues over the following lines of
+/// prose. Lines within a fenced code block (```` ``` ````) or within a roxygen tag
+/// that contains code (like `@examples`) are verbatim.
+fn paragraph_of(lines: &[CommentLine], index: usize) -> Option<std::ops::Range<usize>> {
+    let mut kinds: Vec<LineKind> = Vec::with_capacity(lines.len());
+    let mut in_fence = false;
+    let mut in_code = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 && line.prefix != lines[i - 1].prefix {
+            in_fence = false;
+            in_code = false;
+        }
+
+        let kind = if line.content.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            LineKind::Verbatim
+        } else if in_fence {
+            LineKind::Verbatim
+        } else if let Some(tag) = line.tag() {
+            in_code = ROXYGEN_CODE_TAGS.contains(&tag);
+            if in_code {
+                LineKind::Verbatim
+            } else {
+                line.kind
+            }
+        } else if in_code {
+            LineKind::Verbatim
+        } else {
+            line.kind
+        };
+
+        kinds.push(kind);
+    }
+
+    if kinds[index] == LineKind::Verbatim {
+        return None;
+    }
+
+    let continues = |i: usize| {
+        i > 0
+            && kinds[i] == LineKind::Text
+            && kinds[i - 1] != LineKind::Verbatim
+            && lines[i].prefix == lines[i - 1].prefix
+    };
+
+    let mut start = index;
+    while continues(start) {
+        start -= 1;
+    }
+
+    let mut end = index + 1;
+    while end < lines.len() && continues(end) {
+        end += 1;
+    }
+
+    Some(start..end)
+}
+
+/// Greedily wraps `words` into lines of at most `width` characters
+///
+/// A word that is longer than `width` on its own is placed on its own line. A word
+/// that would change the meaning of a line if it started one, like a roxygen tag, is
+/// glued to the previous word, so that the two are always on the same line.
+fn wrap_words<'a>(
+    prefix: &str,
+    separator: &str,
+    words: impl Iterator<Item = &'a str>,
+    width: usize,
+) -> Vec<String> {
+    let lead = format!("{prefix}{separator}");
+    let lead_width = lead.chars().count();
+
+    // A word that would start a construct is glued to the previous word, so that it
+    // never starts a line
+    let mut units: Vec<String> = Vec::new();
+    for word in words {
+        match units.last_mut() {
+            Some(unit) if starts_construct(&lead, word) => {
+                unit.push(' ');
+                unit.push_str(word);
+            }
+            _ => units.push(word.to_string()),
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut line = lead.clone();
+    let mut line_width = lead_width;
+    let mut empty = true;
+
+    for unit in &units {
+        let unit_width = unit.chars().count();
+
+        if !empty && line_width + 1 + unit_width > width {
+            lines.push(std::mem::replace(&mut line, lead.clone()));
+            line_width = lead_width;
+            empty = true;
+        }
+
+        if !empty {
+            line.push(' ');
+            line_width += 1;
+        }
+
+        line.push_str(unit);
+        line_width += unit_width;
+        empty = false;
+    }
+
+    if !empty {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Would a line that starts with `word` no longer be a line of prose?
+///
+/// This is the case for words like `@param`, `-`, `1.`, `#`, or `fmt:`, which would
+/// turn into a roxygen tag, a list item, a heading, or a directive.
+fn starts_construct(lead: &str, word: &str) -> bool {
+    if word.starts_with("```") {
+        return true;
+    }
+
+    // Followed by another word, as list items require a space after the bullet
+    let line = format!("{lead}{word} word");
+    CommentLine::parse(&line).kind != LineKind::Text
+}
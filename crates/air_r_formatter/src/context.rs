@@ -7,14 +7,22 @@ use biome_formatter::FormatContext;
 use biome_formatter::FormatOptions;
 use biome_formatter::TransformSourceMap;
 use biome_formatter::printer::PrinterOptions;
+use settings::AsciiStrings;
 use settings::AssignmentStyle;
 use settings::IndentStyle;
 use settings::IndentWidth;
 use settings::LineEnding;
 use settings::LineWidth;
+use settings::NormalizeNames;
 use settings::PersistentLineBreaks;
+use settings::PreferRawStrings;
+use settings::RVersion;
+use settings::SectionComments;
 use settings::Skip;
 use settings::Table;
+use settings::TableAlignments;
+use settings::VectorLayout;
+use settings::WrapComments;
 
 use crate::comments::FormatRLeadingComment;
 use crate::comments::RCommentStyle;
@@ -92,6 +100,30 @@ pub struct RFormatOptions {
 
     /// The set of functions that are formatted as tables without requiring a `# fmt: table` comment.
     table: Option<Table>,
+
+    /// The column alignment of functions that are formatted as tables.
+    table_alignment: Option<TableAlignments>,
+
+    /// The layout of atomic vectors that don't fit on a single line.
+    vector_layout: VectorLayout,
+
+    /// The handling of RStudio-style section comments.
+    section_comments: SectionComments,
+
+    /// Whether or not to wrap standalone comments to the line width.
+    wrap_comments: WrapComments,
+
+    /// Whether or not to normalize the quoting of names.
+    normalize_names: NormalizeNames,
+
+    /// Whether or not to escape non-ASCII characters in strings.
+    ascii_strings: AsciiStrings,
+
+    /// The minimum number of backslash escapes of strings rewritten as raw strings.
+    prefer_raw_strings: Option<PreferRawStrings>,
+
+    /// The minimum R version that the formatted code must support.
+    minimum_r_version: Option<RVersion>,
 }
 
 impl RFormatOptions {
@@ -144,6 +176,46 @@ impl RFormatOptions {
         self
     }
 
+    pub fn with_table_alignment(mut self, table_alignment: Option<TableAlignments>) -> Self {
+        self.table_alignment = table_alignment;
+        self
+    }
+
+    pub fn with_vector_layout(mut self, vector_layout: VectorLayout) -> Self {
+        self.vector_layout = vector_layout;
+        self
+    }
+
+    pub fn with_section_comments(mut self, section_comments: SectionComments) -> Self {
+        self.section_comments = section_comments;
+        self
+    }
+
+    pub fn with_wrap_comments(mut self, wrap_comments: WrapComments) -> Self {
+        self.wrap_comments = wrap_comments;
+        self
+    }
+
+    pub fn with_normalize_names(mut self, normalize_names: NormalizeNames) -> Self {
+        self.normalize_names = normalize_names;
+        self
+    }
+
+    pub fn with_ascii_strings(mut self, ascii_strings: AsciiStrings) -> Self {
+        self.ascii_strings = ascii_strings;
+        self
+    }
+
+    pub fn with_prefer_raw_strings(mut self, prefer_raw_strings: Option<PreferRawStrings>) -> Self {
+        self.prefer_raw_strings = prefer_raw_strings;
+        self
+    }
+
+    pub fn with_minimum_r_version(mut self, minimum_r_version: Option<RVersion>) -> Self {
+        self.minimum_r_version = minimum_r_version;
+        self
+    }
+
     pub fn set_indent_style(&mut self, indent_style: IndentStyle) {
         self.indent_style = indent_style;
     }
@@ -176,6 +248,38 @@ impl RFormatOptions {
         self.table = table;
     }
 
+    pub fn set_table_alignment(&mut self, table_alignment: Option<TableAlignments>) {
+        self.table_alignment = table_alignment;
+    }
+
+    pub fn set_vector_layout(&mut self, vector_layout: VectorLayout) {
+        self.vector_layout = vector_layout;
+    }
+
+    pub fn set_section_comments(&mut self, section_comments: SectionComments) {
+        self.section_comments = section_comments;
+    }
+
+    pub fn set_wrap_comments(&mut self, wrap_comments: WrapComments) {
+        self.wrap_comments = wrap_comments;
+    }
+
+    pub fn set_normalize_names(&mut self, normalize_names: NormalizeNames) {
+        self.normalize_names = normalize_names;
+    }
+
+    pub fn set_ascii_strings(&mut self, ascii_strings: AsciiStrings) {
+        self.ascii_strings = ascii_strings;
+    }
+
+    pub fn set_prefer_raw_strings(&mut self, prefer_raw_strings: Option<PreferRawStrings>) {
+        self.prefer_raw_strings = prefer_raw_strings;
+    }
+
+    pub fn set_minimum_r_version(&mut self, minimum_r_version: Option<RVersion>) {
+        self.minimum_r_version = minimum_r_version;
+    }
+
     pub fn persistent_line_breaks(&self) -> PersistentLineBreaks {
         self.persistent_line_breaks
     }
@@ -191,6 +295,38 @@ impl RFormatOptions {
     pub fn table(&self) -> Option<&Table> {
         self.table.as_ref()
     }
+
+    pub fn table_alignment(&self) -> Option<&TableAlignments> {
+        self.table_alignment.as_ref()
+    }
+
+    pub fn vector_layout(&self) -> VectorLayout {
+        self.vector_layout
+    }
+
+    pub fn section_comments(&self) -> SectionComments {
+        self.section_comments
+    }
+
+    pub fn wrap_comments(&self) -> WrapComments {
+        self.wrap_comments
+    }
+
+    pub fn normalize_names(&self) -> NormalizeNames {
+        self.normalize_names
+    }
+
+    pub fn ascii_strings(&self) -> AsciiStrings {
+        self.ascii_strings
+    }
+
+    pub fn prefer_raw_strings(&self) -> Option<PreferRawStrings> {
+        self.prefer_raw_strings
+    }
+
+    pub fn minimum_r_version(&self) -> Option<RVersion> {
+        self.minimum_r_version
+    }
 }
 
 impl FormatOptions for RFormatOptions {
@@ -229,6 +365,30 @@ impl fmt::Display for RFormatOptions {
         if let Some(table) = &self.table {
             writeln!(f, "Table: {table}")?;
         };
+        if let Some(table_alignment) = &self.table_alignment {
+            writeln!(f, "Table alignment: {table_alignment}")?;
+        };
+        if self.vector_layout.is_fill() {
+            writeln!(f, "Vector layout: {}", self.vector_layout)?;
+        };
+        if self.section_comments.is_pad() {
+            writeln!(f, "Section comments: {}", self.section_comments)?;
+        };
+        if self.wrap_comments.is_wrap() {
+            writeln!(f, "Wrap comments: {}", self.wrap_comments)?;
+        };
+        if self.normalize_names.is_normalize() {
+            writeln!(f, "Normalize names: {}", self.normalize_names)?;
+        };
+        if self.ascii_strings.is_escape() {
+            writeln!(f, "ASCII strings: {}", self.ascii_strings)?;
+        };
+        if let Some(prefer_raw_strings) = &self.prefer_raw_strings {
+            writeln!(f, "Prefer raw strings: {prefer_raw_strings}")?;
+        };
+        if let Some(minimum_r_version) = &self.minimum_r_version {
+            writeln!(f, "Minimum R version: {minimum_r_version}")?;
+        };
         Ok(())
     }
 }
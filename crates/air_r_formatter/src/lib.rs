@@ -13,24 +13,28 @@ use biome_formatter::write;
 use biome_rowan::AstNode;
 
 use crate::comments::RCommentStyle;
+use crate::comments::format_r_leading_comments;
 use crate::comments_ext::CommentsExt;
 use crate::context::RFormatContext;
 use crate::context::RFormatOptions;
 use crate::cst::FormatRSyntaxNode;
 
+pub(crate) mod call_arguments_fill;
 pub(crate) mod call_arguments_table;
 pub mod comments;
 pub mod comments_ext;
+pub(crate) mod comments_wrap;
 pub mod context;
 mod cst;
 pub mod either;
 pub mod formatter_ext;
 pub mod joiner_ext;
 pub mod loop_body;
+mod names;
 mod prelude;
 mod r;
 pub(crate) mod separated;
-mod string_content;
+pub mod string_content;
 
 #[rustfmt::skip]
 mod generated;
@@ -233,7 +237,7 @@ where
     /// You may want to override this method if you want to manually handle the formatting of comments
     /// inside of the `fmt_fields` method or customize the formatting of the leading comments.
     fn fmt_leading_comments(&self, node: &N, f: &mut RFormatter) -> FormatResult<()> {
-        format_leading_comments(node.syntax()).fmt(f)
+        format_r_leading_comments(node.syntax()).fmt(f)
     }
 
     /// Formats the [dangling comments](biome_formatter::comments#dangling-comments) of the node.
@@ -0,0 +1,75 @@
+//! Helpers for `normalize-names = true`
+
+/// Is `name` a syntactic R name, i.e. one that can be used without backticks?
+///
+/// Syntactic names consist of letters, digits, `.`, and `_`, and start with a letter
+/// or with a `.` that isn't followed by a digit. Reserved words, like `if` or `TRUE`,
+/// aren't syntactic names. Only ASCII letters are considered, as whether other
+/// letters are valid depends on the locale R is running in.
+pub(crate) fn is_syntactic_name(name: &str) -> bool {
+    let valid_start = match name.chars().next() {
+        Some('a'..='z' | 'A'..='Z') => true,
+        Some('.') => !name[1..].starts_with(|c: char| c.is_ascii_digit()),
+        _ => false,
+    };
+
+    valid_start
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+        && !is_reserved_word(name)
+}
+
+fn is_reserved_word(name: &str) -> bool {
+    if matches!(
+        name,
+        "if" | "else"
+            | "repeat"
+            | "while"
+            | "function"
+            | "for"
+            | "in"
+            | "next"
+            | "break"
+            | "TRUE"
+            | "FALSE"
+            | "NULL"
+            | "Inf"
+            | "NaN"
+            | "NA"
+            | "NA_integer_"
+            | "NA_real_"
+            | "NA_character_"
+            | "NA_complex_"
+            | "..."
+    ) {
+        return true;
+    }
+
+    // `..1`, `..2`, etc.
+    name.strip_prefix("..")
+        .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The name of a backticked identifier, like `` `x` ``, if the backticks are needless
+pub(crate) fn unquoted_identifier_name(text: &str) -> Option<&str> {
+    let name = text.strip_prefix('`')?.strip_suffix('`')?;
+    is_syntactic_name(name).then_some(name)
+}
+
+/// The normalized form of a string argument name with contents `content`
+///
+/// Syntactic names are returned as is, and all other names are quoted with
+/// backticks. Returns `None` for names that can't be safely converted, i.e. empty
+/// names and names containing escapes, backticks, or line breaks.
+pub(crate) fn normalized_string_name(content: &str) -> Option<String> {
+    if content.is_empty() || content.contains(['\\', '`', '\n', '\r']) {
+        return None;
+    }
+
+    if is_syntactic_name(content) {
+        Some(content.to_string())
+    } else {
+        Some(format!("`{content}`"))
+    }
+}
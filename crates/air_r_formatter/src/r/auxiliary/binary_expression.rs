@@ -1,4 +1,5 @@
 use crate::comments_ext::CommentsExt;
+use crate::comments_ext::table_alignment_setting;
 use crate::context::RFormatOptions;
 use crate::either::Either;
 use crate::prelude::*;
@@ -26,6 +27,7 @@ use biome_rowan::AstNode;
 use biome_rowan::SyntaxResult;
 use biome_rowan::SyntaxToken;
 use settings::AssignmentStyle;
+use settings::TableAlignment;
 
 #[derive(Default, Debug, Clone, Copy)]
 pub(crate) enum ChainAlignment {
@@ -208,6 +210,7 @@ fn fmt_binary_assignment(
     // Check for table directive here to simplify lifetimes with
     // `format_assignment_rhs()`
     let table = f.comments().has_table_directive(node.syntax());
+    let table_alignment = f.comments().table_alignment_directive(node.syntax());
 
     let right_format = format_with(|f| {
         if binary_assignment_has_persistent_line_break(&operator, &right, f.options()) {
@@ -217,11 +220,21 @@ fn fmt_binary_assignment(
                         alignment: ChainAlignment::LeftAligned,
                     }))
                 }
-                right => Either::Right(format_assignment_rhs(right, table)),
+                right => Either::Right(format_assignment_rhs(
+                    right,
+                    table,
+                    table_alignment.as_ref(),
+                )),
             };
             write!(f, [indent(&format_args![hard_line_break(), right])])
         } else {
-            write!(f, [space(), format_assignment_rhs(&right, table)])
+            write!(
+                f,
+                [
+                    space(),
+                    format_assignment_rhs(&right, table, table_alignment.as_ref())
+                ]
+            )
         }
     });
 
@@ -440,10 +453,20 @@ fn can_normalize_to_equal(node: &RBinaryExpression) -> bool {
     false
 }
 
-fn format_assignment_rhs(right: &AnyRExpression, table: bool) -> impl Format<RFormatContext> {
+fn format_assignment_rhs(
+    right: &AnyRExpression,
+    table: bool,
+    table_alignment: Option<&TableAlignment>,
+) -> impl Format<RFormatContext> {
     format_with(move |f| {
         if table && let AnyRExpression::RCall(call) = right {
-            let options = FormatRCallArgumentsOptions { table: true };
+            let options = FormatRCallArgumentsOptions {
+                table: true,
+                table_alignment: table_alignment
+                    .cloned()
+                    .or_else(|| table_alignment_setting(call, f).ok().flatten()),
+                grid: None,
+            };
             return write!(f, [call.format().with_options(options)]);
         }
         write!(f, [right.format()])
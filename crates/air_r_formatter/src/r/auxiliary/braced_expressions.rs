@@ -1,4 +1,5 @@
 use crate::comments::RComments;
+use crate::comments::format_r_leading_comments;
 use crate::prelude::*;
 use air_r_syntax::AnyRExpression;
 use air_r_syntax::RArgument;
@@ -269,7 +270,7 @@ fn fmt_curly_curly(node: &RCurlyCurly, f: &mut RFormatter) -> FormatResult<()> {
     write!(
         f,
         [group(&format_args![
-            format_leading_comments(node_inner),
+            format_r_leading_comments(node_inner),
             outer_l_curly_token.format(),
             inner_l_curly_token.format(),
             soft_space_or_block_indent(&expression.format()),
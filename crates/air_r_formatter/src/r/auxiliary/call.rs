@@ -1,6 +1,8 @@
+use crate::call_arguments_table::matrix_grid_width;
 use crate::comments_ext::CommentsExt;
 use crate::comments_ext::in_skip_setting;
 use crate::comments_ext::in_table_setting;
+use crate::comments_ext::table_alignment_setting;
 use crate::prelude::*;
 use crate::r::auxiliary::call_arguments::FormatRCallArgumentsOptions;
 
@@ -11,7 +13,7 @@ use biome_formatter::write;
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct FormatRCall {
-    table: Option<bool>,
+    options: Option<FormatRCallArgumentsOptions>,
 }
 
 impl FormatNodeRule<RCall> for FormatRCall {
@@ -21,8 +23,10 @@ impl FormatNodeRule<RCall> for FormatRCall {
             arguments,
         } = node.as_fields();
 
-        let table = self.table.unwrap_or_else(|| is_table(node, f));
-        let options = FormatRCallArgumentsOptions { table };
+        let options = match &self.options {
+            Some(options) => options.clone(),
+            None => table_options(node, f),
+        };
 
         write!(
             f,
@@ -37,16 +41,39 @@ impl FormatNodeRule<RCall> for FormatRCall {
     }
 }
 
-fn is_table(node: &RCall, f: &RFormatter) -> bool {
+fn table_options(node: &RCall, f: &RFormatter) -> FormatRCallArgumentsOptions {
     let comments = f.comments();
-    comments.has_table_directive(node.syntax()) || in_table_setting(node, f).unwrap_or(false)
+    let table =
+        comments.has_table_directive(node.syntax()) || in_table_setting(node, f).unwrap_or(false);
+
+    if !table {
+        // The literal data of a `matrix()` or `array()` call is laid out as a grid
+        let grid = matrix_grid_width(node);
+
+        return FormatRCallArgumentsOptions {
+            table: grid.is_some(),
+            table_alignment: None,
+            grid,
+        };
+    }
+
+    // Alignment requested by a directive takes precedence over the `air.toml` setting
+    let table_alignment = comments
+        .table_alignment_directive(node.syntax())
+        .or_else(|| table_alignment_setting(node, f).ok().flatten());
+
+    FormatRCallArgumentsOptions {
+        table,
+        table_alignment,
+        grid: None,
+    }
 }
 
 impl FormatRuleWithOptions<RCall> for FormatRCall {
     type Options = FormatRCallArgumentsOptions;
 
     fn with_options(mut self, options: Self::Options) -> Self {
-        self.table = Some(options.table);
+        self.options = Some(options);
         self
     }
 }
@@ -1,5 +1,6 @@
 use std::cell::Cell;
 
+use crate::call_arguments_fill::is_fill_vector;
 use crate::comments::RComments;
 use crate::context::RFormatOptions;
 use crate::either::Either;
@@ -25,15 +26,22 @@ use biome_formatter::separated::TrailingSeparator;
 use biome_formatter::{VecBuffer, format_args, format_element, write};
 use biome_rowan::{AstSeparatedElement, AstSeparatedList, SyntaxResult};
 use itertools::Itertools;
+use settings::TableAlignment;
 
 #[derive(Debug, Clone, Default)]
 pub struct FormatRCallArgumentsOptions {
     pub table: bool,
+    pub table_alignment: Option<TableAlignment>,
+    /// Number of arguments per row when the table rows are determined by a grid
+    /// (like the data of a `matrix()`) rather than by existing line breaks.
+    pub grid: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct FormatRCallArguments {
     table: bool,
+    table_alignment: Option<TableAlignment>,
+    grid: Option<usize>,
 }
 
 impl FormatRCallArguments {
@@ -44,6 +52,14 @@ impl FormatRCallArguments {
     ) -> FormatResult<()> {
         RCallLikeArguments::Call(node.clone()).fmt(f)
     }
+
+    pub(crate) fn table_alignment(&self) -> Option<&TableAlignment> {
+        self.table_alignment.as_ref()
+    }
+
+    pub(crate) fn grid(&self) -> Option<usize> {
+        self.grid
+    }
 }
 
 impl FormatNodeRule<RCallArguments> for FormatRCallArguments {
@@ -53,16 +69,23 @@ impl FormatNodeRule<RCallArguments> for FormatRCallArguments {
             let snapshot = f.state_snapshot();
             if let Some(()) = self.fmt_table(node, f)? {
                 return Ok(());
-            } else {
-                f.restore_state_snapshot(snapshot);
-                // Table formatting failed, fall back to verbatim. Ideally
-                // we'd emit diagnostics about why tabular formatting failed
-                // here.
+            }
+
+            f.restore_state_snapshot(snapshot);
+
+            // Table formatting failed, fall back to verbatim. Ideally we'd emit
+            // diagnostics about why tabular formatting failed here. Grids are not
+            // requested by the user though, so they fall back to the usual layout.
+            if self.grid.is_none() {
                 write!(f, [format_verbatim_node(node.syntax())])?;
                 return Ok(());
             }
         }
 
+        if f.options().vector_layout().is_fill() && is_fill_vector(node) {
+            return self.fmt_fill(node, f);
+        }
+
         self.fmt_call_like(node, f)
     }
 
@@ -78,6 +101,8 @@ impl FormatRuleWithOptions<RCallArguments> for FormatRCallArguments {
 
     fn with_options(mut self, options: Self::Options) -> Self {
         self.table = options.table;
+        self.table_alignment = options.table_alignment;
+        self.grid = options.grid;
         self
     }
 }
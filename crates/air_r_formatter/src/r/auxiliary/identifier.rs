@@ -1,3 +1,4 @@
+use crate::names::unquoted_identifier_name;
 use crate::prelude::*;
 use air_r_syntax::RIdentifier;
 use air_r_syntax::RIdentifierFields;
@@ -8,6 +9,21 @@ pub(crate) struct FormatRIdentifier;
 impl FormatNodeRule<RIdentifier> for FormatRIdentifier {
     fn fmt_fields(&self, node: &RIdentifier, f: &mut RFormatter) -> FormatResult<()> {
         let RIdentifierFields { name_token } = node.as_fields();
+        let name_token = name_token?;
+
+        if f.options().normalize_names().is_normalize()
+            && let Some(name) = unquoted_identifier_name(name_token.text_trimmed())
+        {
+            // Remove needless backticks, i.e. `` `x` `` becomes `x`
+            return write!(
+                f,
+                [format_replaced(
+                    &name_token,
+                    &dynamic_text(name, name_token.text_trimmed_range().start())
+                )]
+            );
+        }
+
         write![f, [name_token.format()]]
     }
 }
@@ -1,13 +1,32 @@
+use crate::names::normalized_string_name;
 use crate::prelude::*;
 use crate::string_content::FormatStringContentToken;
+use crate::string_content::is_raw_string;
+use crate::string_content::raw_string;
+use air_r_syntax::RArgumentNameClause;
 use air_r_syntax::RStringValue;
 use air_r_syntax::RStringValueFields;
 use biome_formatter::write;
+use settings::RVersion;
+
+/// The R version that introduced raw strings
+const RAW_STRINGS_R_VERSION: RVersion = RVersion::new(4, 0, 0);
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct FormatRStringValue;
 impl FormatNodeRule<RStringValue> for FormatRStringValue {
     fn fmt_fields(&self, node: &RStringValue, f: &mut RFormatter) -> FormatResult<()> {
+        if f.options().normalize_names().is_normalize()
+            && node.parent::<RArgumentNameClause>().is_some()
+            && let Some(name) = normalized_argument_name(node, f)
+        {
+            return fmt_replaced_string(node, &name, f);
+        }
+
+        if let Some(raw) = preferred_raw_string(node, f) {
+            return fmt_replaced_string(node, &raw, f);
+        }
+
         let RStringValueFields {
             open_token,
             content_token,
@@ -17,7 +36,13 @@ impl FormatNodeRule<RStringValue> for FormatRStringValue {
         write!(f, [open_token.format()])?;
 
         if let Some(content_token) = content_token {
-            write!(f, [FormatStringContentToken::new(&content_token)])?;
+            let escape_non_ascii = f.options().ascii_strings().is_escape() && !is_raw_string(node);
+
+            write!(
+                f,
+                [FormatStringContentToken::new(&content_token)
+                    .with_escape_non_ascii(escape_non_ascii)]
+            )?;
         }
 
         write!(f, [close_token.format()])?;
@@ -25,3 +50,90 @@ impl FormatNodeRule<RStringValue> for FormatRStringValue {
         Ok(())
     }
 }
+
+/// The normalized form of a string argument name, like `list("a" = 1)`
+///
+/// Raw strings are left as is, and so are non-ASCII names with `ascii-strings = true`,
+/// as they can only be escaped within a string.
+fn normalized_argument_name(node: &RStringValue, f: &RFormatter) -> Option<String> {
+    let open_token = node.open_token().ok()?;
+    if !matches!(open_token.text_trimmed(), "\"" | "'") {
+        return None;
+    }
+
+    let content = node.content_token()?;
+    let content = content.text_trimmed();
+
+    if f.options().ascii_strings().is_escape() && !content.is_ascii() {
+        return None;
+    }
+
+    normalized_string_name(content)
+}
+
+/// The raw string form of a string with `prefer-raw-strings`, like `r"(\d+)"`
+///
+/// Argument names are left as is, and so are non-ASCII strings with
+/// `ascii-strings = true`, as raw strings can't contain escapes. Raw strings are
+/// never introduced when `minimum-r-version` predates them.
+fn preferred_raw_string(node: &RStringValue, f: &RFormatter) -> Option<String> {
+    let min_escapes = f.options().prefer_raw_strings()?.min_escapes();
+
+    if f.options()
+        .minimum_r_version()
+        .is_some_and(|version| version < RAW_STRINGS_R_VERSION)
+    {
+        return None;
+    }
+
+    if node.parent::<RArgumentNameClause>().is_some() {
+        return None;
+    }
+
+    let open_token = node.open_token().ok()?;
+    let quote = open_token.text_trimmed();
+    if !matches!(quote, "\"" | "'") {
+        return None;
+    }
+
+    let content = node.content_token()?;
+    let content = line_ending::normalize_ref(content.text_trimmed());
+
+    if f.options().ascii_strings().is_escape() && !content.is_ascii() {
+        return None;
+    }
+
+    raw_string(quote, &content, min_escapes)
+}
+
+/// Formats a string as a different token, like a bare identifier or a raw string
+///
+/// The whole replacement is written in place of the opening quote, and the content
+/// and closing quote are removed.
+fn fmt_replaced_string(
+    node: &RStringValue,
+    replacement: &str,
+    f: &mut RFormatter,
+) -> FormatResult<()> {
+    let RStringValueFields {
+        open_token,
+        content_token,
+        close_token,
+    } = node.as_fields();
+
+    let open_token = open_token?;
+
+    write!(
+        f,
+        [format_replaced(
+            &open_token,
+            &dynamic_text(replacement, open_token.text_trimmed_range().start())
+        )]
+    )?;
+
+    if let Some(content_token) = content_token {
+        write!(f, [format_removed(&content_token)])?;
+    }
+
+    write!(f, [format_removed(&close_token?)])
+}
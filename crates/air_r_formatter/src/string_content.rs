@@ -1,9 +1,13 @@
+use air_r_syntax::RStringValue;
 use air_r_syntax::RSyntaxKind::STRING_CONTENT;
+use air_r_syntax::RSyntaxNode;
 use air_r_syntax::RSyntaxToken;
 use biome_formatter::Format;
 use biome_formatter::FormatResult;
 use biome_formatter::prelude::syntax_token_cow_slice;
 use biome_formatter::trivia::format_replaced;
+use biome_rowan::AstNode;
+use biome_rowan::TextRange;
 use std::borrow::Cow;
 
 use crate::RFormatter;
@@ -16,11 +20,22 @@ use crate::context::RFormatContext;
 pub(crate) struct FormatStringContentToken<'token> {
     /// The string content token to format
     token: &'token RSyntaxToken,
+
+    /// Whether or not to escape non-ASCII characters, see [escape_non_ascii()]
+    escape_non_ascii: bool,
 }
 
 impl<'token> FormatStringContentToken<'token> {
     pub(crate) fn new(token: &'token RSyntaxToken) -> Self {
-        Self { token }
+        Self {
+            token,
+            escape_non_ascii: false,
+        }
+    }
+
+    pub(crate) fn with_escape_non_ascii(mut self, escape_non_ascii: bool) -> Self {
+        self.escape_non_ascii = escape_non_ascii;
+        self
     }
 }
 
@@ -29,7 +44,7 @@ impl Format<RFormatContext> for FormatStringContentToken<'_> {
         format_replaced(
             self.token,
             &syntax_token_cow_slice(
-                normalize_string_content_token(self.token),
+                normalize_string_content_token(self.token, self.escape_non_ascii),
                 self.token,
                 self.token.text_trimmed_range().start(),
             ),
@@ -41,8 +56,9 @@ impl Format<RFormatContext> for FormatStringContentToken<'_> {
 /// Normalize `STRING_CONTENT` text, returning a [`Cow::Borrowed`] if the text was already
 /// normalized
 ///
-/// Currently the only normalization this does is to convert `\r\n` to `\n`. We may do
-/// more normalization in the future (like, `quote-style`).
+/// This converts `\r\n` to `\n`, and escapes non-ASCII characters when
+/// `escape_non_ascii` is `true`. We may do more normalization in the future (like,
+/// `quote-style`).
 ///
 /// This function is particularly useful for multiline strings, which capture the existing
 /// line ending inside the string content token itself. We must normalize those line
@@ -53,7 +69,7 @@ impl Format<RFormatContext> for FormatStringContentToken<'_> {
 /// https://github.com/biomejs/biome/blob/a658a294087c143b83350cbeb6b44f7a2e9afdd1/crates/biome_formatter/src/printer/mod.rs#L714-L718
 ///
 /// https://github.com/posit-dev/air/pull/127
-fn normalize_string_content_token(token: &RSyntaxToken) -> Cow<'_, str> {
+fn normalize_string_content_token(token: &RSyntaxToken, escape_non_ascii: bool) -> Cow<'_, str> {
     debug_assert!(
         matches!(token.kind(), STRING_CONTENT),
         "Found kind {:?}",
@@ -63,5 +79,116 @@ fn normalize_string_content_token(token: &RSyntaxToken) -> Cow<'_, str> {
         token.text() == token.text_trimmed(),
         "String content tokens should never have trivia. Trivia should be on string open or string close tokens instead."
     );
-    line_ending::normalize_ref(token.text())
+    let text = line_ending::normalize_ref(token.text());
+
+    if !escape_non_ascii {
+        return text;
+    }
+
+    match self::escape_non_ascii(&text) {
+        Some(escaped) => Cow::Owned(escaped),
+        None => text,
+    }
+}
+
+/// Is this string a raw string, like `r"(...)"`?
+pub(crate) fn is_raw_string(node: &RStringValue) -> bool {
+    node.open_token()
+        .is_ok_and(|token| token.text_trimmed().starts_with(['r', 'R']))
+}
+
+/// Escapes the non-ASCII characters of the contents of a (non-raw) string
+///
+/// Characters in the Basic Multilingual Plane are escaped as `\uXXXX`, and all other
+/// characters as `\U{XXXXXX}`. Returns `None` if there is nothing to escape, or if
+/// the string contains `\x` or octal escapes, as R doesn't allow mixing those with
+/// Unicode escapes.
+pub fn escape_non_ascii(content: &str) -> Option<String> {
+    if content.is_ascii() || has_byte_escapes(content) {
+        return None;
+    }
+
+    let mut out = String::with_capacity(content.len() + 8);
+
+    for c in content.chars() {
+        if c.is_ascii() {
+            out.push(c);
+        } else if u32::from(c) <= 0xFFFF {
+            out.push_str(&format!("\\u{:04x}", u32::from(c)));
+        } else {
+            out.push_str(&format!("\\U{{{:06x}}}", u32::from(c)));
+        }
+    }
+
+    Some(out)
+}
+
+/// Does the string contain a `\x` hex escape or an octal escape, like `\0`?
+fn has_byte_escapes(content: &str) -> bool {
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+
+        // Skip the escaped character, so that `\\x` isn't seen as an escape
+        match chars.next() {
+            Some('x' | '0'..='7') => return true,
+            Some(_) | None => continue,
+        }
+    }
+
+    false
+}
+
+/// Rewrites the contents of a (non-raw) string as a raw string, like `r"(\d+)"`
+///
+/// Only strings with at least `min_escapes` backslash escapes (`\\`) are rewritten,
+/// and only when all of their escapes can be expressed in a raw string, i.e. escaped
+/// backslashes and quotes. The number of dashes of the raw string delimiters is the
+/// smallest one that doesn't collide with the contents of the string.
+pub(crate) fn raw_string(quote: &str, content: &str, min_escapes: usize) -> Option<String> {
+    let mut unescaped = String::with_capacity(content.len());
+    let mut backslashes = 0;
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => {
+                backslashes += 1;
+                unescaped.push('\\');
+            }
+            c @ ('"' | '\'') => unescaped.push(c),
+            _ => return None,
+        }
+    }
+
+    if backslashes < min_escapes {
+        return None;
+    }
+
+    let dashes = (0..)
+        .map(|n| "-".repeat(n))
+        .find(|dashes| !unescaped.contains(&format!("){dashes}{quote}")))?;
+
+    Some(format!("r{quote}{dashes}({unescaped}){dashes}{quote}"))
+}
+
+/// Finds the contents of strings that would be rewritten by `ascii-strings = true`
+///
+/// Used to report the location of non-ASCII strings in `air format --check`.
+pub fn non_ascii_strings(root: &RSyntaxNode) -> Vec<TextRange> {
+    root.descendants()
+        .filter_map(RStringValue::cast)
+        .filter(|node| !is_raw_string(node))
+        .filter_map(|node| node.content_token())
+        .filter(|token| escape_non_ascii(token.text_trimmed()).is_some())
+        .map(|token| token.text_trimmed_range())
+        .collect()
 }
@@ -59,7 +59,7 @@ fn format_options_for_test(code: &str) -> RFormatOptions {
 
     let settings = workspace::toml::parse_air_inline_toml(&contents)
         .expect("Can parse inline TOML")
-        .into_settings(root)
+        .into_settings(root, None)
         .unwrap();
 
     settings.format.to_format_options(code)
@@ -1,6 +1,7 @@
 mod error;
 mod options;
 mod parse;
+mod r_version;
 
 #[allow(unused)]
 mod treesitter;
@@ -10,6 +11,7 @@ pub use error::ParseError;
 pub use options::RParserOptions;
 pub use parse::Parse;
 pub use parse::parse;
+pub use r_version::check_minimum_r_version;
 
 use air_r_syntax::RLanguage;
 use biome_parser::tree_sink::LosslessTreeSink;
@@ -1,3 +1,22 @@
+use settings::RVersion;
+
 /// Options to pass to the R parser
 #[derive(Default, Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
-pub struct RParserOptions {}
+pub struct RParserOptions {
+    /// The minimum R version that the parsed code must support
+    ///
+    /// When set, syntax that requires a more recent version of R, like the `|>`
+    /// pipe, is a parse error.
+    minimum_r_version: Option<RVersion>,
+}
+
+impl RParserOptions {
+    pub fn with_minimum_r_version(mut self, minimum_r_version: Option<RVersion>) -> Self {
+        self.minimum_r_version = minimum_r_version;
+        self
+    }
+
+    pub fn minimum_r_version(&self) -> Option<RVersion> {
+        self.minimum_r_version
+    }
+}
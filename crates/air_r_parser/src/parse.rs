@@ -16,6 +16,7 @@ use tree_sitter::Tree;
 use crate::ParseError;
 use crate::RLosslessTreeSink;
 use crate::RParserOptions;
+use crate::check_minimum_r_version;
 use crate::treesitter::NodeTypeExt;
 use crate::treesitter::Preorder;
 use crate::treesitter::WalkEvent;
@@ -96,7 +97,15 @@ impl From<Parse> for AnyParse {
 pub fn parse(text: &str, options: RParserOptions) -> Parse {
     let mut cache = NodeCache::default();
     let (events, tokens, errors) = parse_text(text, options);
-    build_tree(text, events, tokens, errors, &mut cache)
+    let mut parse = build_tree(text, events, tokens, errors, &mut cache);
+
+    if let Some(minimum_r_version) = options.minimum_r_version()
+        && !parse.has_error()
+    {
+        parse.error = check_minimum_r_version(text, &parse.syntax(), minimum_r_version).err();
+    }
+
+    parse
 }
 
 fn build_tree(
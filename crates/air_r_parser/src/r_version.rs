@@ -0,0 +1,101 @@
+use air_r_syntax::RSyntaxKind;
+use air_r_syntax::RSyntaxNode;
+use air_r_syntax::RSyntaxToken;
+use settings::RVersion;
+
+use crate::ParseError;
+
+/// Checks that `root` doesn't use syntax that requires a more recent version of R
+/// than `minimum_r_version`
+///
+/// Returns an error pointing at the first offending syntax:
+///
+/// - Raw strings, like `r"(...)"`, require R 4.0.0.
+/// - The `|>` pipe and the `\(x)` lambda syntax require R 4.1.0.
+/// - The `_` placeholder of the `|>` pipe requires R 4.2.0.
+pub fn check_minimum_r_version(
+    text: &str,
+    root: &RSyntaxNode,
+    minimum_r_version: RVersion,
+) -> Result<(), ParseError> {
+    for token in root.descendants_tokens(biome_rowan::Direction::Next) {
+        let Some((syntax, version)) = versioned_syntax(&token) else {
+            continue;
+        };
+
+        if version <= minimum_r_version {
+            continue;
+        }
+
+        let offset = usize::from(token.text_trimmed_range().start());
+        let line = text[..offset].matches('\n').count() + 1;
+
+        return Err(ParseError::new(format!(
+            "Line {line}: {syntax} requires R >= {version}, but `minimum-r-version` is {minimum_r_version}."
+        )));
+    }
+
+    Ok(())
+}
+
+/// The syntax that `token` belongs to, and the R version it was introduced in
+fn versioned_syntax(token: &RSyntaxToken) -> Option<(&'static str, RVersion)> {
+    match token.kind() {
+        RSyntaxKind::STRING_OPEN if token.text_trimmed().starts_with(['r', 'R']) => {
+            Some(("Raw string syntax", RVersion::new(4, 0, 0)))
+        }
+        RSyntaxKind::PIPE => Some(("The `|>` pipe", RVersion::new(4, 1, 0))),
+        RSyntaxKind::BACKSLASH => Some(("The `\\(x)` lambda syntax", RVersion::new(4, 1, 0))),
+        RSyntaxKind::IDENT if token.text_trimmed() == "_" => {
+            Some(("The `_` placeholder", RVersion::new(4, 2, 0)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use settings::RVersion;
+
+    use crate::RParserOptions;
+    use crate::parse;
+
+    fn parse_error(text: &str, minimum_r_version: RVersion) -> Option<String> {
+        let options = RParserOptions::default().with_minimum_r_version(Some(minimum_r_version));
+        parse(text, options).error().map(ToString::to_string)
+    }
+
+    #[test]
+    fn test_minimum_r_version_allows_older_syntax() {
+        assert_eq!(parse_error("x <- 1\nf(x)\n", RVersion::new(3, 6, 0)), None);
+        assert_eq!(parse_error("x |> f()\n", RVersion::new(4, 1, 0)), None);
+        assert_eq!(parse_error("x |> f(y = _)\n", RVersion::new(4, 2, 0)), None);
+        assert_eq!(parse_error("`_` <- 1\n", RVersion::new(3, 6, 0)), None);
+    }
+
+    #[test]
+    fn test_minimum_r_version_rejects_newer_syntax() {
+        assert_eq!(
+            parse_error("x <- 1\nx |> f()\n", RVersion::new(4, 0, 0)).as_deref(),
+            Some("Line 2: The `|>` pipe requires R >= 4.1.0, but `minimum-r-version` is 4.0.0.")
+        );
+        assert_eq!(
+            parse_error("f <- \\(x) x + 1\n", RVersion::new(4, 0, 0)).as_deref(),
+            Some(
+                "Line 1: The `\\(x)` lambda syntax requires R >= 4.1.0, but `minimum-r-version` is 4.0.0."
+            )
+        );
+        assert_eq!(
+            parse_error("x <- r\"(\\d)\"\n", RVersion::new(3, 6, 0)).as_deref(),
+            Some(
+                "Line 1: Raw string syntax requires R >= 4.0.0, but `minimum-r-version` is 3.6.0."
+            )
+        );
+        assert_eq!(
+            parse_error("x |> f(y = _)\n", RVersion::new(4, 1, 0)).as_deref(),
+            Some(
+                "Line 1: The `_` placeholder requires R >= 4.2.0, but `minimum-r-version` is 4.1.0."
+            )
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use settings::ColumnAlignment;
+use settings::TableAlignment;
+
 #[derive(Debug, PartialEq)]
 pub enum Directive {
     Format(FormatDirective),
@@ -8,6 +11,7 @@ pub enum FormatDirective {
     Skip,
     SkipFile,
     Table,
+    TableAlign(TableAlignment),
 }
 
 /// Parse a comment directive
@@ -24,6 +28,7 @@ pub enum FormatDirective {
 /// # fmt: skip
 /// # fmt: skip file
 /// # fmt: table
+/// # fmt: table align=l,r,c
 /// # fmt: align-right
 /// # lint: skip
 /// # lint: skip rule
@@ -61,10 +66,22 @@ fn parse_format_directive(text: &str) -> Option<Directive> {
         "skip" => Some(Directive::Format(FormatDirective::Skip)),
         "skip file" => Some(Directive::Format(FormatDirective::SkipFile)),
         "table" => Some(Directive::Format(FormatDirective::Table)),
-        _ => None,
+        // Shorthand for `table align=r`
+        "align-right" => Some(Directive::Format(FormatDirective::TableAlign(
+            TableAlignment::new(vec![ColumnAlignment::Right]),
+        ))),
+        _ => parse_table_align_directive(text),
     }
 }
 
+// Parses `table align=l,r,c`. The alignments are applied to the columns in
+// order, or to all columns if there is only one.
+fn parse_table_align_directive(text: &str) -> Option<Directive> {
+    let alignment = text.strip_prefix("table align=")?;
+    let alignment = alignment.parse().ok()?;
+    Some(Directive::Format(FormatDirective::TableAlign(alignment)))
+}
+
 // https://github.com/posit-dev/air/issues/219
 // Should be called only on the first line in a block of comments.
 pub fn parse_special_skip_file(text: &str) -> Option<Directive> {
@@ -83,6 +100,8 @@ mod test {
     use crate::Directive;
     use crate::parse_comment_directive;
     use crate::parse_special_skip_file;
+    use settings::ColumnAlignment;
+    use settings::TableAlignment;
 
     #[test]
     fn test_format_directive() {
@@ -131,4 +150,41 @@ mod test {
 
         assert_eq!(parse_comment_directive("# fmt: table"), format_table);
     }
+
+    #[test]
+    fn test_table_align_directive() {
+        let format_table_align = |alignment: Vec<ColumnAlignment>| {
+            Some(Directive::Format(crate::FormatDirective::TableAlign(
+                TableAlignment::new(alignment),
+            )))
+        };
+
+        assert_eq!(
+            parse_comment_directive("# fmt: table align=l,r,c"),
+            format_table_align(vec![
+                ColumnAlignment::Left,
+                ColumnAlignment::Right,
+                ColumnAlignment::Center
+            ])
+        );
+        assert_eq!(
+            parse_comment_directive("#fmt:table align=left,right"),
+            format_table_align(vec![ColumnAlignment::Left, ColumnAlignment::Right])
+        );
+        assert_eq!(
+            parse_comment_directive("# fmt: align-right"),
+            format_table_align(vec![ColumnAlignment::Right])
+        );
+
+        // Can't have extra spaces between `table` and `align`
+        assert!(parse_comment_directive("# fmt: table  align=l").is_none());
+
+        // Can't have unknown alignments
+        assert!(parse_comment_directive("# fmt: table align=l,x").is_none());
+        assert!(parse_comment_directive("# fmt: table align=").is_none());
+
+        // Can't have unrelated trailing text
+        assert!(parse_comment_directive("# fmt: table align=l please").is_none());
+        assert!(parse_comment_directive("# fmt: align-right please").is_none());
+    }
 }
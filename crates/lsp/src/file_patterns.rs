@@ -4,6 +4,7 @@ use workspace::settings::DefaultExcludePatterns;
 use workspace::settings::DefaultIncludePatterns;
 use workspace::settings::ExcludePatterns;
 use workspace::settings::FormatSettings;
+use workspace::settings::IncludePatterns;
 
 /// Return `true` if the document at the given [`Path`] should be excluded from formatting
 pub(crate) fn is_document_excluded_from_formatting(
@@ -15,6 +16,7 @@ pub(crate) fn is_document_excluded_from_formatting(
         path,
         format_settings.exclude.as_ref(),
         format_settings.default_exclude.as_ref(),
+        format_settings.include.as_ref(),
         format_settings.default_include.as_ref(),
         language_id,
     )
@@ -24,7 +26,7 @@ pub(crate) fn is_document_excluded_from_formatting(
 ///
 /// The logic for the resolution considers both exclusion and inclusion and is as follows:
 /// 1. Check for `exclude` and `default_exclude` patterns first.
-/// 2. Check for `default_include` patterns next (no user supplied `include` yet).
+/// 2. Check for `include` and `default_include` patterns next.
 /// 3. Check if the language ID is R, in which case we include it. This is a feature
 ///    unique to language servers.
 /// 4. If none of the above conditions are met, the document is excluded.
@@ -32,6 +34,7 @@ fn is_document_excluded(
     path: &Path,
     exclude: Option<&ExcludePatterns>,
     default_exclude: Option<&DefaultExcludePatterns>,
+    include: Option<&IncludePatterns>,
     default_include: Option<&DefaultIncludePatterns>,
     language_id: String,
 ) -> bool {
@@ -56,7 +59,9 @@ fn is_document_excluded(
 
     // Then check for explicit inclusions (mostly for `.R` file extensions).
     // No need for `IS_DIRECTORY` since includes are only applicable for files.
-    if let Some(glob) = workspace::discovery::any_include_matched_path(path, default_include) {
+    if let Some(glob) =
+        workspace::discovery::any_include_matched_path(path, include, default_include)
+    {
         tracing::trace!(
             "Included file due to '{glob}' {path}",
             glob = glob.original(),
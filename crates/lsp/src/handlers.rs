@@ -72,6 +72,14 @@ pub(crate) async fn handle_initialized(lsp_state: &LspState) -> anyhow::Result<(
                             glob_pattern: lsp_types::GlobPattern::String("**/.air.toml".into()),
                             kind: None,
                         },
+                        FileSystemWatcher {
+                            glob_pattern: lsp_types::GlobPattern::String("**/DESCRIPTION".into()),
+                            kind: None,
+                        },
+                        FileSystemWatcher {
+                            glob_pattern: lsp_types::GlobPattern::String("**/.editorconfig".into()),
+                            kind: None,
+                        },
                     ],
                 })
                 .unwrap(),
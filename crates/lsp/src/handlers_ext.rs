@@ -1,7 +1,8 @@
 use air_r_formatter::{context::RFormatOptions, format_node};
-use settings::{IndentStyle, LineWidth};
+use air_r_parser::Parse;
 use tower_lsp::lsp_types;
 
+use crate::main_loop::LspState;
 use crate::state::WorldState;
 
 #[derive(Debug, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
@@ -22,53 +23,73 @@ pub(crate) enum ViewFileKind {
     FormatTree,
 }
 
-pub(crate) fn view_file(params: ViewFileParams, state: &WorldState) -> anyhow::Result<String> {
-    let doc = state.get_document_or_error(&params.text_document.uri)?;
-
-    match params.kind {
-        ViewFileKind::TreeSitter => {
-            let mut parser = tree_sitter::Parser::new();
-            parser
-                .set_language(&tree_sitter_r::LANGUAGE.into())
-                .unwrap();
-
-            let ast = parser.parse(&doc.contents, None).unwrap();
+pub(crate) fn view_file(
+    params: ViewFileParams,
+    lsp_state: &LspState,
+    state: &WorldState,
+) -> anyhow::Result<String> {
+    let uri = &params.text_document.uri;
+    let doc = state.get_document_or_error(uri)?;
+
+    let view = match params.kind {
+        ViewFileKind::TreeSitter => view_tree_sitter(&doc.contents),
+        ViewFileKind::SyntaxTree => view_syntax_tree(&doc.parse),
+        ViewFileKind::FormatTree => {
+            // Use the same settings as formatting the document would
+            let path = uri.to_file_path().ok();
+            let options = lsp_state
+                .workspace_document_settings(uri)
+                .to_format_options(
+                    &doc.contents,
+                    &doc.settings,
+                    path.as_deref(),
+                    lsp_state.editorconfig(),
+                );
+            view_format_tree(&doc.parse, options)?
+        }
+    };
 
-            if ast.root_node().has_error() {
-                return Ok(String::from("*Parse error*"));
-            }
+    Ok(view.unwrap_or_else(|| String::from("*Parse error*")))
+}
 
-            let mut output = String::new();
-            let mut cursor = ast.root_node().walk();
-            format_ts_node(&mut cursor, 0, &mut output);
+/// The tree-sitter tree of `source`, or `None` if it has parse errors
+pub fn view_tree_sitter(source: &str) -> Option<String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_r::LANGUAGE.into())
+        .unwrap();
 
-            Ok(output)
-        }
+    let ast = parser.parse(source, None).unwrap();
 
-        ViewFileKind::SyntaxTree => {
-            if doc.parse.has_error() {
-                return Ok(String::from("*Parse error*"));
-            }
+    if ast.root_node().has_error() {
+        return None;
+    }
 
-            let syntax = doc.syntax();
-            Ok(format!("{syntax:#?}"))
-        }
+    let mut output = String::new();
+    let mut cursor = ast.root_node().walk();
+    format_ts_node(&mut cursor, 0, &mut output);
 
-        ViewFileKind::FormatTree => {
-            if doc.parse.has_error() {
-                return Ok(String::from("*Parse error*"));
-            }
+    Some(output)
+}
 
-            let line_width = LineWidth::try_from(80).map_err(|err| anyhow::anyhow!("{err}"))?;
+/// The Rowan syntax tree of `parse`, or `None` if it has parse errors
+pub fn view_syntax_tree(parse: &Parse) -> Option<String> {
+    if parse.has_error() {
+        return None;
+    }
 
-            let options = RFormatOptions::default()
-                .with_indent_style(IndentStyle::Space)
-                .with_line_width(line_width);
+    let syntax = parse.syntax();
+    Some(format!("{syntax:#?}"))
+}
 
-            let formatted = format_node(options.clone(), &doc.parse.syntax())?;
-            Ok(format!("{}", formatted.into_document()))
-        }
+/// The format IR of `parse` with `options`, or `None` if it has parse errors
+pub fn view_format_tree(parse: &Parse, options: RFormatOptions) -> anyhow::Result<Option<String>> {
+    if parse.has_error() {
+        return Ok(None);
     }
+
+    let formatted = format_node(options, &parse.syntax())?;
+    Ok(Some(format!("{}", formatted.into_document())))
 }
 
 fn format_ts_node(cursor: &mut tree_sitter::TreeCursor, depth: usize, output: &mut String) {
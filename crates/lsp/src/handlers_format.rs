@@ -1,10 +1,11 @@
-use air_r_syntax::{RExpressionList, RSyntaxKind, RSyntaxNode, WalkEvent};
-use biome_rowan::{AstNode, Language, SyntaxElement};
-use biome_text_size::{TextRange, TextSize};
+use air_r_formatter::context::RFormatOptions;
 use tower_lsp::lsp_types;
+use workspace::format::FormattedRange;
 use workspace::format::FormattedSource;
+use workspace::format::format_range;
 use workspace::format::format_source_with_parse;
 
+use crate::documents::Document;
 use crate::file_patterns::is_document_excluded_from_formatting;
 use crate::main_loop::LspState;
 use aether_lsp_utils::proto::{from_proto, to_proto};
@@ -44,7 +45,17 @@ pub(crate) fn document_formatting(
         return Ok(None);
     }
 
-    let format_options = workspace_settings.to_format_options(&doc.contents, &doc.settings);
+    let path = uri.to_file_path().ok();
+    let format_options = workspace_settings.to_format_options(
+        &doc.contents,
+        &doc.settings,
+        path.as_deref(),
+        lsp_state.editorconfig(),
+    );
+
+    if !supports_minimum_r_version(uri, doc, &format_options) {
+        return Ok(None);
+    }
 
     match format_source_with_parse(&doc.contents, &doc.parse, format_options)? {
         FormattedSource::Changed(mut formatted) => {
@@ -104,59 +115,27 @@ pub(crate) fn document_range_formatting(
 
     let range = from_proto::text_range(params.range, &doc.line_index, doc.position_encoding)?;
 
-    let logical_lines = find_deepest_enclosing_logical_lines(doc.parse.syntax(), range);
-    if logical_lines.is_empty() {
-        tracing::warn!("Can't find logical line");
-        return Ok(None);
-    };
+    let path = uri.to_file_path().ok();
+    let format_options = workspace_settings.to_format_options(
+        &doc.contents,
+        &doc.settings,
+        path.as_deref(),
+        lsp_state.editorconfig(),
+    );
 
-    // Find the overall formatting range by concatenating the ranges of the logical lines.
-    // We use the "non-whitespace-range" as that corresponds to what Biome will format.
-    let format_range = logical_lines
-        .iter()
-        .map(text_non_whitespace_range)
-        .reduce(|acc, new| acc.cover(new))
-        .expect("`logical_lines` is non-empty");
-
-    // We need to wrap in an `RRoot` otherwise the comments get attached too
-    // deep in the tree. See `CommentsBuilderVisitor` in biome_formatter and the
-    // `is_root` logic. Note that `node` needs to be wrapped in at least two
-    // other nodes in order to fix this problem, and here we have an `RRoot` and
-    // `RExpressionList` that do the job.
-    //
-    // Since we only format logical lines, it is fine to wrap in an expression list.
-    let Some(exprs): Option<Vec<air_r_syntax::AnyRExpression>> = logical_lines
-        .into_iter()
-        .map(air_r_syntax::AnyRExpression::cast)
-        .collect()
-    else {
-        tracing::warn!("Can't cast to `AnyRExpression`");
+    if !supports_minimum_r_version(uri, doc, &format_options) {
         return Ok(None);
-    };
-
-    let list = air_r_factory::r_expression_list(exprs);
-    let eof = air_r_syntax::RSyntaxToken::new_detached(RSyntaxKind::EOF, "", vec![], vec![]);
-    let root = air_r_factory::r_root(list, eof).build();
-
-    let format_options = workspace_settings.to_format_options(&doc.contents, &doc.settings);
-
-    let format_info = biome_formatter::format_sub_tree(
-        root.syntax(),
-        air_r_formatter::RFormatLanguage::new(format_options),
-    )?;
+    }
 
-    if format_info.range().is_none() {
-        // Happens in edge cases when biome returns a `Printed::new_empty()`
+    let Some(FormattedRange { range, formatted }) =
+        format_range(&doc.parse.syntax(), range, format_options)?
+    else {
         return Ok(None);
     };
 
-    let mut format_text = format_info.into_code();
-
-    // Remove last hard break line from our artifical expression list
-    format_text.pop();
     let edits = to_proto::replace_range_edit(
-        format_range,
-        format_text,
+        range,
+        formatted,
         &doc.line_index,
         doc.position_encoding,
         doc.endings,
@@ -165,122 +144,30 @@ pub(crate) fn document_range_formatting(
     Ok(Some(edits))
 }
 
-// From biome_formatter
-fn text_non_whitespace_range<E, L>(elem: &E) -> TextRange
-where
-    E: Into<SyntaxElement<L>> + Clone,
-    L: Language,
-{
-    let elem: SyntaxElement<L> = elem.clone().into();
-
-    let start = elem
-        .leading_trivia()
-        .into_iter()
-        .flat_map(|trivia| trivia.pieces())
-        .find_map(|piece| {
-            if piece.is_whitespace() || piece.is_newline() {
-                None
-            } else {
-                Some(piece.text_range().start())
-            }
-        })
-        .unwrap_or_else(|| elem.text_trimmed_range().start());
-
-    let end = elem
-        .trailing_trivia()
-        .into_iter()
-        .flat_map(|trivia| trivia.pieces().rev())
-        .find_map(|piece| {
-            if piece.is_whitespace() || piece.is_newline() {
-                None
-            } else {
-                Some(piece.text_range().end())
-            }
-        })
-        .unwrap_or_else(|| elem.text_trimmed_range().end());
-
-    TextRange::new(start, end)
-}
-
-/// Finds consecutive logical lines. Currently that's only expressions at
-/// top-level or in a braced list.
-fn find_deepest_enclosing_logical_lines(node: RSyntaxNode, range: TextRange) -> Vec<RSyntaxNode> {
-    let start_lists = find_expression_lists(&node, range.start(), false);
-    let end_lists = find_expression_lists(&node, range.end(), true);
-
-    // Both vectors of lists should have a common prefix, starting from the
-    // program's expression list. As soon as the lists diverge we stop.
-    let Some(list) = start_lists
-        .into_iter()
-        .zip(end_lists)
-        .take_while(|pair| pair.0 == pair.1)
-        .map(|pair| pair.0)
-        .last()
-    else {
-        // Should not happen as the range is always included in the program's expression list
-        tracing::warn!("Can't find common list parent");
-        return vec![];
+/// Does the document only use syntax supported by the `minimum-r-version`?
+///
+/// Documents are parsed without knowledge of the `minimum-r-version`, so this is
+/// checked right before formatting. Like parse errors, this is only logged as a warning.
+fn supports_minimum_r_version(
+    uri: &lsp_types::Url,
+    doc: &Document,
+    format_options: &RFormatOptions,
+) -> bool {
+    let Some(minimum_r_version) = format_options.minimum_r_version() else {
+        return true;
     };
 
-    let Some(list) = RExpressionList::cast(list) else {
-        tracing::warn!("Can't cast to expression list");
-        return vec![];
-    };
-
-    let iter = list.into_iter();
-
-    // We've chosen to be liberal about user selections and always widen the
-    // range to include the selection bounds. If we wanted to be conservative
-    // instead, we could use this `filter()` instead of the `skip_while()` and
-    // `take_while()`:
-    //
-    // ```rust
-    // .filter(|node| range.contains_range(node.text_trimmed_range()))
-    // ```
-    let logical_lines: Vec<RSyntaxNode> = iter
-        .map(|expr| expr.into_syntax())
-        .skip_while(|node| !node.text_range_with_trivia().contains(range.start()))
-        .take_while(|node| node.text_trimmed_range().start() <= range.end())
-        .collect();
-
-    logical_lines
-}
-
-fn find_expression_lists(node: &RSyntaxNode, offset: TextSize, end: bool) -> Vec<RSyntaxNode> {
-    let mut preorder = node.preorder();
-    let mut nodes: Vec<RSyntaxNode> = vec![];
-
-    while let Some(event) = preorder.next() {
-        match event {
-            WalkEvent::Enter(node) => {
-                let Some(parent) = node.parent() else {
-                    continue;
-                };
-
-                let is_contained = if end {
-                    let trimmed_node_range = node.text_trimmed_range();
-                    trimmed_node_range.contains_inclusive(offset)
-                } else {
-                    let node_range = node.text_range_with_trivia();
-                    node_range.contains(offset)
-                };
-
-                if !is_contained {
-                    preorder.skip_subtree();
-                    continue;
-                }
-
-                if parent.kind() == RSyntaxKind::R_EXPRESSION_LIST {
-                    nodes.push(parent.clone());
-                    continue;
-                }
-            }
-
-            WalkEvent::Leave(_) => {}
+    match air_r_parser::check_minimum_r_version(
+        &doc.contents,
+        &doc.parse.syntax(),
+        minimum_r_version,
+    ) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::warn!("Failed to format {uri}. {err}");
+            false
         }
     }
-
-    nodes
 }
 
 #[cfg(test)]
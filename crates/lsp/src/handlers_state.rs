@@ -10,13 +10,16 @@ use tower_lsp::lsp_types::ConfigurationItem;
 use tower_lsp::lsp_types::DidChangeConfigurationParams;
 use tower_lsp::lsp_types::DidChangeTextDocumentParams;
 use tower_lsp::lsp_types::DidChangeWatchedFilesParams;
+use tower_lsp::lsp_types::DidChangeWatchedFilesRegistrationOptions;
 use tower_lsp::lsp_types::DidChangeWorkspaceFoldersParams;
 use tower_lsp::lsp_types::DidCloseTextDocumentParams;
 use tower_lsp::lsp_types::DidOpenTextDocumentParams;
+use tower_lsp::lsp_types::FileSystemWatcher;
 use tower_lsp::lsp_types::FormattingOptions;
 use tower_lsp::lsp_types::InitializeParams;
 use tower_lsp::lsp_types::InitializeResult;
 use tower_lsp::lsp_types::OneOf;
+use tower_lsp::lsp_types::RelativePattern;
 use tower_lsp::lsp_types::ServerCapabilities;
 use tower_lsp::lsp_types::ServerInfo;
 use tower_lsp::lsp_types::TextDocumentSyncCapability;
@@ -199,7 +202,7 @@ pub(crate) async fn did_change_configuration(
         .await
 }
 
-pub(crate) fn did_change_workspace_folders(
+pub(crate) async fn did_change_workspace_folders(
     params: DidChangeWorkspaceFoldersParams,
     lsp_state: &mut LspState,
 ) -> anyhow::Result<()> {
@@ -209,7 +212,8 @@ pub(crate) fn did_change_workspace_folders(
     for lsp_types::WorkspaceFolder { uri, .. } in params.event.removed {
         lsp_state.close_workspace_folder(&uri);
     }
-    Ok(())
+
+    update_extended_watchers(lsp_state).await
 }
 
 pub(crate) async fn did_change_watched_files(
@@ -233,6 +237,88 @@ pub(crate) async fn did_change_watched_files(
         lsp_state.sync_file_settings(state.workspace_uris()).await;
     }
 
+    // The reloaded settings may `extend` different files
+    update_extended_watchers(lsp_state).await
+}
+
+const EXTENDED_WATCHER_ID: &str = "air-extended-watcher";
+
+/// Watch the files inherited through `extend`
+///
+/// The configuration file watchers registered on initialization are relative to the
+/// workspaces, but `extend` may point anywhere on disk. Each extended file is watched
+/// by its absolute path instead. The watchers are replaced whenever the settings are
+/// reloaded, as the extended files may have changed.
+pub(crate) async fn update_extended_watchers(lsp_state: &mut LspState) -> anyhow::Result<()> {
+    if !lsp_state
+        .capabilities
+        .dynamic_registration_for_did_change_watched_files
+    {
+        return Ok(());
+    }
+
+    let extended = lsp_state.workspace_settings_resolver.extended();
+
+    if extended == lsp_state.extended_watchers {
+        return Ok(());
+    }
+
+    if !lsp_state.extended_watchers.is_empty() {
+        lsp_state
+            .client
+            .unregister_capability(vec![lsp_types::Unregistration {
+                id: String::from(EXTENDED_WATCHER_ID),
+                method: String::from("workspace/didChangeWatchedFiles"),
+            }])
+            .await?;
+        lsp_state.extended_watchers.clear();
+    }
+
+    if extended.is_empty() {
+        return Ok(());
+    }
+
+    let relative_pattern_support = lsp_state
+        .capabilities
+        .relative_pattern_support_for_did_change_watched_files;
+
+    let watchers = extended
+        .iter()
+        .filter_map(|path| {
+            let glob_pattern = if relative_pattern_support {
+                let base_uri = Url::from_directory_path(path.parent()?).ok()?;
+                let pattern = path.file_name()?.to_str()?.to_string();
+                lsp_types::GlobPattern::Relative(RelativePattern {
+                    base_uri: OneOf::Right(base_uri),
+                    pattern,
+                })
+            } else {
+                // Clients without relative patterns may still support absolute ones
+                lsp_types::GlobPattern::String(path.to_str()?.to_string())
+            };
+
+            Some(FileSystemWatcher {
+                glob_pattern,
+                kind: None,
+            })
+        })
+        .collect();
+
+    let registration = lsp_types::Registration {
+        id: String::from(EXTENDED_WATCHER_ID),
+        method: String::from("workspace/didChangeWatchedFiles"),
+        register_options: Some(serde_json::to_value(
+            DidChangeWatchedFilesRegistrationOptions { watchers },
+        )?),
+    };
+
+    tracing::trace!("Watching extended configuration files: {extended:?}");
+    lsp_state
+        .client
+        .register_capability(vec![registration])
+        .await?;
+    lsp_state.extended_watchers = extended;
+
     Ok(())
 }
 
@@ -6,6 +6,7 @@
 //
 
 use std::future;
+use std::path::PathBuf;
 use std::pin::Pin;
 
 use anyhow::anyhow;
@@ -16,6 +17,7 @@ use tokio::task::JoinHandle;
 use tower_lsp::Client;
 use tower_lsp::lsp_types::Diagnostic;
 use url::Url;
+use workspace::editorconfig::EditorConfigCache;
 
 use crate::capabilities::AirClientCapabilities;
 use crate::handlers;
@@ -172,6 +174,9 @@ pub(crate) struct LspState {
 
     /// Global settings communicated by the client
     pub(crate) settings: GlobalSettings,
+
+    /// The files inherited through `extend` that we registered watchers for
+    pub(crate) extended_watchers: Vec<PathBuf>,
 }
 
 impl LspState {
@@ -184,6 +189,7 @@ impl LspState {
             capabilities: Default::default(),
             log_state: Default::default(),
             settings: Default::default(),
+            extended_watchers: Default::default(),
         }
     }
 }
@@ -193,6 +199,10 @@ impl LspState {
         self.workspace_settings_resolver.settings_for_url(url)
     }
 
+    pub(crate) fn editorconfig(&self) -> &EditorConfigCache {
+        self.workspace_settings_resolver.editorconfig()
+    }
+
     pub(crate) fn open_workspace_folder(&mut self, url: &Url) {
         self.workspace_settings_resolver.open_workspace_folder(url)
     }
@@ -322,9 +332,10 @@ impl GlobalState {
                     match notif {
                         LspNotification::Initialized(_params) => {
                             handlers::handle_initialized(&self.lsp_state).await?;
+                            handlers_state::update_extended_watchers(&mut self.lsp_state).await?;
                         },
                         LspNotification::DidChangeWorkspaceFolders(params) => {
-                            handlers_state::did_change_workspace_folders(params, &mut self.lsp_state)?;
+                            handlers_state::did_change_workspace_folders(params, &mut self.lsp_state).await?;
                         },
                         LspNotification::DidChangeConfiguration(params) => {
                             handlers_state::did_change_configuration(params, &mut self.lsp_state, &mut self.world).await?;
@@ -367,7 +378,7 @@ impl GlobalState {
                             respond(tx, handlers_format::document_range_formatting(params, &self.lsp_state, &self.world), LspResponse::DocumentRangeFormatting)?;
                         },
                         LspRequest::AirViewFile(params) => {
-                            respond(tx, handlers_ext::view_file(params, &self.world), LspResponse::AirViewFile)?;
+                            respond(tx, handlers_ext::view_file(params, &self.lsp_state, &self.world), LspResponse::AirViewFile)?;
                         },
                     };
                 },
@@ -1,5 +1,9 @@
+use std::path::Path;
+
+use biome_formatter::FormatOptions;
 use tower_lsp::lsp_types::notification::Notification;
 use url::Url;
+use workspace::editorconfig::EditorConfigCache;
 
 use crate::{main_loop::LspState, workspaces::WorkspaceSettings};
 
@@ -30,11 +34,26 @@ impl FileFormatSettings {
     // The interior types we care about sending to the client are all `Copy`
     // and extremely cheap to duplicate, but `FormatSettings` itself is not,
     // so we make sure to take a reference here.
-    fn from_format_settings(settings: &workspace::settings::FormatSettings) -> Self {
+    //
+    // Like when formatting, options that aren't explicitly set in the TOML are taken
+    // from the `.editorconfig` files that apply to `path`, and the overrides that match
+    // `path` are applied on top.
+    fn from_format_settings(
+        settings: &workspace::settings::FormatSettings,
+        path: Option<&Path>,
+        editorconfig: &EditorConfigCache,
+    ) -> Self {
+        let options = match path {
+            Some(path) => {
+                settings.to_format_options_for_path_with("", path, &editorconfig.resolve(path))
+            }
+            None => settings.to_format_options(""),
+        };
+
         Self {
-            indent_style: settings.indent_style,
-            indent_width: settings.indent_width,
-            line_width: settings.line_width,
+            indent_style: options.indent_style().into(),
+            indent_width: options.indent_width().into(),
+            line_width: options.line_width().into(),
         }
     }
 }
@@ -52,7 +71,11 @@ impl LspState {
                     // There is a TOML to backpropagate
                     WorkspaceSettings::Toml(settings) => Some(FileSettings {
                         url: url.to_string(),
-                        format: FileFormatSettings::from_format_settings(&settings.format),
+                        format: FileFormatSettings::from_format_settings(
+                            &settings.format,
+                            url.to_file_path().ok().as_deref(),
+                            self.editorconfig(),
+                        ),
                     }),
                     // There is no TOML. Let the IDE use its own settings.
                     WorkspaceSettings::Fallback(_) => None,
@@ -67,3 +90,44 @@ impl LspState {
             .await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use workspace::editorconfig::EditorConfigCache;
+    use workspace::toml::parse_air_inline_toml;
+
+    use super::FileFormatSettings;
+
+    #[test]
+    fn test_file_format_settings_apply_overrides() -> anyhow::Result<()> {
+        let tempdir = tempfile::TempDir::new()?;
+        let root = tempdir.path();
+
+        let toml = r#"
+[format]
+line-width = 100
+
+[[format.overrides]]
+include = ["data-raw/"]
+line-width = 200
+"#;
+        let settings = parse_air_inline_toml(toml)?.into_settings(root, None)?;
+        let cache = EditorConfigCache::default();
+
+        let path = root.join("R").join("code.R");
+        let format =
+            FileFormatSettings::from_format_settings(&settings.format, Some(&path), &cache);
+        assert_eq!(format.line_width.value(), 100);
+
+        let path = root.join("data-raw").join("data.R");
+        let format =
+            FileFormatSettings::from_format_settings(&settings.format, Some(&path), &cache);
+        assert_eq!(format.line_width.value(), 200);
+
+        // Without a path, no override applies
+        let format = FileFormatSettings::from_format_settings(&settings.format, None, &cache);
+        assert_eq!(format.line_width.value(), 100);
+
+        Ok(())
+    }
+}
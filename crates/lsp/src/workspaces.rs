@@ -11,8 +11,12 @@ use std::path::PathBuf;
 use air_r_formatter::context::RFormatOptions;
 use tower_lsp::lsp_types::Url;
 use tower_lsp::lsp_types::WorkspaceFolder;
+use workspace::description::is_description;
 use workspace::discovery::DiscoveredSettings;
 use workspace::discovery::discover_settings;
+use workspace::discovery::discover_user_settings;
+use workspace::editorconfig::EditorConfigCache;
+use workspace::editorconfig::is_editorconfig;
 use workspace::resolve::PathResolver;
 use workspace::settings::Settings;
 use workspace::toml::is_air_toml;
@@ -28,6 +32,13 @@ pub(crate) struct WorkspaceSettingsResolver {
     /// Resolves a `path` to the closest workspace specific `SettingsResolver`.
     /// That `SettingsResolver` can then return `Settings` for the `path`.
     path_to_settings_resolver: PathResolver<SettingsResolver>,
+
+    /// Whether the fallback settings come from a user level `air.toml`, in which case
+    /// they take precedence over client provided settings, like a project `air.toml`
+    has_user_settings: bool,
+
+    /// The `.editorconfig` files read so far, invalidated when they change on disk
+    editorconfig: EditorConfigCache,
 }
 
 /// Resolved [`WorkspaceSettings`] for a workspace specific [`Path`]
@@ -39,14 +50,18 @@ pub(crate) enum WorkspaceSettings<'resolver> {
 impl WorkspaceSettingsResolver {
     /// Construct a new workspace settings resolver from an initial set of workspace folders
     pub(crate) fn from_workspace_folders(workspace_folders: Vec<WorkspaceFolder>) -> Self {
-        // How to do better here?
-        let fallback = Settings::default();
+        let (fallback, has_user_settings) = match Self::user_settings() {
+            Some(settings) => (settings, true),
+            None => (Settings::default(), false),
+        };
 
         let settings_resolver_fallback = SettingsResolver::new(fallback);
         let path_to_settings_resolver = PathResolver::new(settings_resolver_fallback);
 
         let mut resolver = Self {
             path_to_settings_resolver,
+            has_user_settings,
+            editorconfig: EditorConfigCache::default(),
         };
 
         // Add each workspace folder's settings into the resolver.
@@ -92,8 +107,7 @@ impl WorkspaceSettingsResolver {
             }
         };
 
-        // How to do better here?
-        let fallback = Settings::default();
+        let fallback = Self::user_settings().unwrap_or_default();
 
         let mut settings_resolver = SettingsResolver::new(fallback);
 
@@ -145,16 +159,64 @@ impl WorkspaceSettingsResolver {
             return self.settings_for_path(&path);
         }
 
-        tracing::trace!("Using default settings for non-file URL: {url}");
-        WorkspaceSettings::Fallback(self.path_to_settings_resolver.fallback().fallback())
+        tracing::trace!("Using fallback settings for non-file URL: {url}");
+        self.fallback_settings()
+    }
+
+    /// Return the [`WorkspaceSettings`] for documents that aren't covered by an `air.toml`
+    fn fallback_settings(&self) -> WorkspaceSettings<'_> {
+        let settings = self.path_to_settings_resolver.fallback().fallback();
+
+        if self.has_user_settings {
+            WorkspaceSettings::Toml(settings)
+        } else {
+            WorkspaceSettings::Fallback(settings)
+        }
+    }
+
+    /// The cache of `.editorconfig` files, used to finalize the format options of
+    /// documents
+    pub(crate) fn editorconfig(&self) -> &EditorConfigCache {
+        &self.editorconfig
+    }
+
+    /// The files that the settings of any workspace `extend`, sorted and deduplicated
+    ///
+    /// These may live outside of the workspaces, where the watchers registered for
+    /// configuration files don't reach.
+    pub(crate) fn extended(&self) -> Vec<PathBuf> {
+        let settings_resolvers = std::iter::once(self.path_to_settings_resolver.fallback()).chain(
+            self.path_to_settings_resolver
+                .items()
+                .iter()
+                .map(|workspace| workspace.value()),
+        );
+
+        let mut extended: Vec<PathBuf> = settings_resolvers
+            .flat_map(|settings_resolver| {
+                std::iter::once(settings_resolver.fallback())
+                    .chain(settings_resolver.items().iter().map(|item| item.value()))
+            })
+            .flat_map(|settings| settings.extended.iter().cloned())
+            .collect();
+
+        extended.sort();
+        extended.dedup();
+        extended
     }
 
     /// Reloads all workspaces matched by the [`Url`]
     ///
     /// This is utilized by the watched files handler to reload the settings
-    /// resolver whenever an `air.toml` is modified.
+    /// resolver whenever an `air.toml` or a `DESCRIPTION` file (which may hold
+    /// `Config/air/*` settings) is modified. A modified `.editorconfig` file is
+    /// invalidated in the cache, and still triggers a reload so that the
+    /// backpropagated file settings are refreshed.
+    ///
+    /// Workspaces are also reloaded when their settings `extend` the modified file,
+    /// even if it lives outside of the workspace.
     ///
-    /// Returns whether an `air.toml` file was modified (currently doesn't check
+    /// Returns whether a configuration file was modified (currently doesn't check
     /// for content changes).
     pub(crate) fn reload_workspaces_matched_by_url(&mut self, url: &Url) -> bool {
         let path = match Self::url_to_path(url) {
@@ -169,16 +231,45 @@ impl WorkspaceSettingsResolver {
             }
         };
 
-        if !is_air_toml(&path) {
-            // We could get called with a changed file that isn't an `air.toml` if we are
-            // watching more than `air.toml` files
-            tracing::trace!("Ignoring non-`air.toml` changed URL: {url}");
+        let is_extended = |settings_resolver: &SettingsResolver| {
+            settings_resolver
+                .items()
+                .iter()
+                .any(|item| item.value().extended.contains(&path))
+        };
+
+        let is_extended_by_any_workspace = self
+            .path_to_settings_resolver
+            .items()
+            .iter()
+            .any(|workspace| is_extended(workspace.value()));
+
+        if !is_air_toml(&path)
+            && !is_description(&path)
+            && !is_editorconfig(&path)
+            && !is_extended_by_any_workspace
+        {
+            // We could get called with a changed file that isn't a configuration file if
+            // we are watching more than configuration files
+            tracing::trace!("Ignoring non-configuration changed URL: {url}");
             return false;
         }
 
+        if is_editorconfig(&path) {
+            self.editorconfig.invalidate(&path);
+        }
+
         let mut changed = false;
 
-        for workspace_match in self.path_to_settings_resolver.matches_mut(&path) {
+        let workspace_matches = self
+            .path_to_settings_resolver
+            .items_mut()
+            .iter_mut()
+            .filter(|workspace| {
+                path.starts_with(workspace.path()) || is_extended(workspace.value())
+            });
+
+        for workspace_match in workspace_matches {
             // Clear existing settings up front, regardless of what happens when reloading.
             // Done in a tight scope to avoid simultaneous mutable and immutable borrows.
             {
@@ -227,15 +318,35 @@ impl WorkspaceSettingsResolver {
             .resolve(path)
             .and_then(|resolution| resolution.value().resolve(path))
             .map_or_else(
-                || {
-                    WorkspaceSettings::Fallback(
-                        self.path_to_settings_resolver.fallback().fallback(),
-                    )
+                || self.fallback_settings(),
+                |resolution| {
+                    let settings = resolution.value();
+
+                    // Packages without any configuration only have default settings,
+                    // which client provided settings take precedence over
+                    if settings.from_config {
+                        WorkspaceSettings::Toml(settings)
+                    } else {
+                        WorkspaceSettings::Fallback(settings)
+                    }
                 },
-                |resolution| WorkspaceSettings::Toml(resolution.value()),
             )
     }
 
+    /// Settings of the user level `air.toml`, if any
+    ///
+    /// Failures are logged rather than propagated, falling back to the default
+    /// settings, so that the server can still start up.
+    fn user_settings() -> Option<Settings> {
+        match discover_user_settings() {
+            Ok(settings) => settings,
+            Err(error) => {
+                tracing::error!("Failed to load user level settings:\n{error}");
+                None
+            }
+        }
+    }
+
     fn url_to_path(url: &Url) -> anyhow::Result<Option<PathBuf>> {
         if url.scheme() != "file" {
             return Ok(None);
@@ -257,20 +368,41 @@ impl WorkspaceSettings<'_> {
         }
     }
 
+    /// Finalize the format options of a document
+    ///
+    /// `path` is the path of the document on disk, if any, and is used to look up
+    /// the `.editorconfig` properties that apply to it in the `editorconfig` cache.
     pub(crate) fn to_format_options(
         &self,
         source: &str,
         document_settings: &DocumentSettings,
+        path: Option<&Path>,
+        editorconfig: &EditorConfigCache,
     ) -> RFormatOptions {
         match self {
             WorkspaceSettings::Toml(settings) => {
-                // If there is an actual TOML, that wins
-                settings.format.to_format_options(source)
+                // If there is an actual TOML, that wins, followed by the `.editorconfig`
+                match path {
+                    Some(path) => settings.format.to_format_options_for_path_with(
+                        source,
+                        path,
+                        &editorconfig.resolve(path),
+                    ),
+                    None => settings.format.to_format_options(source),
+                }
             }
             WorkspaceSettings::Fallback(settings) => {
-                // In the fallback case, merge with client provided `DocumentSettings`
+                // In the fallback case, merge with client provided `DocumentSettings`,
+                // and then with the `.editorconfig`, which is more specific to the
+                // document than the client settings
                 let format_options = settings.format.to_format_options(source);
-                DocumentSettings::merge(format_options, document_settings)
+                let mut format_options = DocumentSettings::merge(format_options, document_settings);
+                if let Some(path) = path {
+                    settings
+                        .format
+                        .merge_editorconfig(&mut format_options, &editorconfig.resolve(path));
+                }
+                format_options
             }
         }
     }
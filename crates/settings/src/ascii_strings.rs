@@ -0,0 +1,40 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsciiStrings {
+    /// Preserve
+    #[default]
+    Preserve,
+    /// Escape
+    Escape,
+}
+
+impl AsciiStrings {
+    /// Returns `true` if non-ASCII characters in strings should be escaped.
+    pub const fn is_escape(&self) -> bool {
+        matches!(self, AsciiStrings::Escape)
+    }
+}
+
+impl FromStr for AsciiStrings {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "escape" => Ok(Self::Escape),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl Display for AsciiStrings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsciiStrings::Preserve => std::write!(f, "Preserve"),
+            AsciiStrings::Escape => std::write!(f, "Escape"),
+        }
+    }
+}
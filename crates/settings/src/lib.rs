@@ -1,19 +1,35 @@
+mod ascii_strings;
 mod assignment_style;
 mod indent_style;
 mod indent_width;
 mod line_ending;
 mod line_width;
+mod normalize_names;
 mod persistent_line_breaks;
+mod prefer_raw_strings;
+mod r_version;
+mod section_comments;
 mod skip;
 mod sorted_strings;
 mod table;
+mod table_alignment;
+mod vector_layout;
+mod wrap_comments;
 
+pub use ascii_strings::*;
 pub use assignment_style::*;
 pub use indent_style::*;
 pub use indent_width::*;
 pub use line_ending::*;
 pub use line_width::*;
+pub use normalize_names::*;
 pub use persistent_line_breaks::*;
+pub use prefer_raw_strings::*;
+pub use r_version::*;
+pub use section_comments::*;
 pub use skip::*;
 pub use sorted_strings::*;
 pub use table::*;
+pub use table_alignment::*;
+pub use vector_layout::*;
+pub use wrap_comments::*;
@@ -0,0 +1,40 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalizeNames {
+    /// Preserve
+    #[default]
+    Preserve,
+    /// Normalize
+    Normalize,
+}
+
+impl NormalizeNames {
+    /// Returns `true` if names should be normalized.
+    pub const fn is_normalize(&self) -> bool {
+        matches!(self, NormalizeNames::Normalize)
+    }
+}
+
+impl FromStr for NormalizeNames {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "normalize" => Ok(Self::Normalize),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl Display for NormalizeNames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NormalizeNames::Preserve => std::write!(f, "Preserve"),
+            NormalizeNames::Normalize => std::write!(f, "Normalize"),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+use std::num::NonZeroU8;
+
+/// Validated value for the `prefer-raw-strings` formatter option
+///
+/// The minimum number of backslash escapes a string must contain to be rewritten as a
+/// raw string.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(description = ""))]
+pub struct PreferRawStrings(
+    #[cfg_attr(feature = "schemars", schemars(range(min = 1, max = 255)))] NonZeroU8,
+);
+
+impl PreferRawStrings {
+    pub fn new(min_escapes: NonZeroU8) -> Self {
+        Self(min_escapes)
+    }
+
+    /// Return the minimum number of backslash escapes
+    pub fn min_escapes(&self) -> usize {
+        usize::from(self.0.get())
+    }
+}
+
+impl std::fmt::Debug for PreferRawStrings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl std::fmt::Display for PreferRawStrings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
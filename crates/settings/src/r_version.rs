@@ -0,0 +1,171 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// An R version, like `4.1.0`
+///
+/// Used by the `minimum-r-version` option to gate syntax that is only available
+/// from a certain R version.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RVersion {
+    major: u16,
+    minor: u16,
+    patch: u16,
+}
+
+impl RVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    pub fn major(&self) -> u16 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u16 {
+        self.minor
+    }
+
+    pub fn patch(&self) -> u16 {
+        self.patch
+    }
+}
+
+impl FromStr for RVersion {
+    type Err = RVersionParseError;
+
+    /// Parses `4`, `4.1`, or `4.1.2`. Missing components default to `0`. Like R, both
+    /// `.` and `-` are accepted as separators.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || RVersionParseError(s.to_string());
+
+        let mut components = s.trim().split(['.', '-']).map(|component| {
+            if component.is_empty() || !component.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(error());
+            }
+            component.parse::<u16>().map_err(|_| error())
+        });
+
+        let major = components.next().ok_or_else(error)??;
+        let minor = components.next().transpose()?.unwrap_or(0);
+        let patch = components.next().transpose()?.unwrap_or(0);
+
+        if components.next().is_some() {
+            return Err(error());
+        }
+
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for RVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        std::write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: String = serde::Deserialize::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for RVersion {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("RVersion")
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        String::json_schema(generator)
+    }
+}
+
+/// Error type returned when parsing an [`RVersion`] fails
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RVersionParseError(String);
+
+impl std::error::Error for RVersionParseError {}
+
+impl fmt::Display for RVersionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        std::write!(
+            f,
+            "Unsupported R version `{}`. Expected a version like `4.1` or `4.1.2`.",
+            self.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use anyhow::Result;
+
+    use crate::RVersion;
+
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+    struct Options {
+        minimum_r_version: Option<RVersion>,
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("4".parse::<RVersion>(), Ok(RVersion::new(4, 0, 0)));
+        assert_eq!("4.1".parse::<RVersion>(), Ok(RVersion::new(4, 1, 0)));
+        assert_eq!("4.1.2".parse::<RVersion>(), Ok(RVersion::new(4, 1, 2)));
+        assert_eq!(" 3.6-1 ".parse::<RVersion>(), Ok(RVersion::new(3, 6, 1)));
+        assert!("".parse::<RVersion>().is_err());
+        assert!("4.".parse::<RVersion>().is_err());
+        assert!("4.1.2.3".parse::<RVersion>().is_err());
+        assert!("4.x".parse::<RVersion>().is_err());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(RVersion::new(4, 1, 0) > RVersion::new(4, 0, 5));
+        assert!(RVersion::new(3, 6, 3) < RVersion::new(4, 0, 0));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(RVersion::new(4, 1, 0).to_string(), "4.1.0");
+    }
+
+    #[test]
+    fn deserialize() -> Result<()> {
+        let options: Options = toml::from_str(
+            r#"
+minimum-r-version = "4.1"
+"#,
+        )?;
+        assert_eq!(options.minimum_r_version, Some(RVersion::new(4, 1, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_error() -> Result<()> {
+        let result: std::result::Result<Options, toml::de::Error> = toml::from_str(
+            r#"
+minimum-r-version = "four"
+"#,
+        );
+        let error = result.err().context("Expected `RVersion` error")?;
+        assert!(error.to_string().contains("Unsupported R version `four`"));
+        Ok(())
+    }
+}
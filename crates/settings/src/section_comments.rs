@@ -0,0 +1,46 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SectionComments {
+    /// # Leave section comments as they are written
+    #[default]
+    Preserve,
+
+    /// # Pad section comments to the line width and separate sections with a blank line
+    Pad,
+}
+
+impl SectionComments {
+    /// Returns `true` if section comments should be padded.
+    pub const fn is_pad(&self) -> bool {
+        matches!(self, SectionComments::Pad)
+    }
+}
+
+impl FromStr for SectionComments {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "pad" => Ok(Self::Pad),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl Display for SectionComments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionComments::Preserve => std::write!(f, "Preserve"),
+            SectionComments::Pad => std::write!(f, "Pad"),
+        }
+    }
+}
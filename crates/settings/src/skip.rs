@@ -17,6 +17,10 @@ impl Skip {
         Self(SortedStrings::new(names))
     }
 
+    pub fn as_slice(&self) -> &[String] {
+        self.0.as_slice()
+    }
+
     pub fn contains(&self, name: &str) -> bool {
         self.0.contains(name)
     }
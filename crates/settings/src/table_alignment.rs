@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The alignment of a single column of a table.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+    Center,
+}
+
+impl FromStr for ColumnAlignment {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l" | "left" => Ok(Self::Left),
+            "r" | "right" => Ok(Self::Right),
+            "c" | "center" => Ok(Self::Center),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl fmt::Display for ColumnAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnAlignment::Left => std::write!(f, "Left"),
+            ColumnAlignment::Right => std::write!(f, "Right"),
+            ColumnAlignment::Center => std::write!(f, "Center"),
+        }
+    }
+}
+
+/// The alignment of the columns of a table, such as `"l,r,c"`.
+///
+/// When a single alignment is supplied, it applies to every column. Otherwise the
+/// alignments apply to the columns in order, and columns without an alignment use
+/// the default table alignment (numbers are right or decimal aligned, everything else
+/// is left aligned).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(description = ""))]
+pub struct TableAlignment(
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))] Arc<[ColumnAlignment]>,
+);
+
+impl TableAlignment {
+    pub fn new(columns: Vec<ColumnAlignment>) -> Self {
+        Self(columns.into())
+    }
+
+    pub fn as_slice(&self) -> &[ColumnAlignment] {
+        &self.0
+    }
+
+    /// The alignment requested for the column at `index`, if any
+    pub fn get(&self, index: usize) -> Option<ColumnAlignment> {
+        match self.0.as_ref() {
+            [alignment] => Some(*alignment),
+            columns => columns.get(index).copied(),
+        }
+    }
+}
+
+impl FromStr for TableAlignment {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let columns = s
+            .split(',')
+            .map(|column| column.trim().parse())
+            .collect::<Result<Vec<ColumnAlignment>, _>>()?;
+        Ok(Self::new(columns))
+    }
+}
+
+impl fmt::Display for TableAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut columns = self.0.iter();
+        let last = columns.next_back();
+        for column in columns {
+            std::write!(f, "{column}, ")?;
+        }
+        if let Some(last) = last {
+            std::write!(f, "{last}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TableAlignment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: String = serde::Deserialize::deserialize(deserializer)?;
+        value.parse().map_err(|_| {
+            serde::de::Error::custom(format!(
+                "Unsupported table alignment `{value}`. Expected a comma separated list of `l`, `r`, or `c`."
+            ))
+        })
+    }
+}
+
+/// Column alignment of function calls formatted as tables, keyed by function name.
+///
+/// Internally wrapped in an [Arc] for cheap cloning, see [crate::SortedStrings].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(description = ""))]
+pub struct TableAlignments(Arc<BTreeMap<String, TableAlignment>>);
+
+impl TableAlignments {
+    pub fn new(alignments: BTreeMap<String, TableAlignment>) -> Self {
+        Self(Arc::new(alignments))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TableAlignment> {
+        self.0.get(name)
+    }
+
+    /// Iterate over the function names and their alignment, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &TableAlignment)> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for TableAlignments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut alignments = self.0.iter();
+        let last = alignments.next_back();
+        for (name, alignment) in alignments {
+            std::write!(f, "{name}({alignment}), ")?;
+        }
+        if let Some((name, alignment)) = last {
+            std::write!(f, "{name}({alignment})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TableAlignments {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: BTreeMap<String, TableAlignment> =
+            serde::Deserialize::deserialize(deserializer)?;
+        Ok(TableAlignments::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ColumnAlignment;
+    use crate::TableAlignment;
+    use crate::TableAlignments;
+    use anyhow::Context;
+    use anyhow::Result;
+
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields, rename_all = "kebab-case")]
+    struct Options {
+        table_alignment: Option<TableAlignments>,
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "l,r,c".parse::<TableAlignment>(),
+            Ok(TableAlignment::new(vec![
+                ColumnAlignment::Left,
+                ColumnAlignment::Right,
+                ColumnAlignment::Center
+            ]))
+        );
+        assert_eq!(
+            "left, right".parse::<TableAlignment>(),
+            Ok(TableAlignment::new(vec![
+                ColumnAlignment::Left,
+                ColumnAlignment::Right
+            ]))
+        );
+        assert!("".parse::<TableAlignment>().is_err());
+        assert!("l,,r".parse::<TableAlignment>().is_err());
+        assert!("l,x".parse::<TableAlignment>().is_err());
+    }
+
+    #[test]
+    fn get() {
+        let alignment: TableAlignment = "r".parse().unwrap();
+        assert_eq!(alignment.get(0), Some(ColumnAlignment::Right));
+        assert_eq!(alignment.get(5), Some(ColumnAlignment::Right));
+
+        let alignment: TableAlignment = "l,r".parse().unwrap();
+        assert_eq!(alignment.get(0), Some(ColumnAlignment::Left));
+        assert_eq!(alignment.get(1), Some(ColumnAlignment::Right));
+        assert_eq!(alignment.get(2), None);
+    }
+
+    #[test]
+    fn deserialize() -> Result<()> {
+        let options: Options = toml::from_str(
+            r#"
+table-alignment = { my_table = "l,r" }
+"#,
+        )?;
+
+        let alignments = options.table_alignment.context("Expected alignments")?;
+        assert_eq!(
+            alignments.get("my_table"),
+            Some(&TableAlignment::new(vec![
+                ColumnAlignment::Left,
+                ColumnAlignment::Right
+            ]))
+        );
+        assert_eq!(alignments.get("other_table"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_error() -> Result<()> {
+        let result: std::result::Result<Options, toml::de::Error> = toml::from_str(
+            r#"
+table-alignment = { my_table = "l,x" }
+"#,
+        );
+        let error = result.err().context("Expected `TableAlignments` error")?;
+        assert!(
+            error
+                .to_string()
+                .contains("Unsupported table alignment `l,x`")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn display() -> Result<()> {
+        let options: Options = toml::from_str(
+            r#"
+table-alignment = { my_table = "l,r,c", my_other_table = "r" }
+"#,
+        )?;
+        insta::assert_snapshot!(options.table_alignment.unwrap());
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum VectorLayout {
+    /// # Place each element on its own line when the vector doesn't fit on one line
+    #[default]
+    Expand,
+
+    /// # Pack as many elements per line as fit when the vector doesn't fit on one line
+    Fill,
+}
+
+impl VectorLayout {
+    /// Returns `true` if vectors should be laid out in fill mode.
+    pub const fn is_fill(&self) -> bool {
+        matches!(self, VectorLayout::Fill)
+    }
+}
+
+impl FromStr for VectorLayout {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "expand" => Ok(Self::Expand),
+            "fill" => Ok(Self::Fill),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl Display for VectorLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorLayout::Expand => std::write!(f, "Expand"),
+            VectorLayout::Fill => std::write!(f, "Fill"),
+        }
+    }
+}
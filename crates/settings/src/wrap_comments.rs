@@ -0,0 +1,40 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WrapComments {
+    /// Preserve
+    #[default]
+    Preserve,
+    /// Wrap
+    Wrap,
+}
+
+impl WrapComments {
+    /// Returns `true` if comments should be wrapped to the line width.
+    pub const fn is_wrap(&self) -> bool {
+        matches!(self, WrapComments::Wrap)
+    }
+}
+
+impl FromStr for WrapComments {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "wrap" => Ok(Self::Wrap),
+            _ => Err("Unsupported value for this option"),
+        }
+    }
+}
+
+impl Display for WrapComments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WrapComments::Preserve => std::write!(f, "Preserve"),
+            WrapComments::Wrap => std::write!(f, "Wrap"),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//
+// description.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+//! Utilities for extracting information from an R package `DESCRIPTION` file.
+
+use std::path::Path;
+
+use settings::RVersion;
+
+use crate::settings::Encoding;
+use crate::toml_options::TomlOptions;
+
+/// The prefix of `DESCRIPTION` fields that hold Air settings, like
+/// `Config/air/line-width`
+const AIR_CONFIG_PREFIX: &str = "Config/air/";
+
+/// Settings that live at the top level of an `air.toml`, rather than in `[format]`
+const GLOBAL_KEYS: &[&str] = &["extend", "minimum-r-version"];
+
+/// The fields of a `DESCRIPTION` file
+///
+/// `DESCRIPTION` files use the Debian Control File format: one `Field: value` per
+/// line, where values may continue over following lines indented with whitespace.
+#[derive(Debug, Default)]
+pub struct Description {
+    fields: Vec<(String, String)>,
+}
+
+impl Description {
+    /// Read and parse the `DESCRIPTION` file at `path`
+    ///
+    /// Returns `None` if the file doesn't exist or can't be read.
+    pub fn read(path: &Path) -> Option<Self> {
+        // Packages with `Encoding: latin1` may have a `DESCRIPTION` that isn't valid
+        // UTF-8. The fields that Air reads are ASCII, so invalid characters are replaced.
+        let contents = std::fs::read(path).ok()?;
+        Some(Self::parse(&String::from_utf8_lossy(&contents)))
+    }
+
+    /// Parse the contents of a `DESCRIPTION` file
+    ///
+    /// Malformed lines are ignored.
+    pub fn parse(contents: &str) -> Self {
+        let mut fields: Vec<(String, String)> = Vec::new();
+
+        for line in contents.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                fields.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        Self { fields }
+    }
+
+    /// The value of the field called `name`, if any
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The Air settings of the `Config/air/*` fields, as `(key, value)` pairs
+    ///
+    /// For example, `Config/air/line-width: 100` results in `("line-width", "100")`.
+    pub fn air_config(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().filter_map(|(name, value)| {
+            let key = name.strip_prefix(AIR_CONFIG_PREFIX)?;
+            Some((key, value.as_str()))
+        })
+    }
+
+    /// Convert the `Config/air/*` fields into [TomlOptions]
+    ///
+    /// Returns `None` if there aren't any `Config/air/*` fields. Global settings, like
+    /// `Config/air/minimum-r-version`, are placed at the top level, and all others are
+    /// placed in `[format]`, so `Config/air/line-width: 100` is equivalent to:
+    ///
+    /// ```toml
+    /// [format]
+    /// line-width = 100
+    /// ```
+    pub fn air_toml_options(&self) -> Option<Result<TomlOptions, toml::de::Error>> {
+        let mut global = toml::Table::new();
+        let mut format = toml::Table::new();
+
+        for (key, value) in self.air_config() {
+            let value = config_value(value);
+
+            if GLOBAL_KEYS.contains(&key) {
+                global.insert(key.to_string(), value);
+            } else {
+                format.insert(key.to_string(), value);
+            }
+        }
+
+        if global.is_empty() && format.is_empty() {
+            return None;
+        }
+
+        if !format.is_empty() {
+            global.insert(String::from("format"), toml::Value::Table(format));
+        }
+
+        Some(toml::Value::Table(global).try_into())
+    }
+
+    /// The minimum R version declared in `Depends`, like `R (>= 4.1)`
+    pub fn minimum_r_version(&self) -> Option<RVersion> {
+        self.get("Depends")?.split(',').find_map(|dependency| {
+            let requirement = dependency.trim().strip_prefix('R')?.trim_start();
+            let requirement = requirement.strip_prefix('(')?.strip_suffix(')')?;
+            let version = requirement.trim().strip_prefix(">=")?;
+            version.parse().ok()
+        })
+    }
+
+    /// The encoding of the package files declared in `Encoding`, like `latin1`
+    ///
+    /// Returns `None` for unknown encodings.
+    pub fn encoding(&self) -> Option<Encoding> {
+        let value = self.get("Encoding")?;
+        let encoding = Encoding::for_label(value);
+        if encoding.is_none() {
+            tracing::warn!("Ignoring unsupported `Encoding: {value}` in 'DESCRIPTION'");
+        }
+        encoding
+    }
+}
+
+/// Is this path named like a `DESCRIPTION` file?
+///
+/// Does not check if the path is an existing file on disk
+pub fn is_description<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().ends_with("DESCRIPTION")
+}
+
+/// Is this directory an R package, i.e. does it have a `DESCRIPTION` file?
+pub fn is_package_directory(directory: &Path) -> bool {
+    directory.join("DESCRIPTION").is_file()
+}
+
+/// Convert the value of a `Config/air/*` field into a TOML value
+///
+/// Values are interpreted as TOML values when possible, like `100` or `["a", "b"]`,
+/// and as strings otherwise, so that `Config/air/assignment-style: equal` doesn't
+/// require quotes. R's `TRUE` and `FALSE` are also accepted for booleans. No setting
+/// is a float, so values like `4.1` are kept as strings, as expected by
+/// `minimum-r-version`.
+fn config_value(value: &str) -> toml::Value {
+    match value {
+        "TRUE" => return toml::Value::Boolean(true),
+        "FALSE" => return toml::Value::Boolean(false),
+        _ => {}
+    }
+
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .filter(|value| !value.is_float())
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use settings::RVersion;
+
+    use crate::description::Description;
+    use crate::settings::Encoding;
+    use crate::toml_options::TomlOptions;
+
+    #[test]
+    fn test_parse_description() {
+        let description = Description::parse(
+            "Package: mypackage\nTitle: My Package\nDescription: A package that does\n    many things.\n",
+        );
+
+        assert_eq!(description.get("Package"), Some("mypackage"));
+        assert_eq!(
+            description.get("Description"),
+            Some("A package that does\nmany things.")
+        );
+        assert_eq!(description.get("Depends"), None);
+    }
+
+    #[test]
+    fn test_minimum_r_version() {
+        let description = Description::parse("Package: mypackage\nDepends: R (>= 4.1)\n");
+        assert_eq!(
+            description.minimum_r_version(),
+            Some(RVersion::new(4, 1, 0))
+        );
+
+        let description =
+            Description::parse("Package: mypackage\nDepends:\n    methods,\n    R (>=3.6.2)\n");
+        assert_eq!(
+            description.minimum_r_version(),
+            Some(RVersion::new(3, 6, 2))
+        );
+
+        let description = Description::parse("Package: mypackage\nDepends: Rcpp (>= 1.0)\n");
+        assert_eq!(description.minimum_r_version(), None);
+
+        let description = Description::parse("Package: mypackage\nImports: R6\n");
+        assert_eq!(description.minimum_r_version(), None);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let description = Description::parse("Package: mypackage\nEncoding: latin1\n");
+        assert_eq!(description.encoding(), Encoding::for_label("ISO-8859-1"));
+
+        let description = Description::parse("Package: mypackage\nEncoding: UTF-8\n");
+        assert_eq!(description.encoding(), Some(Encoding::UTF_8));
+
+        let description = Description::parse("Package: mypackage\nEncoding: klingon\n");
+        assert_eq!(description.encoding(), None);
+
+        let description = Description::parse("Package: mypackage\n");
+        assert_eq!(description.encoding(), None);
+    }
+
+    #[test]
+    fn test_air_toml_options() -> anyhow::Result<()> {
+        let description = Description::parse(
+            "Package: mypackage\nConfig/testthat/edition: 3\nConfig/air/line-width: 100\nConfig/air/assignment-style: equal\nConfig/air/persistent-line-breaks: FALSE\nConfig/air/skip: [\"tribble\"]\nConfig/air/minimum-r-version: 4.1\n",
+        );
+
+        let options = description.air_toml_options().unwrap()?;
+
+        let expected: TomlOptions = toml::from_str(
+            r#"
+minimum-r-version = "4.1"
+
+[format]
+line-width = 100
+assignment-style = "equal"
+persistent-line-breaks = false
+skip = ["tribble"]
+"#,
+        )?;
+
+        assert_eq!(options, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_air_toml_options_without_config() {
+        let description = Description::parse("Package: mypackage\nConfig/testthat/edition: 3\n");
+        assert!(description.air_toml_options().is_none());
+    }
+
+    #[test]
+    fn test_air_toml_options_error() {
+        let description = Description::parse("Package: mypackage\nConfig/air/line-width: wide\n");
+        assert!(description.air_toml_options().unwrap().is_err());
+    }
+}
@@ -8,16 +8,25 @@
 use ignore::DirEntry;
 use ignore::gitignore::Glob;
 use rustc_hash::FxHashSet;
+use std::io::BufRead;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::description::Description;
+use crate::description::is_package_directory;
 use crate::resolve::PathResolver;
 use crate::settings::DefaultExcludePatterns;
 use crate::settings::DefaultIncludePatterns;
 use crate::settings::ExcludePatterns;
+use crate::settings::IncludePatterns;
 use crate::settings::Settings;
+use crate::toml::ParseTomlError;
 use crate::toml::find_air_toml_in_directory;
 use crate::toml::parse_air_toml;
+use crate::toml::resolve_extend;
+use crate::toml::user_config_directory;
+use crate::toml_options::TomlOptions;
 
 #[derive(Debug)]
 pub struct DiscoveredSettings {
@@ -31,32 +40,88 @@ pub struct DiscoveredSettings {
 /// [crate::resolve::PathResolver].
 ///
 /// For each `path`, we:
-/// - Walk up its ancestors, looking for an `air.toml`
+/// - Walk up its ancestors, looking for an `air.toml`, or a `DESCRIPTION` file with
+///   `Config/air/*` fields in directories without an `air.toml`
 /// - TODO(hierarchical): Walk down its children, looking for nested `air.toml`s
+///
+/// The closest directory with settings wins, and within a directory an `air.toml`
+/// takes precedence over `DESCRIPTION`. The two are never merged, but either may
+/// `extend` another `air.toml`, which is merged field by field.
+///
+/// Every R package directory (one with a `DESCRIPTION` file) crossed along the way
+/// also gets its own settings, finalized with the defaults of that package, like
+/// `ascii-strings`. These come from the closest settings above the package, or from
+/// the user level `air.toml` or the default settings when there aren't any, so that
+/// the defaults don't depend on where a configuration file lives.
 pub fn discover_settings<P: AsRef<Path>>(paths: &[P]) -> anyhow::Result<Vec<DiscoveredSettings>> {
+    discover_settings_with_user_directory(paths, user_config_directory().as_deref())
+}
+
+fn discover_settings_with_user_directory<P: AsRef<Path>>(
+    paths: &[P],
+    user_directory: Option<&Path>,
+) -> anyhow::Result<Vec<DiscoveredSettings>> {
     let paths: Vec<PathBuf> = paths.iter().map(fs::normalize_path).collect();
 
     let mut seen = FxHashSet::default();
     let mut discovered_settings = Vec::with_capacity(paths.len());
 
+    // Only read when a package isn't covered by any project settings
+    let mut user_config = None;
+
     // Discover all `Settings` across all `paths`, looking up each path's directory tree
     for path in &paths {
+        // Package directories crossed on the way to the closest settings
+        let mut packages = Vec::new();
+        let mut config = None;
+
         for ancestor in path.ancestors() {
             let is_new_ancestor = seen.insert(ancestor);
 
-            if !is_new_ancestor {
+            if !is_new_ancestor && packages.is_empty() {
                 // We already visited this ancestor, we can stop here.
                 break;
             }
 
-            if let Some(toml) = find_air_toml_in_directory(ancestor) {
-                let settings = parse_settings(&toml, ancestor)?;
-                discovered_settings.push(DiscoveredSettings {
-                    directory: ancestor.to_path_buf(),
-                    settings,
-                });
+            let package = is_package_directory(ancestor).then_some(ancestor);
+
+            if let Some(found) = read_config(ancestor)? {
+                if is_new_ancestor {
+                    discovered_settings.push(DiscoveredSettings {
+                        directory: ancestor.to_path_buf(),
+                        settings: found.to_settings(package)?,
+                    });
+                }
+                config = Some(found);
                 break;
             }
+
+            if is_new_ancestor && let Some(package) = package {
+                packages.push(package);
+            }
+        }
+
+        if packages.is_empty() {
+            continue;
+        }
+
+        if config.is_none() {
+            if user_config.is_none() {
+                user_config = Some(read_user_config(user_directory)?);
+            }
+            config = user_config.clone().flatten();
+        }
+
+        for package in packages {
+            let settings = match &config {
+                Some(config) => config.to_settings(Some(package))?,
+                None => TomlOptions::default().into_settings(package, Some(package))?,
+            };
+
+            discovered_settings.push(DiscoveredSettings {
+                directory: package.to_path_buf(),
+                settings,
+            });
         }
     }
 
@@ -73,14 +138,108 @@ pub fn discover_settings<P: AsRef<Path>>(paths: &[P]) -> anyhow::Result<Vec<Disc
     Ok(discovered_settings)
 }
 
-/// Parse [Settings] from a given `air.toml`
-// TODO(hierarchical): Allow for an `extends` option in `air.toml`, which will make things
-// more complex, but will be very useful once we support hierarchical configuration as a
-// way of "inheriting" most top level configuration while slightly tweaking it in a nested directory.
-fn parse_settings(toml: &Path, root_directory: &Path) -> anyhow::Result<Settings> {
+/// Discover [Settings] from the user level `air.toml`, if any
+///
+/// These settings are the fallback for files that aren't covered by a project
+/// `air.toml`. See [crate::toml::user_config_directory()] for its location.
+pub fn discover_user_settings() -> anyhow::Result<Option<Settings>> {
+    match read_user_config(user_config_directory().as_deref())? {
+        Some(config) => Ok(Some(config.to_settings(None)?)),
+        None => Ok(None),
+    }
+}
+
+/// Options read from an `air.toml` or from `Config/air/*` fields, not yet finalized
+/// into [Settings]
+///
+/// They are finalized once per directory they apply to, as package directories get
+/// their own defaults.
+#[derive(Debug, Clone)]
+struct Config {
+    options: TomlOptions,
+
+    /// The directory of the configuration, which patterns are relative to
+    root: PathBuf,
+
+    /// Configuration files inherited through `extend`, from closest to furthest
+    extended: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Finalize the options for the files in `package`, or for files outside of any
+    /// package when `None`
+    fn to_settings(&self, package: Option<&Path>) -> anyhow::Result<Settings> {
+        let mut settings = self.options.clone().into_settings(&self.root, package)?;
+        settings.extended = self.extended.clone();
+        settings.from_config = true;
+        Ok(settings)
+    }
+}
+
+/// Read the configuration in `directory`, if any
+///
+/// An `air.toml` takes precedence over the `Config/air/*` fields of a `DESCRIPTION`.
+fn read_config(directory: &Path) -> anyhow::Result<Option<Config>> {
+    if let Some(toml) = find_air_toml_in_directory(directory) {
+        return Ok(Some(read_air_toml(&toml, directory)?));
+    }
+
+    read_description_config(directory)
+}
+
+/// Read the user level `air.toml` in `directory`, if any
+fn read_user_config(directory: Option<&Path>) -> anyhow::Result<Option<Config>> {
+    let Some(directory) = directory else {
+        return Ok(None);
+    };
+
+    let Some(toml) = find_air_toml_in_directory(directory) else {
+        return Ok(None);
+    };
+
+    Ok(Some(read_air_toml(&toml, directory)?))
+}
+
+/// Read a given `air.toml`, resolving its `extend` chain
+fn read_air_toml(toml: &Path, root_directory: &Path) -> anyhow::Result<Config> {
     let options = parse_air_toml(toml)?;
-    let settings = options.into_settings(root_directory)?;
-    Ok(settings)
+    let (options, extended) = resolve_extend(options, toml)?;
+    tracing::debug!("Using settings from '{toml}'", toml = toml.display());
+    Ok(Config {
+        options,
+        root: root_directory.to_path_buf(),
+        extended,
+    })
+}
+
+/// Read the `Config/air/*` fields of a `DESCRIPTION` file, if any
+fn read_description_config(root_directory: &Path) -> anyhow::Result<Option<Config>> {
+    let path = root_directory.join("DESCRIPTION");
+
+    let Some(description) = Description::read(&path) else {
+        return Ok(None);
+    };
+
+    let Some(options) = description.air_toml_options() else {
+        return Ok(None);
+    };
+
+    let options = options.map_err(|err| ParseTomlError::Deserialize(path.clone(), err))?;
+
+    for (key, value) in description.air_config() {
+        tracing::debug!(
+            "Using `{key}` = `{value}` from `Config/air/{key}` in '{path}'",
+            path = path.display()
+        );
+    }
+
+    let (options, extended) = resolve_extend(options, &path)?;
+
+    Ok(Some(Config {
+        options,
+        root: root_directory.to_path_buf(),
+        extended,
+    }))
 }
 
 type DiscoveredFiles = Vec<Result<PathBuf, ignore::Error>>;
@@ -138,18 +297,21 @@ pub fn discover_r_file_paths<P: AsRef<Path>>(
         builder.add(path);
     }
 
-    // TODO: Make these configurable options (possibly just one?)
-    // Right now we explicitly call them even though they are `true` by default
-    // to remind us to expose them.
-    //
-    // "This toggles, as a group, all the filters that are enabled by default"
-    // builder.standard_filters(true)
-    builder.hidden(true);
+    // The git filters apply to the whole walk, so we can't toggle them per directory.
+    // They are used unless the settings of every supplied path opt out with
+    // `respect-gitignore = false`.
+    let respect_gitignore = std::iter::once(first_path)
+        .chain(paths)
+        .any(|path| resolver.resolve_or_fallback(path).format.respect_gitignore);
+
+    // Hidden entries are handled by the visitor instead, as hidden files like
+    // `.Rprofile` can be included through `include` and `extend-include`.
+    builder.hidden(false);
     builder.parents(true);
     builder.ignore(false);
-    builder.git_ignore(true);
-    builder.git_global(true);
-    builder.git_exclude(true);
+    builder.git_ignore(respect_gitignore);
+    builder.git_global(respect_gitignore);
+    builder.git_exclude(respect_gitignore);
 
     // Prefer `available_parallelism()`, with a max of 12 threads
     builder.threads(
@@ -278,6 +440,16 @@ impl FilesVisitor<'_, '_> {
 
         let is_directory = entry.file_type().is_none_or(|ft| ft.is_dir());
 
+        let is_hidden = !is_directly_supplied
+            && path
+                .file_name()
+                .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."));
+
+        if is_hidden && is_directory {
+            tracing::trace!("Excluded hidden directory: {path}", path = path.display());
+            return ignore::WalkState::Skip;
+        }
+
         // Retrieve the settings for this `path`
         let settings = self.state.resolver.resolve_or_fallback(path);
 
@@ -322,6 +494,24 @@ impl FilesVisitor<'_, '_> {
                     );
                     return ignore::WalkState::Skip;
                 }
+
+                // Same idea for `.Rbuildignore` patterns, which are regexes rather than
+                // globs
+                if let Some(rbuildignore) = settings.format.rbuildignore.as_ref() {
+                    let pattern = if is_directly_supplied {
+                        rbuildignore.matched_path_or_any_parents(path)
+                    } else {
+                        rbuildignore.matched(path)
+                    };
+
+                    if let Some(pattern) = pattern {
+                        tracing::trace!(
+                            "Excluded due to `.Rbuildignore` pattern '{pattern}': {path}",
+                            path = path.display()
+                        );
+                        return ignore::WalkState::Skip;
+                    }
+                }
             }
             Exclude::Nothing => {
                 // Exclusion patterns are not considered
@@ -336,9 +526,21 @@ impl FilesVisitor<'_, '_> {
         // Now handle files
         match self.state.include {
             Include::Matched => {
-                // Files that haven't been excluded are only included if they match a
-                // `default_include` pattern, even if they are directly supplied by the user!
-                match any_include_matched_path(path, settings.format.default_include.as_ref()) {
+                // Files that haven't been excluded are only included if they match an
+                // `include` or `default_include` pattern, even if they are directly
+                // supplied by the user! Hidden files must be explicitly included by the
+                // user, so they don't consider the `default_include` patterns.
+                let default_include = if is_hidden {
+                    None
+                } else {
+                    settings.format.default_include.as_ref()
+                };
+
+                match any_include_matched_path(
+                    path,
+                    settings.format.include.as_ref(),
+                    default_include,
+                ) {
                     Some(glob) => {
                         tracing::trace!(
                             "Included due to '{glob}': {path}",
@@ -348,6 +550,16 @@ impl FilesVisitor<'_, '_> {
                         self.files.push(Ok(entry.into_path()));
                         ignore::WalkState::Continue
                     }
+                    // Like the default patterns, the `Rscript` shebang is replaced by
+                    // a user supplied `include`
+                    None if default_include.is_some() && has_rscript_shebang(path) => {
+                        tracing::trace!(
+                            "Included due to `Rscript` shebang: {path}",
+                            path = path.display()
+                        );
+                        self.files.push(Ok(entry.into_path()));
+                        ignore::WalkState::Continue
+                    }
                     None => {
                         tracing::trace!(
                             "Excluded due to not matching an include: {path}",
@@ -357,6 +569,22 @@ impl FilesVisitor<'_, '_> {
                     }
                 }
             }
+            Include::Everything if is_hidden => {
+                // Hidden files must still be explicitly included by the user
+                match any_include_matched_path(path, settings.format.include.as_ref(), None) {
+                    Some(_) => {
+                        tracing::trace!(
+                            "Included due to including everything: {path}",
+                            path = path.display()
+                        );
+                        self.files.push(Ok(entry.into_path()));
+                    }
+                    None => {
+                        tracing::trace!("Excluded hidden file: {path}", path = path.display());
+                    }
+                }
+                ignore::WalkState::Continue
+            }
             Include::Everything => {
                 tracing::trace!(
                     "Included due to including everything: {path}",
@@ -440,27 +668,72 @@ pub fn any_exclude_matched_path_or_any_parents<'patterns, P: AsRef<Path>>(
 ///
 /// Includes are only about files, so this is only ever called on a file and never a
 /// directory
-pub fn any_include_matched_path<P: AsRef<Path>>(
+pub fn any_include_matched_path<'patterns, P: AsRef<Path>>(
     path: P,
-    default_include: Option<&DefaultIncludePatterns>,
-) -> Option<&Glob> {
+    include: Option<&'patterns IncludePatterns>,
+    default_include: Option<&'patterns DefaultIncludePatterns>,
+) -> Option<&'patterns Glob> {
     const IS_DIRECTORY: bool = false;
 
     let path = path.as_ref();
 
+    if let Some(glob) = include.and_then(|include| include.matched(path, IS_DIRECTORY)) {
+        return Some(glob);
+    }
+
     default_include.and_then(|default_include| default_include.matched(path, IS_DIRECTORY))
 }
 
+/// Does this extension-less file start with an `Rscript` shebang?
+///
+/// Matches shebangs like `#!/usr/bin/env Rscript` and `#!/usr/local/bin/Rscript
+/// --vanilla`. Only the first line of the file is read.
+fn has_rscript_shebang<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+
+    if path.extension().is_some() {
+        return false;
+    }
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+
+    // Limit the read in case of a binary file without line breaks
+    let mut line = Vec::new();
+    let mut reader = std::io::BufReader::new(file).take(256);
+    if reader.read_until(b'\n', &mut line).is_err() {
+        return false;
+    }
+
+    let Some(interpreter) = line.strip_prefix(b"#!") else {
+        return false;
+    };
+
+    String::from_utf8_lossy(interpreter)
+        .split_whitespace()
+        .take_while(|word| !word.starts_with('-') || *word == "-S")
+        .any(|word| word.rsplit('/').next() == Some("Rscript"))
+}
+
 #[cfg(test)]
 mod test {
+    use std::path::Path;
+    use std::path::PathBuf;
+
     use anyhow::Context;
+    use settings::AsciiStrings;
+    use settings::AssignmentStyle;
+    use settings::RVersion;
     use tempfile::TempDir;
 
+    use crate::description::Description;
     use crate::discovery::Exclude;
     use crate::discovery::Include;
     use crate::discovery::Mode;
     use crate::discovery::discover_r_file_paths;
     use crate::discovery::discover_settings;
+    use crate::discovery::discover_settings_with_user_directory;
     use crate::resolve::PathResolver;
     use crate::settings::Settings;
 
@@ -938,6 +1211,139 @@ exclude = ["exclude/"]
         Ok(())
     }
 
+    #[test]
+    fn test_hidden_files_are_excluded_unless_included() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::create_dir(tempdir.join(".hidden"))?;
+
+        let test_path = tempdir.join("test.R");
+        std::fs::write(&test_path, b"")?;
+
+        // Hidden files and directories don't match `default-include`
+        let hidden_r_path = tempdir.join(".hidden.R");
+        std::fs::write(&hidden_r_path, b"")?;
+        std::fs::write(tempdir.join(".hidden").join("test.R"), b"")?;
+
+        let rprofile_path = tempdir.join(".Rprofile");
+        std::fs::write(&rprofile_path, b"")?;
+
+        let template_path = tempdir.join("template.R.in");
+        std::fs::write(&template_path, b"")?;
+
+        let resolver = PathResolver::new(Settings::default());
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        assert_eq!(paths, vec![test_path.clone()]);
+
+        // But they can be included through `extend-include`
+        std::fs::write(
+            tempdir.join("air.toml"),
+            "[format]\nextend-include = [\".Rprofile\", \"*.R.in\"]\n",
+        )?;
+
+        let mut resolver = PathResolver::new(Settings::default());
+        for item in discover_settings(&[tempdir])? {
+            resolver.add(&item.directory, item.settings);
+        }
+
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let mut paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        paths.sort();
+
+        let mut expect = vec![rprofile_path, test_path, template_path.clone()];
+        expect.sort();
+        assert_eq!(paths, expect);
+
+        // While `include` replaces the default patterns
+        std::fs::write(
+            tempdir.join("air.toml"),
+            "[format]\ninclude = [\"*.R.in\"]\n",
+        )?;
+
+        let mut resolver = PathResolver::new(Settings::default());
+        for item in discover_settings(&[tempdir])? {
+            resolver.add(&item.directory, item.settings);
+        }
+
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        assert_eq!(paths, vec![template_path]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rscript_shebang_files_are_included() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        let env_path = tempdir.join("script");
+        std::fs::write(&env_path, b"#!/usr/bin/env Rscript\n1 + 1\n")?;
+
+        let direct_path = tempdir.join("script2");
+        std::fs::write(&direct_path, b"#!/usr/local/bin/Rscript --vanilla\n1 + 1\n")?;
+
+        std::fs::write(tempdir.join("script3"), b"#!/bin/sh\necho Rscript\n")?;
+        std::fs::write(tempdir.join("script4"), b"1 + 1\n")?;
+        std::fs::write(tempdir.join("script.sh"), b"#!/usr/bin/env Rscript\n")?;
+
+        let resolver = PathResolver::new(Settings::default());
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let mut paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        paths.sort();
+
+        let mut expect = vec![env_path, direct_path];
+        expect.sort();
+        assert_eq!(paths, expect);
+
+        // Supplying `include` replaces the shebang detection along with the default
+        // patterns
+        std::fs::write(tempdir.join("air.toml"), "[format]\ninclude = [\"*.sh\"]\n")?;
+
+        let mut resolver = PathResolver::new(Settings::default());
+        for item in discover_settings(&[tempdir])? {
+            resolver.add(&item.directory, item.settings);
+        }
+
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        assert_eq!(paths, vec![tempdir.join("script.sh")]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_gitignore() -> anyhow::Result<()> {
         let tempdir = TempDir::new()?;
@@ -995,4 +1401,354 @@ ignore/
 
         Ok(())
     }
+
+    #[test]
+    fn test_respect_gitignore_option() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::create_dir(tempdir.join(".git"))?;
+        std::fs::write(tempdir.join(".gitignore"), "ignore/\n")?;
+        std::fs::write(
+            tempdir.join("air.toml"),
+            "[format]\nrespect-gitignore = false\n",
+        )?;
+
+        std::fs::create_dir(tempdir.join("ignore"))?;
+        let test_path = tempdir.join("ignore").join("test.R");
+        std::fs::write(&test_path, b"")?;
+
+        let mut resolver = PathResolver::new(Settings::default());
+        for item in discover_settings(&[tempdir])? {
+            resolver.add(&item.directory, item.settings);
+        }
+
+        let paths = discover_r_file_paths(
+            &[tempdir],
+            &resolver,
+            Mode::Format,
+            Exclude::Matched,
+            Include::Matched,
+        );
+        let paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+        assert_eq!(paths, vec![test_path]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_respect_rbuildignore_option() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        // The `.Rbuildignore` of the package is used, rather than the one next to an
+        // `air.toml` in a parent directory
+        let package = tempdir.join("pkg");
+        std::fs::create_dir_all(package.join("R"))?;
+        std::fs::create_dir_all(package.join("data-raw"))?;
+        std::fs::write(package.join("DESCRIPTION"), "Package: pkg\n")?;
+        std::fs::write(package.join(".Rbuildignore"), "^data-raw$\n^dev\\.R$\n")?;
+        std::fs::write(tempdir.join(".Rbuildignore"), "^pkg$\n")?;
+
+        let test_path = package.join("R").join("test.R");
+        std::fs::write(&test_path, b"")?;
+
+        let data_path = package.join("data-raw").join("data.R");
+        std::fs::write(&data_path, b"")?;
+        std::fs::write(package.join("dev.R"), b"")?;
+
+        let discover = |contents: &str, start: &Path| -> anyhow::Result<Vec<PathBuf>> {
+            std::fs::write(tempdir.join("air.toml"), contents)?;
+
+            let mut resolver = PathResolver::new(Settings::default());
+            for item in discover_settings(&[start])? {
+                resolver.add(&item.directory, item.settings);
+            }
+
+            let paths = discover_r_file_paths(
+                &[start],
+                &resolver,
+                Mode::Format,
+                Exclude::Matched,
+                Include::Matched,
+            );
+            let mut paths: Vec<_> = paths.into_iter().collect::<Result<_, _>>()?;
+            paths.sort();
+            Ok(paths)
+        };
+
+        // `.Rbuildignore` is not respected by default
+        let paths = discover("", &package)?;
+        assert_eq!(paths.len(), 3);
+
+        let contents = "[format]\nrespect-rbuildignore = true\n";
+
+        let paths = discover(contents, &package)?;
+        assert_eq!(paths, vec![test_path]);
+
+        // Parents of directly supplied paths are considered too
+        let paths = discover(contents, &data_path)?;
+        assert!(paths.is_empty());
+
+        // Unsupported patterns are reported rather than ignored
+        std::fs::write(package.join(".Rbuildignore"), "(?<=foo)bar\n")?;
+        assert!(discover(contents, &package).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_description_config_settings() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::create_dir(tempdir.join("R"))?;
+
+        let description_path = tempdir.join("DESCRIPTION");
+        let description_contents = "Package: test\nConfig/air/line-width: 100\n";
+        std::fs::write(&description_path, description_contents)?;
+
+        {
+            // `{tempdir}/R`
+            // Without an `air.toml`, `Config/air/*` fields are used
+            let start = &[tempdir.join("R")];
+
+            let mut settings = discover_settings(start)?;
+            let settings = settings.pop().context("Should find DESCRIPTION")?;
+
+            assert_eq!(settings.directory, tempdir);
+            assert_eq!(settings.settings.format.line_width.value(), 100);
+        }
+
+        let air_path = tempdir.join("air.toml");
+        let air_contents = r#"
+[format]
+indent-width = 4
+"#;
+        std::fs::write(&air_path, air_contents)?;
+
+        {
+            // `{tempdir}/R`
+            // An `air.toml` takes precedence, and the two aren't merged
+            let start = &[tempdir.join("R")];
+
+            let mut settings = discover_settings(start)?;
+            let settings = settings.pop().context("Should find air.toml")?;
+
+            assert_eq!(settings.settings.format.line_width.value(), 80);
+            assert_eq!(settings.settings.format.indent_width.value(), 4);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_without_config_gets_package_settings() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        std::fs::create_dir(tempdir.join("R"))?;
+        std::fs::write(tempdir.join("DESCRIPTION"), "Package: test\n")?;
+
+        let mut settings = discover_settings_with_user_directory(&[tempdir.join("R")], None)?;
+        let settings = settings.pop().context("Should find DESCRIPTION")?;
+
+        assert_eq!(settings.directory, tempdir);
+        assert_eq!(settings.settings.format.ascii_strings, AsciiStrings::Escape);
+        assert!(!settings.settings.from_config);
+
+        // The user level `air.toml` is used when there are no project settings
+        let user_directory = tempdir.join("user");
+        std::fs::create_dir(&user_directory)?;
+        std::fs::write(
+            user_directory.join("air.toml"),
+            "[format]\nline-width = 120\n",
+        )?;
+
+        let mut settings =
+            discover_settings_with_user_directory(&[tempdir.join("R")], Some(&user_directory))?;
+        let settings = settings.pop().context("Should find DESCRIPTION")?;
+
+        assert_eq!(settings.directory, tempdir);
+        assert_eq!(settings.settings.format.line_width.value(), 120);
+        assert_eq!(settings.settings.format.ascii_strings, AsciiStrings::Escape);
+        assert!(settings.settings.from_config);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_below_air_toml_gets_package_settings() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        let package = tempdir.join("pkg");
+        std::fs::create_dir_all(package.join("R"))?;
+        std::fs::write(package.join("DESCRIPTION"), "Package: test\n")?;
+        std::fs::write(tempdir.join("air.toml"), "[format]\nline-width = 100\n")?;
+
+        let mut resolver = PathResolver::new(Settings::default());
+        for item in discover_settings_with_user_directory(&[package.join("R")], None)? {
+            resolver.add(&item.directory, item.settings);
+        }
+
+        // Files in the package get the package defaults on top of the `air.toml`
+        let settings = resolver
+            .resolve(&package.join("R").join("code.R"))
+            .context("Should resolve package settings")?
+            .value();
+        assert_eq!(settings.format.line_width.value(), 100);
+        assert_eq!(settings.format.ascii_strings, AsciiStrings::Escape);
+
+        // Other files only get the `air.toml`
+        let settings = resolver
+            .resolve(&tempdir.join("code.R"))
+            .context("Should resolve air.toml settings")?
+            .value();
+        assert_eq!(settings.format.line_width.value(), 100);
+        assert_eq!(settings.format.ascii_strings, AsciiStrings::Preserve);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimum_r_version_is_inferred_for_every_package() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        let description = "Package: test\nDepends: R (>= 4.1)\n";
+
+        let minimum_r_version = |path: &Path| -> anyhow::Result<Option<RVersion>> {
+            let mut settings = discover_settings_with_user_directory(&[path], None)?;
+            let settings = settings.pop().context("Should find DESCRIPTION")?;
+            Ok(settings.settings.format.minimum_r_version)
+        };
+
+        // Without any configuration
+        let package = tempdir.join("plain");
+        std::fs::create_dir(&package)?;
+        std::fs::write(package.join("DESCRIPTION"), description)?;
+        assert_eq!(minimum_r_version(&package)?, Some(RVersion::new(4, 1, 0)));
+
+        // With `Config/air/*` fields
+        let package = tempdir.join("config");
+        std::fs::create_dir(&package)?;
+        let contents = format!("{description}Config/air/line-width: 100\n");
+        std::fs::write(package.join("DESCRIPTION"), contents)?;
+        assert_eq!(minimum_r_version(&package)?, Some(RVersion::new(4, 1, 0)));
+
+        // With an `air.toml` next to the `DESCRIPTION`
+        let package = tempdir.join("toml");
+        std::fs::create_dir(&package)?;
+        std::fs::write(package.join("DESCRIPTION"), description)?;
+        std::fs::write(package.join("air.toml"), "")?;
+        assert_eq!(minimum_r_version(&package)?, Some(RVersion::new(4, 1, 0)));
+
+        // Below an `air.toml`
+        let package = tempdir.join("nested").join("pkg");
+        std::fs::create_dir_all(&package)?;
+        std::fs::write(package.join("DESCRIPTION"), description)?;
+        std::fs::write(tempdir.join("air.toml"), "")?;
+        assert_eq!(minimum_r_version(&package)?, Some(RVersion::new(4, 1, 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_settings() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        std::fs::create_dir_all(tempdir.join("shared"))?;
+        std::fs::create_dir_all(tempdir.join("packages").join("pkg"))?;
+
+        let shared_contents = r#"
+minimum-r-version = "4.1"
+
+[format]
+line-width = 100
+indent-width = 4
+"#;
+        std::fs::write(tempdir.join("shared").join("air.toml"), shared_contents)?;
+
+        let parent_contents = r#"
+extend = "shared/air.toml"
+
+[format]
+indent-width = 8
+assignment-style = "equal"
+"#;
+        std::fs::write(tempdir.join("air.toml"), parent_contents)?;
+
+        let pkg_contents = r#"
+extend = "../../air.toml"
+
+[format]
+assignment-style = "arrow"
+"#;
+        let pkg_path = tempdir.join("packages").join("pkg").join("air.toml");
+        std::fs::write(&pkg_path, pkg_contents)?;
+
+        let mut settings = discover_settings(&[tempdir.join("packages").join("pkg")])?;
+        let settings = settings.pop().context("Should find air.toml")?;
+        let settings = settings.settings;
+
+        // Closest file wins, field by field
+        assert_eq!(settings.format.line_width.value(), 100);
+        assert_eq!(settings.format.indent_width.value(), 8);
+        assert_eq!(settings.format.assignment_style, AssignmentStyle::Arrow);
+        assert_eq!(
+            settings.format.minimum_r_version,
+            Some(RVersion::new(4, 1, 0))
+        );
+
+        assert_eq!(
+            settings.extended,
+            vec![
+                tempdir.join("air.toml"),
+                tempdir.join("shared").join("air.toml")
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_errors() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = fs::normalize_path(tempdir.path());
+
+        std::fs::create_dir(tempdir.join("a"))?;
+        std::fs::create_dir(tempdir.join("b"))?;
+
+        let a = tempdir.join("a").join("air.toml");
+        let b = tempdir.join("b").join("air.toml");
+
+        // Missing file
+        std::fs::write(&a, "extend = \"../missing.toml\"\n")?;
+
+        let error = discover_settings(&[tempdir.join("a")]).unwrap_err();
+        let error = error.to_string();
+        assert!(error.starts_with(&format!(
+            "Failed to resolve `extend` chain {a} -> {missing}:\nFailed to read {missing}",
+            a = a.display(),
+            missing = tempdir.join("missing.toml").display()
+        )));
+
+        // Cycle
+        std::fs::write(&a, "extend = \"../b/air.toml\"\n")?;
+        std::fs::write(&b, "extend = \"../a/air.toml\"\n")?;
+
+        let error = discover_settings(&[tempdir.join("a")]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Circular `extend` chain {a} -> {b} -> {a}",
+                a = a.display(),
+                b = b.display()
+            )
+        );
+
+        Ok(())
+    }
 }
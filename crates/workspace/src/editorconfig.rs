@@ -0,0 +1,632 @@
+//
+// editorconfig.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+//! Utilities for extracting formatting options from `.editorconfig` files.
+//!
+//! `.editorconfig` files are a lower precedence layer beneath the `air.toml`: their
+//! properties only apply to options that the `air.toml` leaves unset. See
+//! <https://spec.editorconfig.org> for the file format.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use settings::IndentStyle;
+use settings::IndentWidth;
+use settings::LineEnding;
+use settings::LineWidth;
+
+/// The formatting options supplied by the `.editorconfig` files that apply to a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EditorConfigProperties {
+    /// From `indent_style`
+    pub indent_style: Option<IndentStyle>,
+
+    /// From `indent_size`, or `tab_width` if `indent_size` is unset or `tab`
+    pub indent_width: Option<IndentWidth>,
+
+    /// From `max_line_length`
+    pub line_width: Option<LineWidth>,
+
+    /// From `end_of_line`
+    pub line_ending: Option<LineEnding>,
+}
+
+impl EditorConfigProperties {
+    /// Resolve the properties of the `.editorconfig` files that apply to the file at
+    /// `path`
+    ///
+    /// `.editorconfig` files are looked up in the directory of `path` and all of its
+    /// ancestors, stopping at the first one that declares `root = true`. Closer files
+    /// take precedence over more distant ones, and within a file, later sections take
+    /// precedence over earlier ones.
+    pub fn resolve(path: &Path) -> Self {
+        Self::resolve_with(path, |directory| {
+            EditorConfigFile::read(directory).map(Arc::new)
+        })
+    }
+
+    /// Resolve the properties that apply to `path`, with `read` returning the
+    /// `.editorconfig` file of a directory, if any
+    fn resolve_with(
+        path: &Path,
+        mut read: impl FnMut(&Path) -> Option<Arc<EditorConfigFile>>,
+    ) -> Self {
+        let Some(directory) = path.parent() else {
+            return Self::default();
+        };
+
+        let mut files = Vec::new();
+
+        for ancestor in directory.ancestors() {
+            let Some(file) = read(ancestor) else {
+                continue;
+            };
+
+            let root = file.root;
+            files.push((ancestor, file));
+
+            if root {
+                break;
+            }
+        }
+
+        let mut properties = BTreeMap::new();
+
+        for (directory, file) in files.iter().rev() {
+            let Ok(relative) = path.strip_prefix(directory) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+
+            for section in &file.sections {
+                if !section_matches(&section.glob, &relative) {
+                    continue;
+                }
+
+                for (key, value) in &section.properties {
+                    if value == "unset" {
+                        properties.remove(key.as_str());
+                    } else {
+                        properties.insert(key.as_str(), value.as_str());
+                    }
+                }
+            }
+        }
+
+        let properties = Self::from_properties(&properties);
+
+        if properties != Self::default() {
+            tracing::debug!(
+                "Using `.editorconfig` properties for '{path}': {properties:?}",
+                path = path.display()
+            );
+        }
+
+        properties
+    }
+
+    fn from_properties(properties: &BTreeMap<&str, &str>) -> Self {
+        let indent_style = match properties.get("indent_style").copied() {
+            Some("space") => Some(IndentStyle::Space),
+            Some("tab") => Some(IndentStyle::Tab),
+            _ => None,
+        };
+
+        let parse_width = |key: &str| {
+            let value: u8 = properties.get(key)?.parse().ok()?;
+            IndentWidth::try_from(value).ok()
+        };
+
+        let indent_width = match properties.get("indent_size").copied() {
+            Some("tab") | None => parse_width("tab_width"),
+            Some(_) => parse_width("indent_size"),
+        };
+
+        let line_width = properties
+            .get("max_line_length")
+            .and_then(|value| value.parse::<u16>().ok())
+            .and_then(|value| LineWidth::try_from(value).ok());
+
+        let line_ending = match properties.get("end_of_line").copied() {
+            Some("lf") => Some(LineEnding::Lf),
+            Some("crlf") => Some(LineEnding::Crlf),
+            _ => None,
+        };
+
+        Self {
+            indent_style,
+            indent_width,
+            line_width,
+            line_ending,
+        }
+    }
+}
+
+/// A cache of the `.editorconfig` files used to resolve [EditorConfigProperties]
+///
+/// Files are cached by directory, including the absence of a file, so that resolving
+/// the properties of a path doesn't touch the disk once its ancestors have been read.
+/// The cache must be invalidated with [EditorConfigCache::invalidate()] when an
+/// `.editorconfig` file is created, modified, or deleted.
+#[derive(Debug, Default)]
+pub struct EditorConfigCache {
+    files: Mutex<HashMap<PathBuf, Option<Arc<EditorConfigFile>>>>,
+}
+
+impl EditorConfigCache {
+    /// Like [EditorConfigProperties::resolve()], but reading each `.editorconfig` file
+    /// at most once
+    pub fn resolve(&self, path: &Path) -> EditorConfigProperties {
+        let mut files = self.files.lock().unwrap();
+
+        EditorConfigProperties::resolve_with(path, |directory| {
+            files
+                .entry(directory.to_path_buf())
+                .or_insert_with(|| EditorConfigFile::read(directory).map(Arc::new))
+                .clone()
+        })
+    }
+
+    /// Invalidate the `.editorconfig` file at `path`
+    pub fn invalidate(&mut self, path: &Path) {
+        if let Some(directory) = path.parent() {
+            self.files.get_mut().unwrap().remove(directory);
+        }
+    }
+}
+
+/// Is this path named like an `.editorconfig` file?
+///
+/// Does not check if the path is an existing file on disk
+pub fn is_editorconfig<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().ends_with(".editorconfig")
+}
+
+/// A parsed `.editorconfig` file
+#[derive(Debug, Default)]
+struct EditorConfigFile {
+    /// Whether `root = true` is declared, which stops the search for `.editorconfig`
+    /// files in parent directories
+    root: bool,
+
+    sections: Vec<EditorConfigSection>,
+}
+
+#[derive(Debug)]
+struct EditorConfigSection {
+    /// The glob of the section header, like `*.R` in `[*.R]`
+    glob: String,
+
+    /// The `key = value` pairs of the section. Keys and values are lowercased, as
+    /// the properties we care about are case insensitive.
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfigFile {
+    /// Read the `.editorconfig` file of `directory`, if any
+    fn read(directory: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(directory.join(".editorconfig")).ok()?;
+        Some(Self::parse(&contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut file = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if let Some(glob) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                file.sections.push(EditorConfigSection {
+                    glob: glob.to_string(),
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+
+            match file.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                // The preamble only supports `root`
+                None => {
+                    if key == "root" {
+                        file.root = value == "true";
+                    }
+                }
+            }
+        }
+
+        file
+    }
+}
+
+/// Does the glob of a section match `path`, relative to the `.editorconfig` directory?
+///
+/// Globs without a `/` match files in any subdirectory, like `*.R`. Other globs are
+/// relative to the directory of the `.editorconfig`, like `R/*.R`.
+fn section_matches(glob: &str, path: &str) -> bool {
+    let glob = if glob.contains('/') {
+        glob.strip_prefix('/').unwrap_or(glob).to_string()
+    } else {
+        format!("**/{glob}")
+    };
+
+    let path: Vec<char> = path.chars().collect();
+
+    expand_braces(&glob).iter().any(|glob| {
+        let glob: Vec<char> = glob.chars().collect();
+        glob_matches(&glob, &path)
+    })
+}
+
+/// Expands the `{a,b}` alternatives and `{1..3}` numeric ranges of a glob
+///
+/// Braces without alternatives, like `{a}`, are matched literally.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let chars: Vec<char> = glob.chars().collect();
+
+    let Some((start, end)) = find_braces(&chars) else {
+        return vec![glob.to_string()];
+    };
+
+    let prefix: String = chars[..start].iter().collect();
+    let inner: String = chars[start + 1..end].iter().collect();
+    let suffix: String = chars[end + 1..].iter().collect();
+
+    let alternatives = split_alternatives(&inner);
+
+    let alternatives = if alternatives.len() > 1 {
+        alternatives
+    } else if let Some(range) = numeric_range(&inner) {
+        range
+    } else {
+        // Escape the braces so they are matched literally
+        return expand_braces(&format!("{prefix}\\{{{inner}\\}}{suffix}"));
+    };
+
+    alternatives
+        .iter()
+        .flat_map(|alternative| expand_braces(&format!("{prefix}{alternative}{suffix}")))
+        .collect()
+}
+
+/// Finds the first unescaped `{` and its matching `}`
+fn find_braces(chars: &[char]) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '{' => {
+                if depth == 0 {
+                    start = Some(i);
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((start?, i));
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Splits the inside of braces on its top level commas
+fn split_alternatives(inner: &str) -> Vec<String> {
+    let mut alternatives = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+                continue;
+            }
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+
+    alternatives.push(current);
+    alternatives
+}
+
+/// Expands a numeric range like `1..3`
+fn numeric_range(inner: &str) -> Option<Vec<String>> {
+    /// Larger ranges are matched literally rather than expanded
+    const MAX_RANGE: i64 = 1000;
+
+    let (from, to) = inner.split_once("..")?;
+    let from: i64 = from.parse().ok()?;
+    let to: i64 = to.parse().ok()?;
+    let (from, to) = (from.min(to), from.max(to));
+
+    if to - from > MAX_RANGE {
+        return None;
+    }
+
+    Some((from..=to).map(|number| number.to_string()).collect())
+}
+
+/// Matches a glob against a path, both split into characters
+///
+/// - `*` matches any sequence of characters, except `/`
+/// - `**` matches any sequence of characters, including `/`
+/// - `?` matches any character, except `/`
+/// - `[abc]`, `[a-z]`, and `[!abc]` match any character in (or not in) the set
+/// - `\` escapes the next character
+fn glob_matches(glob: &[char], path: &[char]) -> bool {
+    match glob {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches no directory at all
+            if let ['/', after @ ..] = rest
+                && glob_matches(after, path)
+            {
+                return true;
+            }
+            (0..=path.len()).any(|i| glob_matches(rest, &path[i..]))
+        }
+        ['*', rest @ ..] => {
+            let end = path.iter().position(|c| *c == '/').unwrap_or(path.len());
+            (0..=end).any(|i| glob_matches(rest, &path[i..]))
+        }
+        ['?', rest @ ..] => match path {
+            [c, path @ ..] if *c != '/' => glob_matches(rest, path),
+            _ => false,
+        },
+        ['[', class @ ..] => match class_matches(class, path.first().copied()) {
+            Some((true, rest)) => glob_matches(rest, &path[1..]),
+            Some((false, _)) => false,
+            // An unclosed `[` is matched literally
+            None => path.first() == Some(&'[') && glob_matches(class, &path[1..]),
+        },
+        ['\\', c, rest @ ..] | [c, rest @ ..] => match path {
+            [first, path @ ..] if first == c => glob_matches(rest, path),
+            _ => false,
+        },
+    }
+}
+
+/// Matches a character class, like `[a-z]`, against `c`
+///
+/// `class` starts right after the opening `[`. Returns whether `c` matched, and the
+/// rest of the glob after the closing `]`, or `None` if the class isn't closed.
+fn class_matches(class: &[char], c: Option<char>) -> Option<(bool, &[char])> {
+    let (negated, class) = match class {
+        ['!' | '^', class @ ..] => (true, class),
+        class => (false, class),
+    };
+
+    // A `]` right after the opening `[` is part of the set
+    let close = class.iter().skip(1).position(|c| *c == ']')? + 1;
+    let (set, rest) = (&class[..close], &class[close + 1..]);
+
+    let Some(c) = c.filter(|c| *c != '/') else {
+        return Some((false, rest));
+    };
+
+    let mut matched = false;
+    let mut i = 0;
+
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            matched |= (set[i]..=set[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= set[i] == c;
+            i += 1;
+        }
+    }
+
+    Some((matched != negated, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use settings::IndentStyle;
+    use settings::IndentWidth;
+    use settings::LineEnding;
+    use settings::LineWidth;
+    use tempfile::TempDir;
+
+    use biome_formatter::FormatOptions;
+
+    use crate::editorconfig::EditorConfigCache;
+    use crate::editorconfig::EditorConfigProperties;
+    use crate::editorconfig::section_matches;
+    use crate::toml::parse_air_inline_toml;
+
+    #[test]
+    fn test_section_matches() {
+        assert!(section_matches("*", "test.R"));
+        assert!(section_matches("*", "R/test.R"));
+        assert!(section_matches("*.R", "R/test.R"));
+        assert!(!section_matches("*.R", "R/test.py"));
+        assert!(section_matches("*.{R,r,Rmd}", "test.r"));
+        assert!(!section_matches("*.{R,r,Rmd}", "test.qmd"));
+        assert!(section_matches("R/*.R", "R/test.R"));
+        assert!(!section_matches("R/*.R", "R/sub/test.R"));
+        assert!(!section_matches("R/*.R", "tests/R/test.R"));
+        assert!(section_matches("/R/**.R", "R/sub/test.R"));
+        assert!(section_matches("R/**/*.R", "R/test.R"));
+        assert!(section_matches("test?.R", "test1.R"));
+        assert!(section_matches("[Tt]est.R", "Test.R"));
+        assert!(!section_matches("[!Tt]est.R", "Test.R"));
+        assert!(section_matches("test{1..3}.R", "test2.R"));
+        assert!(!section_matches("test{1..3}.R", "test4.R"));
+        assert!(section_matches("{a}.R", "{a}.R"));
+    }
+
+    #[test]
+    fn test_resolve_properties() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::create_dir(tempdir.join("R"))?;
+
+        let editorconfig = "
+root = true
+
+[*]
+indent_style = space
+indent_size = 4
+max_line_length = 100
+end_of_line = crlf
+
+# R files use tabs
+[*.{R,r}]
+indent_style = tab
+indent_size = tab
+tab_width = 8
+
+[*.py]
+indent_size = 2
+";
+        std::fs::write(tempdir.join(".editorconfig"), editorconfig)?;
+
+        // Closer files take precedence
+        std::fs::write(
+            tempdir.join("R").join(".editorconfig"),
+            "[*]\nmax_line_length = off\nend_of_line = unset\n",
+        )?;
+
+        assert_eq!(
+            EditorConfigProperties::resolve(&tempdir.join("test.R")),
+            EditorConfigProperties {
+                indent_style: Some(IndentStyle::Tab),
+                indent_width: Some(IndentWidth::try_from(8_u8).unwrap()),
+                line_width: Some(LineWidth::try_from(100_u16).unwrap()),
+                line_ending: Some(LineEnding::Crlf),
+            }
+        );
+
+        assert_eq!(
+            EditorConfigProperties::resolve(&tempdir.join("R").join("test.R")),
+            EditorConfigProperties {
+                indent_style: Some(IndentStyle::Tab),
+                indent_width: Some(IndentWidth::try_from(8_u8).unwrap()),
+                line_width: None,
+                line_ending: None,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_stops_at_root() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::create_dir(tempdir.join("project"))?;
+
+        std::fs::write(tempdir.join(".editorconfig"), "[*]\nindent_size = 8\n")?;
+        std::fs::write(
+            tempdir.join("project").join(".editorconfig"),
+            "root = true\n[*]\nindent_style = space\n",
+        )?;
+
+        assert_eq!(
+            EditorConfigProperties::resolve(&tempdir.join("project").join("test.R")),
+            EditorConfigProperties {
+                indent_style: Some(IndentStyle::Space),
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_is_invalidated() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        let editorconfig = tempdir.join(".editorconfig");
+        let path = tempdir.join("test.R");
+
+        std::fs::write(&editorconfig, "root = true\n[*]\nindent_size = 4\n")?;
+
+        let mut cache = EditorConfigCache::default();
+        assert_eq!(
+            cache.resolve(&path).indent_width,
+            Some(IndentWidth::try_from(4_u8).unwrap())
+        );
+
+        // Changes are only seen once the file is invalidated
+        std::fs::write(&editorconfig, "root = true\n[*]\nindent_size = 8\n")?;
+        assert_eq!(
+            cache.resolve(&path).indent_width,
+            Some(IndentWidth::try_from(4_u8).unwrap())
+        );
+
+        cache.invalidate(&editorconfig);
+        assert_eq!(
+            cache.resolve(&path).indent_width,
+            Some(IndentWidth::try_from(8_u8).unwrap())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_air_toml_takes_precedence() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let tempdir = tempdir.path();
+
+        std::fs::write(
+            tempdir.join(".editorconfig"),
+            "root = true\n[*]\nindent_style = tab\nmax_line_length = 100\n",
+        )?;
+
+        let path = tempdir.join("test.R");
+
+        let settings =
+            parse_air_inline_toml("[format]\nline-width = 120\n")?.into_settings(tempdir, None)?;
+        let options = settings.format.to_format_options_for_path("", &path);
+
+        assert_eq!(options.line_width().value(), 120);
+        assert_eq!(options.indent_style(), biome_formatter::IndentStyle::Tab);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! Decoding and encoding of R files that aren't encoded in UTF-8
+//!
+//! Older R packages declare the encoding of their files with the `Encoding` field of
+//! their `DESCRIPTION`, usually `latin1`. These files are decoded to UTF-8 for
+//! formatting, and the formatted code is encoded back to the original encoding, so
+//! that formatting never changes the encoding of a file.
+
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::settings::Encoding;
+
+/// The encoding of a decoded file, used to encode it back after formatting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    encoding: &'static encoding_rs::Encoding,
+    /// Whether the file starts with a byte order mark, which is kept when encoding
+    bom: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    #[error(
+        "Invalid UTF-8 on line {line}. If the file uses another encoding, like latin1, declare it with the `Encoding` field of the `DESCRIPTION` or the `encoding` option of the `air.toml`"
+    )]
+    InvalidUtf8 { line: usize },
+    #[error("The file isn't valid {encoding}")]
+    Invalid { encoding: &'static str },
+}
+
+#[derive(Error, Debug)]
+#[error(
+    "Can't represent `{character}` on line {line} in {encoding}, the encoding of the file. The file was left unchanged"
+)]
+pub struct EncodeError {
+    character: char,
+    line: usize,
+    encoding: &'static str,
+}
+
+/// Decode the `bytes` of a file to a string
+///
+/// A byte order mark takes precedence over the `encoding` that the file is declared
+/// with, and is removed from the decoded string.
+pub fn decode(bytes: Vec<u8>, encoding: Encoding) -> Result<(String, FileEncoding), DecodeError> {
+    let (encoding, bom_len) = match encoding_rs::Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => (encoding.as_encoding_rs(), 0),
+    };
+
+    let file_encoding = FileEncoding {
+        encoding,
+        bom: bom_len > 0,
+    };
+
+    if encoding == encoding_rs::UTF_8 {
+        let mut bytes = bytes;
+        bytes.drain(..bom_len);
+
+        return match String::from_utf8(bytes) {
+            Ok(source) => Ok((source, file_encoding)),
+            Err(err) => {
+                let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+                Err(DecodeError::InvalidUtf8 {
+                    line: line_number(valid),
+                })
+            }
+        };
+    }
+
+    match encoding.decode_without_bom_handling_and_without_replacement(&bytes[bom_len..]) {
+        Some(source) => Ok((source.into_owned(), file_encoding)),
+        None => Err(DecodeError::Invalid {
+            encoding: encoding.name(),
+        }),
+    }
+}
+
+impl FileEncoding {
+    pub const UTF_8: Self = Self {
+        encoding: encoding_rs::UTF_8,
+        bom: false,
+    };
+
+    /// Encode the formatted `source` like the original file
+    pub fn encode<'source>(&self, source: &'source str) -> Result<Cow<'source, [u8]>, EncodeError> {
+        let is_utf16_le = self.encoding == encoding_rs::UTF_16LE;
+        let is_utf16_be = self.encoding == encoding_rs::UTF_16BE;
+
+        let bom: &[u8] = match self.bom {
+            false => &[],
+            true if is_utf16_le => &[0xFF, 0xFE],
+            true if is_utf16_be => &[0xFE, 0xFF],
+            true => &[0xEF, 0xBB, 0xBF],
+        };
+
+        // `encoding_rs` only decodes UTF-16, so it is encoded by hand
+        if is_utf16_le || is_utf16_be {
+            let mut bytes = bom.to_vec();
+            for unit in source.encode_utf16() {
+                if is_utf16_be {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        let (encoded, _, has_unmappable) = self.encoding.encode(source);
+
+        if has_unmappable {
+            return Err(self.unmappable_error(source));
+        }
+
+        if bom.is_empty() {
+            return Ok(encoded);
+        }
+
+        let mut bytes = bom.to_vec();
+        bytes.extend_from_slice(&encoded);
+        Ok(Cow::Owned(bytes))
+    }
+
+    /// Locate the first character of `source` that can't be encoded
+    fn unmappable_error(&self, source: &str) -> EncodeError {
+        let mut buffer = [0; 4];
+
+        let (index, character) = source
+            .char_indices()
+            .find(|(_, character)| {
+                let (_, _, has_unmappable) =
+                    self.encoding.encode(character.encode_utf8(&mut buffer));
+                has_unmappable
+            })
+            .unwrap_or((0, char::REPLACEMENT_CHARACTER));
+
+        EncodeError {
+            character,
+            line: line_number(&source.as_bytes()[..index]),
+            encoding: self.encoding.name(),
+        }
+    }
+}
+
+/// The 1-based line number at the end of `prefix`
+fn line_number(prefix: &[u8]) -> usize {
+    prefix.iter().filter(|byte| **byte == b'\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::DecodeError;
+    use crate::encoding::FileEncoding;
+    use crate::encoding::decode;
+    use crate::settings::Encoding;
+
+    #[test]
+    fn test_latin1_round_trip() -> anyhow::Result<()> {
+        let latin1 = Encoding::for_label("latin1").unwrap();
+
+        // `"café"` in latin1
+        let bytes = b"x <- \"caf\xE9\"\n".to_vec();
+
+        let (source, encoding) = decode(bytes.clone(), latin1)?;
+        assert_eq!(source, "x <- \"café\"\n");
+        assert_eq!(encoding.encode(&source)?.as_ref(), bytes.as_slice());
+
+        // Characters that latin1 can't represent are an error
+        let error = encoding.encode("x <- 1\ny <- \"ŝ\"\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.character, 'ŝ');
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_order_marks() -> anyhow::Result<()> {
+        // UTF-8 BOM, which is kept
+        let bytes = b"\xEF\xBB\xBFx\n".to_vec();
+        let (source, encoding) = decode(bytes.clone(), Encoding::UTF_8)?;
+        assert_eq!(source, "x\n");
+        assert_eq!(encoding.encode(&source)?.as_ref(), bytes.as_slice());
+
+        // UTF-16LE BOM, which takes precedence over the declared encoding
+        let bytes = b"\xFF\xFEx\0\n\0".to_vec();
+        let (source, encoding) = decode(bytes.clone(), Encoding::for_label("latin1").unwrap())?;
+        assert_eq!(source, "x\n");
+        assert_eq!(encoding.encode(&source)?.as_ref(), bytes.as_slice());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let bytes = b"x <- 1\ny <- \"caf\xE9\"\n".to_vec();
+        assert!(matches!(
+            decode(bytes, Encoding::UTF_8),
+            Err(DecodeError::InvalidUtf8 { line: 2 })
+        ));
+
+        let (source, encoding) = decode(b"x\n".to_vec(), Encoding::UTF_8).unwrap();
+        assert_eq!(source, "x\n");
+        assert_eq!(encoding, FileEncoding::UTF_8);
+    }
+}
@@ -3,6 +3,12 @@ use air_r_parser::Parse;
 use air_r_parser::RParserOptions;
 use thiserror::Error;
 
+mod range;
+mod verify;
+
+pub use range::*;
+pub use verify::*;
+
 #[derive(Debug)]
 pub enum FormattedSource {
     /// The source was formatted, the [`String`] contains the transformed source code.
@@ -40,7 +46,9 @@ pub fn format_source(
     source: &str,
     options: RFormatOptions,
 ) -> std::result::Result<FormattedSource, FormatSourceError> {
-    let parse = air_r_parser::parse(source, RParserOptions::default());
+    let parser_options =
+        RParserOptions::default().with_minimum_r_version(options.minimum_r_version());
+    let parse = air_r_parser::parse(source, parser_options);
 
     if parse.has_error() {
         let error = parse.into_error().unwrap();
@@ -80,3 +88,70 @@ pub fn format_source_with_parse(
         Ok(FormattedSource::Changed(formatted))
     }
 }
+
+/// A location in a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The 1-based line number
+    pub line: usize,
+    /// The 1-based column number, in characters
+    pub column: usize,
+}
+
+/// Finds the strings of `source` with non-ASCII characters that `ascii-strings = true`
+/// would escape
+///
+/// Returns the location of the first non-ASCII character of each string. Returns
+/// an empty vector if `source` can't be parsed.
+pub fn find_non_ascii_strings(source: &str) -> Vec<SourceLocation> {
+    let parse = air_r_parser::parse(source, RParserOptions::default());
+
+    if parse.has_error() {
+        return Vec::new();
+    }
+
+    air_r_formatter::string_content::non_ascii_strings(&parse.syntax())
+        .into_iter()
+        .filter_map(|range| {
+            let start = usize::from(range.start());
+            let content = &source[start..usize::from(range.end())];
+            let offset = content.find(|c: char| !c.is_ascii())?;
+            Some(source_location(source, start + offset))
+        })
+        .collect()
+}
+
+fn source_location(source: &str, offset: usize) -> SourceLocation {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    SourceLocation {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::SourceLocation;
+    use crate::format::find_non_ascii_strings;
+
+    #[test]
+    fn test_find_non_ascii_strings() {
+        let source = "x <- \"café\"\n# é\ny <- r\"(é)\"\nz <- c(\"a\", \"naïve\")\n";
+
+        assert_eq!(
+            find_non_ascii_strings(source),
+            vec![
+                SourceLocation {
+                    line: 1,
+                    column: 10
+                },
+                SourceLocation {
+                    line: 4,
+                    column: 16
+                },
+            ]
+        );
+    }
+}
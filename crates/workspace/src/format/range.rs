@@ -0,0 +1,430 @@
+use std::str::FromStr;
+
+use air_r_formatter::context::RFormatOptions;
+use air_r_parser::RParserOptions;
+use air_r_syntax::RExpressionList;
+use air_r_syntax::RSyntaxKind;
+use air_r_syntax::RSyntaxNode;
+use air_r_syntax::WalkEvent;
+use biome_rowan::AstNode;
+use biome_rowan::Language;
+use biome_rowan::SyntaxElement;
+use biome_text_size::TextRange;
+use biome_text_size::TextSize;
+use thiserror::Error;
+
+use crate::format::FormatParseError;
+use crate::format::FormatSourceError;
+use crate::format::FormattedSource;
+
+/// A range of the source, along with its formatted replacement
+#[derive(Debug)]
+pub struct FormattedRange {
+    pub range: TextRange,
+    pub formatted: String,
+}
+
+/// A range of lines, 1-based and inclusive on both ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Error, Debug)]
+#[error(
+    "Expected a range of lines like `10:20`, with 1-based line numbers and a start that doesn't exceed the end"
+)]
+pub struct ParseLineRangeError;
+
+impl LineRange {
+    /// Returns `None` if `start` is `0` or exceeds `end`
+    pub fn new(start: usize, end: usize) -> Option<Self> {
+        if start == 0 || start > end {
+            return None;
+        }
+        Some(Self { start, end })
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The range of `source` covered by these lines, excluding the final line ending
+    ///
+    /// Lines past the end of `source` are ignored. Returns `None` if `source` doesn't
+    /// have a line `start`.
+    pub fn text_range(&self, source: &str) -> Option<TextRange> {
+        let mut line_starts =
+            std::iter::once(0).chain(source.match_indices('\n').map(|(index, _)| index + 1));
+
+        let start = line_starts.nth(self.start - 1)?;
+
+        let end = match line_starts.nth(self.end - self.start) {
+            Some(next_line_start) => next_line_start - 1,
+            None => source.len(),
+        };
+        let end = if source[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+
+        let start = TextSize::try_from(start).ok()?;
+        let end = TextSize::try_from(end).ok()?;
+
+        Some(TextRange::new(start, end.max(start)))
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = ParseLineRangeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value.split_once(':').ok_or(ParseLineRangeError)?;
+        let start = start.trim().parse().map_err(|_| ParseLineRangeError)?;
+        let end = end.trim().parse().map_err(|_| ParseLineRangeError)?;
+        Self::new(start, end).ok_or(ParseLineRangeError)
+    }
+}
+
+/// Formats the given `lines` of `source` code
+///
+/// Each range of lines is widened to the logical lines that it overlaps, see
+/// [format_range()]. The rest of `source` is left untouched.
+pub fn format_source_lines(
+    source: &str,
+    lines: &[LineRange],
+    options: RFormatOptions,
+) -> std::result::Result<FormattedSource, FormatSourceError> {
+    let parser_options =
+        RParserOptions::default().with_minimum_r_version(options.minimum_r_version());
+    let parse = air_r_parser::parse(source, parser_options);
+
+    if parse.has_error() {
+        let error = parse.into_error().unwrap();
+        return Err(error.into());
+    }
+
+    let root = parse.syntax();
+
+    let mut ranges: Vec<TextRange> = lines
+        .iter()
+        .filter_map(|lines| lines.text_range(source))
+        .collect();
+    ranges.sort_by_key(|range| range.start());
+
+    // Merge the ranges that widen to overlapping logical lines, so that each formatted
+    // range replaces a distinct part of `source`. Pairs of `(requested, widened)`.
+    let mut merged: Vec<(TextRange, TextRange)> = Vec::new();
+
+    for range in ranges {
+        let Some(widened) = logical_lines_range(&root, range) else {
+            continue;
+        };
+        merged.push((range, widened));
+
+        // Merging can widen a range backwards, so keep going until the last range no
+        // longer overlaps the previous one
+        while let [.., previous, last] = merged.as_slice()
+            && overlaps(previous.1, last.1)
+        {
+            let requested = previous.0.cover(last.0);
+            let widened =
+                logical_lines_range(&root, requested).unwrap_or_else(|| previous.1.cover(last.1));
+            merged.pop();
+            merged.pop();
+            merged.push((requested, widened));
+        }
+    }
+
+    let mut formatted = source.to_string();
+
+    // Replace back to front, so that earlier ranges remain valid
+    for (requested, _) in merged.into_iter().rev() {
+        let Some(FormattedRange {
+            range,
+            formatted: text,
+        }) = format_range(&root, requested, options.clone()).map_err(|err| match err {
+            FormatParseError::Format(err) => FormatSourceError::Format(err),
+            FormatParseError::Print(err) => FormatSourceError::Print(err),
+        })?
+        else {
+            continue;
+        };
+
+        formatted.replace_range(usize::from(range.start())..usize::from(range.end()), &text);
+    }
+
+    if source == formatted {
+        Ok(FormattedSource::Unchanged)
+    } else {
+        Ok(FormattedSource::Changed(formatted))
+    }
+}
+
+/// Formats the logical lines of `root` that overlap `range`
+///
+/// Logical lines are currently expressions at top level or in a braced list. The
+/// `range` is widened to the deepest list of logical lines that encloses it.
+///
+/// Returns the range of the source that was formatted, along with its replacement, or
+/// `None` if there is nothing to format.
+pub fn format_range(
+    root: &RSyntaxNode,
+    range: TextRange,
+    options: RFormatOptions,
+) -> std::result::Result<Option<FormattedRange>, FormatParseError> {
+    let logical_lines = find_deepest_enclosing_logical_lines(root.clone(), range);
+    if logical_lines.is_empty() {
+        tracing::warn!("Can't find logical line");
+        return Ok(None);
+    };
+
+    // Find the overall formatting range by concatenating the ranges of the logical lines.
+    // We use the "non-whitespace-range" as that corresponds to what Biome will format.
+    let format_range = logical_lines
+        .iter()
+        .map(text_non_whitespace_range)
+        .reduce(|acc, new| acc.cover(new))
+        .expect("`logical_lines` is non-empty");
+
+    // We need to wrap in an `RRoot` otherwise the comments get attached too
+    // deep in the tree. See `CommentsBuilderVisitor` in biome_formatter and the
+    // `is_root` logic. Note that `node` needs to be wrapped in at least two
+    // other nodes in order to fix this problem, and here we have an `RRoot` and
+    // `RExpressionList` that do the job.
+    //
+    // Since we only format logical lines, it is fine to wrap in an expression list.
+    let Some(exprs): Option<Vec<air_r_syntax::AnyRExpression>> = logical_lines
+        .into_iter()
+        .map(air_r_syntax::AnyRExpression::cast)
+        .collect()
+    else {
+        tracing::warn!("Can't cast to `AnyRExpression`");
+        return Ok(None);
+    };
+
+    let list = air_r_factory::r_expression_list(exprs);
+    let eof = air_r_syntax::RSyntaxToken::new_detached(RSyntaxKind::EOF, "", vec![], vec![]);
+    let root = air_r_factory::r_root(list, eof).build();
+
+    let format_info = biome_formatter::format_sub_tree(
+        root.syntax(),
+        air_r_formatter::RFormatLanguage::new(options),
+    )?;
+
+    if format_info.range().is_none() {
+        // Happens in edge cases when biome returns a `Printed::new_empty()`
+        return Ok(None);
+    };
+
+    let mut formatted = format_info.into_code();
+
+    // Remove last hard break line from our artifical expression list
+    if formatted.pop() == Some('\n') && formatted.ends_with('\r') {
+        formatted.pop();
+    }
+
+    Ok(Some(FormattedRange {
+        range: format_range,
+        formatted,
+    }))
+}
+
+/// The range of the logical lines of `root` that overlap `range`, without formatting
+fn logical_lines_range(root: &RSyntaxNode, range: TextRange) -> Option<TextRange> {
+    find_deepest_enclosing_logical_lines(root.clone(), range)
+        .iter()
+        .map(text_non_whitespace_range)
+        .reduce(|acc, new| acc.cover(new))
+}
+
+/// Do `x` and `y` share at least one offset? Ranges that merely touch don't overlap.
+fn overlaps(x: TextRange, y: TextRange) -> bool {
+    x.start() < y.end() && y.start() < x.end()
+}
+
+// From biome_formatter
+fn text_non_whitespace_range<E, L>(elem: &E) -> TextRange
+where
+    E: Into<SyntaxElement<L>> + Clone,
+    L: Language,
+{
+    let elem: SyntaxElement<L> = elem.clone().into();
+
+    let start = elem
+        .leading_trivia()
+        .into_iter()
+        .flat_map(|trivia| trivia.pieces())
+        .find_map(|piece| {
+            if piece.is_whitespace() || piece.is_newline() {
+                None
+            } else {
+                Some(piece.text_range().start())
+            }
+        })
+        .unwrap_or_else(|| elem.text_trimmed_range().start());
+
+    let end = elem
+        .trailing_trivia()
+        .into_iter()
+        .flat_map(|trivia| trivia.pieces().rev())
+        .find_map(|piece| {
+            if piece.is_whitespace() || piece.is_newline() {
+                None
+            } else {
+                Some(piece.text_range().end())
+            }
+        })
+        .unwrap_or_else(|| elem.text_trimmed_range().end());
+
+    TextRange::new(start, end)
+}
+
+/// Finds consecutive logical lines. Currently that's only expressions at
+/// top-level or in a braced list.
+fn find_deepest_enclosing_logical_lines(node: RSyntaxNode, range: TextRange) -> Vec<RSyntaxNode> {
+    let start_lists = find_expression_lists(&node, range.start(), false);
+    let end_lists = find_expression_lists(&node, range.end(), true);
+
+    // Both vectors of lists should have a common prefix, starting from the
+    // program's expression list. As soon as the lists diverge we stop.
+    let Some(list) = start_lists
+        .into_iter()
+        .zip(end_lists)
+        .take_while(|pair| pair.0 == pair.1)
+        .map(|pair| pair.0)
+        .last()
+    else {
+        // Should not happen as the range is always included in the program's expression list
+        tracing::warn!("Can't find common list parent");
+        return vec![];
+    };
+
+    let Some(list) = RExpressionList::cast(list) else {
+        tracing::warn!("Can't cast to expression list");
+        return vec![];
+    };
+
+    let iter = list.into_iter();
+
+    // We've chosen to be liberal about user selections and always widen the
+    // range to include the selection bounds. If we wanted to be conservative
+    // instead, we could use this `filter()` instead of the `skip_while()` and
+    // `take_while()`:
+    //
+    // ```rust
+    // .filter(|node| range.contains_range(node.text_trimmed_range()))
+    // ```
+    let logical_lines: Vec<RSyntaxNode> = iter
+        .map(|expr| expr.into_syntax())
+        .skip_while(|node| !node.text_range_with_trivia().contains(range.start()))
+        .take_while(|node| node.text_trimmed_range().start() <= range.end())
+        .collect();
+
+    logical_lines
+}
+
+fn find_expression_lists(node: &RSyntaxNode, offset: TextSize, end: bool) -> Vec<RSyntaxNode> {
+    let mut preorder = node.preorder();
+    let mut nodes: Vec<RSyntaxNode> = vec![];
+
+    while let Some(event) = preorder.next() {
+        match event {
+            WalkEvent::Enter(node) => {
+                let Some(parent) = node.parent() else {
+                    continue;
+                };
+
+                let is_contained = if end {
+                    let trimmed_node_range = node.text_trimmed_range();
+                    trimmed_node_range.contains_inclusive(offset)
+                } else {
+                    let node_range = node.text_range_with_trivia();
+                    node_range.contains(offset)
+                };
+
+                if !is_contained {
+                    preorder.skip_subtree();
+                    continue;
+                }
+
+                if parent.kind() == RSyntaxKind::R_EXPRESSION_LIST {
+                    nodes.push(parent.clone());
+                    continue;
+                }
+            }
+
+            WalkEvent::Leave(_) => {}
+        }
+    }
+
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use air_r_formatter::context::RFormatOptions;
+
+    use crate::format::FormattedSource;
+    use crate::format::LineRange;
+    use crate::format::format_source_lines;
+
+    fn format_lines(source: &str, lines: &[&str]) -> String {
+        let lines: Vec<LineRange> = lines.iter().map(|lines| lines.parse().unwrap()).collect();
+        match format_source_lines(source, &lines, RFormatOptions::default()).unwrap() {
+            FormattedSource::Changed(formatted) => formatted,
+            FormattedSource::Unchanged => source.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        assert_eq!("2:5".parse::<LineRange>().ok(), LineRange::new(2, 5));
+        assert_eq!("3:3".parse::<LineRange>().ok(), LineRange::new(3, 3));
+        assert!("0:3".parse::<LineRange>().is_err());
+        assert!("5:2".parse::<LineRange>().is_err());
+        assert!("5".parse::<LineRange>().is_err());
+        assert!("a:b".parse::<LineRange>().is_err());
+    }
+
+    #[test]
+    fn test_line_range_text_range() {
+        let source = "a\r\nbb\nccc";
+        let text = |start, end| {
+            let range = LineRange::new(start, end).unwrap().text_range(source)?;
+            Some(&source[usize::from(range.start())..usize::from(range.end())])
+        };
+
+        assert_eq!(text(1, 1), Some("a"));
+        assert_eq!(text(2, 3), Some("bb\nccc"));
+        assert_eq!(text(3, 10), Some("ccc"));
+        assert_eq!(text(4, 4), None);
+    }
+
+    #[test]
+    fn test_format_source_lines() {
+        let source = "1+1\n2+2\n3+3\n4+4\n";
+        assert_eq!(format_lines(source, &["2:2"]), "1+1\n2 + 2\n3+3\n4+4\n");
+        assert_eq!(
+            format_lines(source, &["4:4", "1:1"]),
+            "1 + 1\n2+2\n3+3\n4 + 4\n"
+        );
+
+        // Lines are widened to the logical lines they overlap
+        let source = "f(a,\nb)\nx<-1\n";
+        assert_eq!(format_lines(source, &["2:2"]), "f(a, b)\nx<-1\n");
+
+        // Overlapping logical lines are only formatted once
+        let source = "g <- function() {\n1+1\n2+2\n}\ny<-2\n";
+        assert_eq!(
+            format_lines(source, &["2:2", "1:3"]),
+            "g <- function() {\n  1 + 1\n  2 + 2\n}\ny<-2\n"
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use air_r_formatter::context::RFormatOptions;
+use air_r_parser::RParserOptions;
+use air_r_syntax::RElseClause;
+use air_r_syntax::RForStatement;
+use air_r_syntax::RFunctionDefinition;
+use air_r_syntax::RIfStatement;
+use air_r_syntax::RRepeatStatement;
+use air_r_syntax::RSyntaxKind;
+use air_r_syntax::RSyntaxNode;
+use air_r_syntax::RSyntaxToken;
+use air_r_syntax::RWhileStatement;
+use biome_rowan::AstNode;
+use biome_rowan::NodeOrToken;
+use biome_text_size::TextSize;
+use thiserror::Error;
+
+use crate::format::FormatSourceError;
+use crate::format::FormattedSource;
+use crate::format::SourceLocation;
+use crate::format::format_source;
+use crate::format::source_location;
+
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error("The formatted code failed to parse: {0}")]
+    Parse(air_r_parser::ParseError),
+    #[error(
+        "The formatted code doesn't have the same syntax tree as the original code, starting at {line}:{column}",
+        line = .0.line,
+        column = .0.column
+    )]
+    Mismatch(SourceLocation),
+    #[error("Failed to format the formatted code again: {0}")]
+    Reformat(FormatSourceError),
+    #[error("Formatting the formatted code again changed it")]
+    NotIdempotent,
+}
+
+/// Verifies that `formatted` is a safe replacement of `source`
+///
+/// Both are parsed and their syntax trees are compared, ignoring trivia and the
+/// rewrites that the formatter makes on purpose, i.e.:
+///
+/// - Braces added around the body of `if` statements, loops, and functions. Braces
+///   elsewhere, like the `{{ x }}` of rlang, must be kept.
+/// - `=` and `<-` assignments, as rewritten by `assignment-style`.
+/// - Backticked identifiers and string argument names, as rewritten by
+///   `normalize-names`.
+/// - The escapes and quotes of strings, as rewritten by `ascii-strings` and
+///   `prefer-raw-strings`, by comparing the values of strings rather than their text.
+///
+/// When `idempotent` is set, formatting `formatted` again must also be a no-op. This
+/// only holds when the whole file was formatted, rather than a range of lines.
+pub fn verify_formatted(
+    source: &str,
+    formatted: &str,
+    options: RFormatOptions,
+    idempotent: bool,
+) -> Result<(), VerifyError> {
+    let parser_options =
+        RParserOptions::default().with_minimum_r_version(options.minimum_r_version());
+
+    let source_parse = air_r_parser::parse(source, parser_options);
+    let formatted_parse = air_r_parser::parse(formatted, parser_options);
+
+    if formatted_parse.has_error() {
+        let error = formatted_parse.into_error().unwrap();
+        return Err(VerifyError::Parse(error));
+    }
+
+    let mut source_items = Vec::new();
+    normalize_node(&source_parse.syntax(), &mut source_items);
+
+    let mut formatted_items = Vec::new();
+    normalize_node(&formatted_parse.syntax(), &mut formatted_items);
+
+    if let Some(offset) = first_mismatch(&source_items, &formatted_items) {
+        return Err(VerifyError::Mismatch(source_location(
+            source,
+            usize::from(offset),
+        )));
+    }
+
+    if idempotent {
+        match format_source(formatted, options) {
+            Ok(FormattedSource::Unchanged) => {}
+            Ok(FormattedSource::Changed(_)) => return Err(VerifyError::NotIdempotent),
+            Err(error) => return Err(VerifyError::Reformat(error)),
+        }
+    }
+
+    Ok(())
+}
+
+/// An element of a syntax tree, normalized for comparison
+#[derive(Debug, PartialEq, Eq)]
+enum Item {
+    Enter(RSyntaxKind),
+    Exit,
+    Token(RSyntaxKind, String),
+    /// The name of an argument, whether written as an identifier or a string
+    Name(String),
+}
+
+/// An [Item] along with its offset in the source, for reporting mismatches
+#[derive(Debug)]
+struct LocatedItem {
+    item: Item,
+    offset: TextSize,
+}
+
+/// Returns the offset in the source of the first item that differs, if any
+fn first_mismatch(source: &[LocatedItem], formatted: &[LocatedItem]) -> Option<TextSize> {
+    let index = source
+        .iter()
+        .zip(formatted)
+        .position(|(source, formatted)| source.item != formatted.item);
+
+    match index {
+        Some(index) => Some(source[index].offset),
+        None if source.len() == formatted.len() => None,
+        None => Some(
+            source
+                .get(formatted.len())
+                .or(source.last())
+                .map_or(TextSize::from(0), |item| item.offset),
+        ),
+    }
+}
+
+fn normalize_node(node: &RSyntaxNode, items: &mut Vec<LocatedItem>) {
+    let offset = node.text_trimmed_range().start();
+
+    match node.kind() {
+        // Autobracing wraps the bodies of `if` statements, loops, and functions in
+        // braces, so braces around a single expression are dropped on both sides in
+        // these positions. Elsewhere, like in `{{ x }}`, braces are meaningful.
+        RSyntaxKind::R_BRACED_EXPRESSIONS if is_body(node) => {
+            let expressions: Vec<RSyntaxNode> = node
+                .children()
+                .filter(|child| child.kind() == RSyntaxKind::R_EXPRESSION_LIST)
+                .flat_map(|list| list.children())
+                .collect();
+
+            if let [expression] = expressions.as_slice() {
+                normalize_node(expression, items);
+                return;
+            }
+        }
+
+        RSyntaxKind::R_ARGUMENT_NAME_CLAUSE => {
+            items.push(LocatedItem {
+                item: Item::Enter(node.kind()),
+                offset,
+            });
+
+            for element in node.children_with_tokens() {
+                match element {
+                    NodeOrToken::Node(name) => items.push(LocatedItem {
+                        item: Item::Name(name_value(&name)),
+                        offset: name.text_trimmed_range().start(),
+                    }),
+                    NodeOrToken::Token(token) => normalize_token(&token, items),
+                }
+            }
+
+            items.push(LocatedItem {
+                item: Item::Exit,
+                offset,
+            });
+            return;
+        }
+
+        RSyntaxKind::R_STRING_VALUE => {
+            items.push(LocatedItem {
+                item: Item::Token(node.kind(), string_value(node)),
+                offset,
+            });
+            return;
+        }
+
+        RSyntaxKind::R_IDENTIFIER => {
+            items.push(LocatedItem {
+                item: Item::Token(node.kind(), name_value(node)),
+                offset,
+            });
+            return;
+        }
+
+        _ => {}
+    }
+
+    items.push(LocatedItem {
+        item: Item::Enter(node.kind()),
+        offset,
+    });
+
+    for element in node.children_with_tokens() {
+        match element {
+            NodeOrToken::Node(child) => normalize_node(&child, items),
+            NodeOrToken::Token(token) => normalize_token(&token, items),
+        }
+    }
+
+    items.push(LocatedItem {
+        item: Item::Exit,
+        offset,
+    });
+}
+
+/// Is `node` the body of an `if` statement, a loop, or a function?
+fn is_body(node: &RSyntaxNode) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    let body = match parent.kind() {
+        RSyntaxKind::R_IF_STATEMENT => {
+            RIfStatement::cast(parent).and_then(|node| node.consequence().ok())
+        }
+        RSyntaxKind::R_ELSE_CLAUSE => {
+            RElseClause::cast(parent).and_then(|node| node.alternative().ok())
+        }
+        RSyntaxKind::R_FOR_STATEMENT => {
+            RForStatement::cast(parent).and_then(|node| node.body().ok())
+        }
+        RSyntaxKind::R_WHILE_STATEMENT => {
+            RWhileStatement::cast(parent).and_then(|node| node.body().ok())
+        }
+        RSyntaxKind::R_REPEAT_STATEMENT => {
+            RRepeatStatement::cast(parent).and_then(|node| node.body().ok())
+        }
+        RSyntaxKind::R_FUNCTION_DEFINITION => {
+            RFunctionDefinition::cast(parent).and_then(|node| node.body().ok())
+        }
+        _ => None,
+    };
+
+    body.is_some_and(|body| body.syntax() == node)
+}
+
+fn normalize_token(token: &RSyntaxToken, items: &mut Vec<LocatedItem>) {
+    let kind = match token.kind() {
+        // Semicolons only separate expressions, which the tree already captures
+        RSyntaxKind::SEMICOLON => return,
+        // `assignment-style` swaps `=` and `<-` assignments. In other places, like
+        // argument names, the kind of the parent node still tells them apart.
+        RSyntaxKind::EQUAL => RSyntaxKind::ASSIGN,
+        kind => kind,
+    };
+
+    let text = match kind {
+        RSyntaxKind::ASSIGN => String::from("<-"),
+        _ => token.text_trimmed().to_string(),
+    };
+
+    items.push(LocatedItem {
+        item: Item::Token(kind, text),
+        offset: token.text_trimmed_range().start(),
+    });
+}
+
+/// The name of an identifier or string, without backticks or quotes
+fn name_value(node: &RSyntaxNode) -> String {
+    if node.kind() == RSyntaxKind::R_STRING_VALUE {
+        return string_value(node);
+    }
+
+    let text = node.text_trimmed().to_string();
+
+    match text
+        .strip_prefix('`')
+        .and_then(|text| text.strip_suffix('`'))
+    {
+        Some(name) => name.to_string(),
+        None => text,
+    }
+}
+
+/// The value of a string, with escapes resolved and line endings normalized
+///
+/// Only the escapes that the formatter may introduce or remove are resolved, all
+/// other escapes are left as is.
+fn string_value(node: &RSyntaxNode) -> String {
+    let mut tokens = node
+        .children_with_tokens()
+        .filter_map(NodeOrToken::into_token);
+
+    let is_raw = tokens
+        .next()
+        .is_some_and(|open| open.text_trimmed().starts_with(['r', 'R']));
+
+    let content = tokens
+        .find(|token| token.kind() == RSyntaxKind::STRING_CONTENT)
+        .map(|token| token.text_trimmed().replace("\r\n", "\n"))
+        .unwrap_or_default();
+
+    if is_raw { content } else { unescape(&content) }
+}
+
+fn unescape(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        let Some(escape) = chars.next() else {
+            out.push('\\');
+            break;
+        };
+
+        let max_digits = match escape {
+            '\\' | '"' | '\'' | '`' => {
+                out.push(escape);
+                continue;
+            }
+            'u' => 4,
+            'U' => 8,
+            _ => {
+                out.push('\\');
+                out.push(escape);
+                continue;
+            }
+        };
+
+        let braced = chars.next_if_eq(&'{').is_some();
+
+        let mut digits = String::new();
+        while digits.len() < max_digits
+            && let Some(digit) = chars.next_if(char::is_ascii_hexdigit)
+        {
+            digits.push(digit);
+        }
+
+        if braced {
+            chars.next_if_eq(&'}');
+        }
+
+        match u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(c) => out.push(c),
+            None => {
+                out.push('\\');
+                out.push(escape);
+                out.push_str(&digits);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use air_r_formatter::context::RFormatOptions;
+
+    use crate::format::SourceLocation;
+    use crate::format::VerifyError;
+    use crate::format::verify_formatted;
+
+    #[test]
+    fn test_verify_formatted_allows_intentional_rewrites() {
+        let options = RFormatOptions::default();
+
+        let source = "x = 1\nif (x) y\n`z`\nlist(\"a\" = 1)\n\"caf\\u00e9\"\n";
+        let formatted = "x <- 1\nif (x) {\n  y\n}\nz\nlist(a = 1)\n\"café\"\n";
+
+        assert!(verify_formatted(source, formatted, options, false).is_ok());
+    }
+
+    #[test]
+    fn test_verify_formatted_rejects_semantic_changes() {
+        let options = RFormatOptions::default();
+
+        assert!(matches!(
+            verify_formatted(
+                "x <- 1\ny <- 2\n",
+                "x <- 1\ny <- 3\n",
+                options.clone(),
+                false
+            ),
+            Err(VerifyError::Mismatch(SourceLocation { line: 2, column: 6 }))
+        ));
+        assert!(matches!(
+            verify_formatted("f(a, b)\n", "f(a)\n", options.clone(), false),
+            Err(VerifyError::Mismatch(_))
+        ));
+        assert!(matches!(
+            verify_formatted("\"a\"\n", "\"b\"\n", options.clone(), false),
+            Err(VerifyError::Mismatch(_))
+        ));
+        assert!(matches!(
+            verify_formatted("1 + 1\n", "1 +\n", options.clone(), false),
+            Err(VerifyError::Parse(_))
+        ));
+        assert!(matches!(
+            verify_formatted("f({{ x }})\n", "f(x)\n", options.clone(), false),
+            Err(VerifyError::Mismatch(_))
+        ));
+        assert!(matches!(
+            verify_formatted("quote({x})\n", "quote(x)\n", options.clone(), false),
+            Err(VerifyError::Mismatch(_))
+        ));
+        assert!(matches!(
+            verify_formatted("1+1\n", "1+1\n", options, true),
+            Err(VerifyError::NotIdempotent)
+        ));
+    }
+}
@@ -5,9 +5,13 @@
 //
 //
 
+pub mod description;
 pub mod discovery;
+pub mod editorconfig;
+pub mod encoding;
 pub mod file_patterns;
 pub mod format;
+pub mod rbuildignore;
 pub mod resolve;
 pub mod settings;
 pub mod toml;
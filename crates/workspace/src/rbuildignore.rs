@@ -0,0 +1,148 @@
+//
+// rbuildignore.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+//! Matcher for the patterns of an R package `.Rbuildignore` file.
+//!
+//! Unlike `.gitignore` files, each line of an `.Rbuildignore` is a Perl regular
+//! expression. `R CMD build` matches these case insensitively against the paths of
+//! files and directories relative to the package root, like `data-raw` or
+//! `R/sysdata.R`, and drops directories along with all of their contents.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use regex::Regex;
+use regex::RegexBuilder;
+
+#[derive(Debug, Clone)]
+pub struct RBuildIgnore {
+    /// The package root, which patterns are matched relative to
+    root: PathBuf,
+    patterns: Vec<Regex>,
+}
+
+impl RBuildIgnore {
+    /// Read and parse the `.Rbuildignore` file of the package at `root`
+    ///
+    /// Returns `None` if the file doesn't exist or can't be read.
+    pub fn read(root: &Path) -> anyhow::Result<Option<Self>> {
+        let path = root.join(".Rbuildignore");
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        tracing::debug!("Using patterns from '{path}'", path = path.display());
+
+        let rbuildignore = Self::parse(root, &contents)
+            .with_context(|| format!("Failed to parse '{path}'", path = path.display()))?;
+
+        Ok(Some(rbuildignore))
+    }
+
+    /// Parse the contents of an `.Rbuildignore` file
+    ///
+    /// Patterns that aren't supported by the `regex` crate, like look-arounds and
+    /// backreferences, are errors. Skipping them would format files that `R CMD build`
+    /// leaves out.
+    pub fn parse(root: &Path, contents: &str) -> anyhow::Result<Self> {
+        let patterns = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                RegexBuilder::new(line)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Unsupported pattern '{line}'"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            patterns,
+        })
+    }
+
+    /// Returns the pattern that matches this `path`, or `None` if no pattern matches
+    ///
+    /// Does not search parents, so a path of `data-raw/data.R` would not match a pattern
+    /// of `^data-raw$`. Paths outside of the package root are never matched.
+    pub fn matched(&self, path: &Path) -> Option<&Regex> {
+        let path = path.strip_prefix(&self.root).ok()?;
+
+        // Patterns are written with `/` separators, whatever the platform
+        let path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if path.is_empty() {
+            return None;
+        }
+
+        self.patterns.iter().find(|pattern| pattern.is_match(&path))
+    }
+
+    /// Returns the pattern that matches this `path` or any of its parents within the
+    /// package root, or `None` if no pattern matches
+    pub fn matched_path_or_any_parents(&self, path: &Path) -> Option<&Regex> {
+        path.ancestors()
+            .take_while(|path| path.starts_with(&self.root))
+            .find_map(|path| self.matched(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::rbuildignore::RBuildIgnore;
+
+    #[test]
+    fn test_matches_relative_paths() -> anyhow::Result<()> {
+        let root = Path::new("/pkg");
+        let contents = r"
+^.*\.Rproj$
+^data-raw$
+
+^README\.Rmd$
+";
+        let rbuildignore = RBuildIgnore::parse(root, contents)?;
+        assert_eq!(rbuildignore.patterns.len(), 3);
+
+        let matched = |path: &str| rbuildignore.matched(&root.join(path)).is_some();
+
+        assert!(matched("pkg.Rproj"));
+        assert!(matched("data-raw"));
+        assert!(matched("readme.rmd"));
+        assert!(!matched("R/data-raw"));
+        assert!(!matched("data-raw/data.R"));
+        assert!(!matched("R/code.R"));
+
+        assert!(
+            rbuildignore
+                .matched_path_or_any_parents(&root.join("data-raw").join("data.R"))
+                .is_some()
+        );
+
+        // Paths outside of the root are never matched
+        assert!(rbuildignore.matched(Path::new("/data-raw")).is_none());
+        assert!(rbuildignore.matched(root).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_patterns_are_errors() {
+        let root = Path::new("/pkg");
+
+        // Look-behinds aren't supported by the `regex` crate
+        let err = RBuildIgnore::parse(root, "^data-raw$\n(?<=foo)bar\n").unwrap_err();
+        assert!(err.to_string().contains("(?<=foo)bar"));
+    }
+}
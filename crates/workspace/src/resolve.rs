@@ -114,6 +114,12 @@ impl<T> PathResolver<T> {
         &self.items
     }
 
+    /// Mutable access to the items, which can't change their paths and therefore
+    /// keeps them sorted
+    pub fn items_mut(&mut self) -> &mut [PathItem<T>] {
+        &mut self.items
+    }
+
     pub fn len(&self) -> usize {
         self.items.len()
     }
@@ -1,23 +1,43 @@
 mod default_exclude_patterns;
 mod default_include_patterns;
 mod default_table;
+mod encoding;
 mod exclude_patterns;
+mod format_override;
+mod include_patterns;
 mod line_ending;
 
 pub use default_exclude_patterns::DefaultExcludePatterns;
 pub use default_include_patterns::DefaultIncludePatterns;
 pub use default_table::*;
+pub use encoding::Encoding;
 pub use exclude_patterns::ExcludePatterns;
+pub use format_override::FormatOverride;
+pub use include_patterns::IncludePatterns;
 pub(crate) use line_ending::LineEnding;
 
+use std::path::Path;
+use std::path::PathBuf;
+
 use air_r_formatter::context::RFormatOptions;
+use settings::AsciiStrings;
 use settings::AssignmentStyle;
 use settings::IndentStyle;
 use settings::IndentWidth;
 use settings::LineWidth;
+use settings::NormalizeNames;
 use settings::PersistentLineBreaks;
+use settings::PreferRawStrings;
+use settings::RVersion;
+use settings::SectionComments;
 use settings::Skip;
 use settings::Table;
+use settings::TableAlignments;
+use settings::VectorLayout;
+use settings::WrapComments;
+
+use crate::editorconfig::EditorConfigProperties;
+use crate::rbuildignore::RBuildIgnore;
 
 /// Resolved configuration settings used within air
 ///
@@ -27,6 +47,17 @@ use settings::Table;
 pub struct Settings {
     /// Settings to configure code formatting.
     pub format: FormatSettings,
+
+    /// Configuration files inherited through `extend`, from closest to furthest.
+    ///
+    /// These settings must be reloaded when any of these files change.
+    pub extended: Vec<PathBuf>,
+
+    /// Whether these settings come from an `air.toml` or `Config/air/*` fields, rather
+    /// than being the defaults of a package directory without any configuration.
+    ///
+    /// Editors may override settings that don't come from a configuration.
+    pub from_config: bool,
 }
 
 #[derive(Debug)]
@@ -35,13 +66,47 @@ pub struct FormatSettings {
     pub indent_width: IndentWidth,
     pub line_ending: LineEnding,
     pub line_width: LineWidth,
+    /// The encoding of files without a byte order mark, from the `encoding` option or
+    /// the `Encoding` field of the `DESCRIPTION`
+    pub encoding: Encoding,
     pub persistent_line_breaks: PersistentLineBreaks,
     pub assignment_style: AssignmentStyle,
     pub exclude: Option<ExcludePatterns>,
     pub default_exclude: Option<DefaultExcludePatterns>,
+    /// From both `include` and `extend-include`
+    pub include: Option<IncludePatterns>,
+    /// `None` when `include` replaces the default include patterns
     pub default_include: Option<DefaultIncludePatterns>,
+    pub respect_gitignore: bool,
+    /// `None` when `respect-rbuildignore` is disabled or there is no `.Rbuildignore`
+    pub rbuildignore: Option<RBuildIgnore>,
     pub skip: Option<Skip>,
     pub table: Option<Table>,
+    pub table_alignment: Option<TableAlignments>,
+    pub vector_layout: VectorLayout,
+    pub section_comments: SectionComments,
+    pub wrap_comments: WrapComments,
+    pub normalize_names: NormalizeNames,
+    pub ascii_strings: AsciiStrings,
+    pub prefer_raw_strings: Option<PreferRawStrings>,
+    /// Resolved from the global `minimum-r-version` option, as formatting is currently
+    /// the only command that parses R code
+    pub minimum_r_version: Option<RVersion>,
+    /// Options explicitly set in the `air.toml`, which take precedence over the
+    /// `.editorconfig`
+    pub explicit: ExplicitFormatOptions,
+    /// Options that apply to a subset of files, from `[[format.overrides]]`, in order
+    pub overrides: Vec<FormatOverride>,
+}
+
+/// Which of the options supported by `.editorconfig` files were explicitly set in the
+/// `air.toml`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExplicitFormatOptions {
+    pub indent_style: bool,
+    pub indent_width: bool,
+    pub line_ending: bool,
+    pub line_width: bool,
 }
 
 impl Default for FormatSettings {
@@ -55,13 +120,27 @@ impl Default for FormatSettings {
             indent_width: Default::default(),
             line_ending: Default::default(),
             line_width: Default::default(),
+            encoding: Default::default(),
             persistent_line_breaks: Default::default(),
             assignment_style: Default::default(),
             exclude: Default::default(),
             default_exclude: Some(Default::default()),
+            include: Default::default(),
             default_include: Some(Default::default()),
+            respect_gitignore: true,
+            rbuildignore: None,
             skip: Default::default(),
             table: Some(DEFAULT_TABLE.clone()),
+            table_alignment: Default::default(),
+            vector_layout: Default::default(),
+            section_comments: Default::default(),
+            wrap_comments: Default::default(),
+            normalize_names: Default::default(),
+            ascii_strings: Default::default(),
+            prefer_raw_strings: Default::default(),
+            minimum_r_version: Default::default(),
+            explicit: Default::default(),
+            overrides: Default::default(),
         }
     }
 }
@@ -79,5 +158,72 @@ impl FormatSettings {
             // Note that `clone()` on these options is ultimately on an `Arc`
             .with_skip(self.skip.clone())
             .with_table(self.table.clone())
+            .with_table_alignment(self.table_alignment.clone())
+            .with_vector_layout(self.vector_layout)
+            .with_section_comments(self.section_comments)
+            .with_wrap_comments(self.wrap_comments)
+            .with_normalize_names(self.normalize_names)
+            .with_ascii_strings(self.ascii_strings)
+            .with_prefer_raw_strings(self.prefer_raw_strings)
+            .with_minimum_r_version(self.minimum_r_version)
+    }
+
+    /// Finalize `RFormatOptions` in preparation for a formatting operation on `source`,
+    /// located at `path`
+    ///
+    /// Options that aren't explicitly set in the `air.toml` are taken from the
+    /// `.editorconfig` files that apply to `path`, if any. The overrides that match
+    /// `path` are then applied in order.
+    pub fn to_format_options_for_path(&self, source: &str, path: &Path) -> RFormatOptions {
+        let properties = EditorConfigProperties::resolve(path);
+        self.to_format_options_for_path_with(source, path, &properties)
+    }
+
+    /// Like [FormatSettings::to_format_options_for_path()], with the `.editorconfig`
+    /// `properties` that apply to `path` resolved by the caller, like from an
+    /// [EditorConfigCache](crate::editorconfig::EditorConfigCache)
+    pub fn to_format_options_for_path_with(
+        &self,
+        source: &str,
+        path: &Path,
+        properties: &EditorConfigProperties,
+    ) -> RFormatOptions {
+        let mut options = self.to_format_options(source);
+        self.merge_editorconfig(&mut options, properties);
+
+        for format_override in self.overrides.iter().filter(|o| o.matches(path)) {
+            format_override.apply(&mut options, source);
+        }
+
+        options
+    }
+
+    /// Merge the `.editorconfig` `properties` into `options`, for the options that
+    /// aren't explicitly set in the `air.toml`
+    pub fn merge_editorconfig(
+        &self,
+        options: &mut RFormatOptions,
+        properties: &EditorConfigProperties,
+    ) {
+        if let Some(indent_style) = properties.indent_style
+            && !self.explicit.indent_style
+        {
+            options.set_indent_style(indent_style);
+        }
+        if let Some(indent_width) = properties.indent_width
+            && !self.explicit.indent_width
+        {
+            options.set_indent_width(indent_width);
+        }
+        if let Some(line_ending) = properties.line_ending
+            && !self.explicit.line_ending
+        {
+            options.set_line_ending(line_ending);
+        }
+        if let Some(line_width) = properties.line_width
+            && !self.explicit.line_width
+        {
+            options.set_line_width(line_width);
+        }
     }
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+
+/// The character encoding of R files, like `latin1`
+///
+/// Accepts the labels of the [WHATWG Encoding Standard](https://encoding.spec.whatwg.org/#names-and-labels),
+/// which cover the encodings used in `DESCRIPTION` files, like `latin1`, `latin2`,
+/// `UTF-8`, or `CP1252`. Labels are case insensitive.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Encoding(&'static encoding_rs::Encoding);
+
+impl Encoding {
+    pub const UTF_8: Self = Self(encoding_rs::UTF_8);
+
+    /// Returns `None` if `label` isn't a known encoding
+    pub fn for_label(label: &str) -> Option<Self> {
+        encoding_rs::Encoding::for_label(label.trim().as_bytes()).map(Self)
+    }
+
+    pub fn as_encoding_rs(&self) -> &'static encoding_rs::Encoding {
+        self.0
+    }
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::UTF_8
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Encoding {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: String = serde::Deserialize::deserialize(deserializer)?;
+        Self::for_label(&value).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "Unsupported encoding `{value}`. Expected an encoding like `UTF-8` or `latin1`."
+            ))
+        })
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Encoding {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed("Encoding")
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        String::json_schema(generator)
+    }
+}
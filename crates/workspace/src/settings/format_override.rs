@@ -0,0 +1,177 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use air_r_formatter::context::RFormatOptions;
+use settings::AsciiStrings;
+use settings::AssignmentStyle;
+use settings::IndentStyle;
+use settings::IndentWidth;
+use settings::LineWidth;
+use settings::NormalizeNames;
+use settings::PersistentLineBreaks;
+use settings::PreferRawStrings;
+use settings::SectionComments;
+use settings::Skip;
+use settings::Table;
+use settings::TableAlignments;
+use settings::VectorLayout;
+use settings::WrapComments;
+
+use crate::file_patterns::FilePatterns;
+use crate::settings::LineEnding;
+
+/// Format options that apply to the files matched by `include` and `exclude`
+///
+/// Resolved from a `[[format.overrides]]` entry. Options that are `None` aren't set by
+/// the override, and are left as is.
+#[derive(Debug)]
+pub struct FormatOverride {
+    /// The directory of the `air.toml`, which `include` and `exclude` are relative to
+    pub root: PathBuf,
+    /// When `None`, the override applies to all files under `root`
+    pub include: Option<FilePatterns>,
+    pub exclude: Option<FilePatterns>,
+    pub indent_style: Option<IndentStyle>,
+    pub indent_width: Option<IndentWidth>,
+    pub line_ending: Option<LineEnding>,
+    pub line_width: Option<LineWidth>,
+    pub persistent_line_breaks: Option<PersistentLineBreaks>,
+    pub assignment_style: Option<AssignmentStyle>,
+    pub skip: Option<Skip>,
+    /// Resolved against the `table` and `default-table` of the `[format]` table, as
+    /// either may be overridden independently of the other
+    pub table: Option<Option<Table>>,
+    pub table_alignment: Option<TableAlignments>,
+    pub vector_layout: Option<VectorLayout>,
+    pub section_comments: Option<SectionComments>,
+    pub wrap_comments: Option<WrapComments>,
+    pub normalize_names: Option<NormalizeNames>,
+    pub ascii_strings: Option<AsciiStrings>,
+    pub prefer_raw_strings: Option<PreferRawStrings>,
+}
+
+impl FormatOverride {
+    /// Does this override apply to the file at `path`?
+    pub fn matches(&self, path: &Path) -> bool {
+        // `ignore` panics on paths that are not under the `root`
+        if !path.starts_with(&self.root) {
+            return false;
+        }
+
+        let is_directory = false;
+
+        let is_included = self.include.as_ref().is_none_or(|include| {
+            include
+                .matched_path_or_any_parents(path, is_directory)
+                .is_some()
+        });
+
+        let is_excluded = self.exclude.as_ref().is_some_and(|exclude| {
+            exclude
+                .matched_path_or_any_parents(path, is_directory)
+                .is_some()
+        });
+
+        is_included && !is_excluded
+    }
+
+    /// Apply the options set by this override to `options`, in preparation for a
+    /// formatting operation on `source`
+    pub fn apply(&self, options: &mut RFormatOptions, source: &str) {
+        if let Some(indent_style) = self.indent_style {
+            options.set_indent_style(indent_style);
+        }
+        if let Some(indent_width) = self.indent_width {
+            options.set_indent_width(indent_width);
+        }
+        if let Some(line_ending) = self.line_ending {
+            options.set_line_ending(line_ending.finalize(source));
+        }
+        if let Some(line_width) = self.line_width {
+            options.set_line_width(line_width);
+        }
+        if let Some(persistent_line_breaks) = self.persistent_line_breaks {
+            options.set_persistent_line_breaks(persistent_line_breaks);
+        }
+        if let Some(assignment_style) = self.assignment_style {
+            options.set_assignment_style(assignment_style);
+        }
+        // Note that `clone()` on these options is ultimately on an `Arc`
+        if let Some(skip) = &self.skip {
+            options.set_skip(Some(skip.clone()));
+        }
+        if let Some(table) = &self.table {
+            options.set_table(table.clone());
+        }
+        if let Some(table_alignment) = &self.table_alignment {
+            options.set_table_alignment(Some(table_alignment.clone()));
+        }
+        if let Some(vector_layout) = self.vector_layout {
+            options.set_vector_layout(vector_layout);
+        }
+        if let Some(section_comments) = self.section_comments {
+            options.set_section_comments(section_comments);
+        }
+        if let Some(wrap_comments) = self.wrap_comments {
+            options.set_wrap_comments(wrap_comments);
+        }
+        if let Some(normalize_names) = self.normalize_names {
+            options.set_normalize_names(normalize_names);
+        }
+        if let Some(ascii_strings) = self.ascii_strings {
+            options.set_ascii_strings(ascii_strings);
+        }
+        if let Some(prefer_raw_strings) = self.prefer_raw_strings {
+            options.set_prefer_raw_strings(Some(prefer_raw_strings));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use biome_formatter::FormatOptions;
+    use tempfile::TempDir;
+
+    use crate::toml::parse_air_inline_toml;
+
+    #[test]
+    fn test_overrides_apply_in_order() -> anyhow::Result<()> {
+        let tempdir = TempDir::new()?;
+        let root = fs::normalize_path(tempdir.path());
+
+        let toml = r#"
+[format]
+line-width = 100
+
+[[format.overrides]]
+include = ["data-raw/"]
+line-width = 200
+indent-width = 4
+
+[[format.overrides]]
+include = ["data-raw/*.R"]
+exclude = ["data-raw/keep.R"]
+line-width = 120
+"#;
+        let settings = parse_air_inline_toml(toml)?.into_settings(&root, None)?;
+        let format = settings.format;
+
+        let widths = |path: &[&str]| {
+            let path = path.iter().fold(root.clone(), |path, part| path.join(part));
+            let options = format.to_format_options_for_path("", &path);
+            (options.line_width().value(), options.indent_width().value())
+        };
+
+        assert_eq!(widths(&["R", "code.R"]), (100, 2));
+        assert_eq!(widths(&["data-raw", "nested", "data.R"]), (200, 4));
+        assert_eq!(widths(&["data-raw", "data.R"]), (120, 4));
+        assert_eq!(widths(&["data-raw", "keep.R"]), (200, 4));
+
+        // Paths outside of the root are never matched
+        let outside = root.parent().unwrap().join("data-raw").join("data.R");
+        let options = format.to_format_options_for_path("", &outside);
+        assert_eq!(options.line_width().value(), 100);
+
+        Ok(())
+    }
+}
@@ -7,7 +7,11 @@
 
 //! Utilities for locating (and extracting configuration from) an air.toml.
 
+use crate::settings::FormatSettings;
 use crate::toml_options::TomlOptions;
+use air_r_formatter::context::RFormatOptions;
+use biome_formatter::FormatOptions;
+use settings::ColumnAlignment;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io;
@@ -27,10 +31,60 @@ pub fn parse_air_inline_toml(contents: &str) -> Result<TomlOptions, toml::de::Er
     toml::from_str(contents)
 }
 
+/// Resolve the `extend` chain of `options`, read from the configuration file at `path`
+///
+/// Each extended file is combined with the options that extend it, field by field, with
+/// the options closest to `path` taking precedence. Returns the combined options, along
+/// with the paths of the extended files, from closest to furthest.
+pub fn resolve_extend(
+    options: TomlOptions,
+    path: &Path,
+) -> Result<(TomlOptions, Vec<PathBuf>), ParseTomlError> {
+    let mut chain = vec![path.to_path_buf()];
+    let mut options = options;
+    let mut extend = options.global.extend.take();
+
+    while let Some(extended) = extend {
+        // Relative to the directory of the file that declares `extend`
+        let directory = chain.last().and_then(|path| path.parent());
+        let extended = match directory {
+            Some(directory) => fs::normalize_path(directory.join(extended)),
+            None => fs::normalize_path(extended),
+        };
+
+        let is_cycle = chain.contains(&extended);
+        chain.push(extended.clone());
+
+        if is_cycle {
+            return Err(ParseTomlError::ExtendCycle(chain));
+        }
+
+        let mut base = parse_air_toml(&extended)
+            .map_err(|err| ParseTomlError::Extend(chain.clone(), Box::new(err)))?;
+
+        tracing::debug!(
+            "Extending settings of '{path}' with '{extended}'",
+            path = path.display(),
+            extended = extended.display()
+        );
+
+        extend = base.global.extend.take();
+        options = options.combine(base);
+    }
+
+    chain.remove(0);
+    Ok((options, chain))
+}
+
 #[derive(Debug)]
 pub enum ParseTomlError {
     Read(PathBuf, io::Error),
     Deserialize(PathBuf, toml::de::Error),
+    /// Failed to parse a file of an `extend` chain. Holds the chain of files leading
+    /// to the failing file.
+    Extend(Vec<PathBuf>, Box<ParseTomlError>),
+    /// An `extend` chain that refers back to one of its own files
+    ExtendCycle(Vec<PathBuf>),
 }
 
 impl std::error::Error for ParseTomlError {}
@@ -46,10 +100,32 @@ impl Display for ParseTomlError {
             Self::Deserialize(path, err) => {
                 write!(f, "Failed to parse {path}:\n{err}", path = path.display())
             }
+            Self::Extend(chain, err) => {
+                write!(
+                    f,
+                    "Failed to resolve `extend` chain {chain}:\n{err}",
+                    chain = display_chain(chain)
+                )
+            }
+            Self::ExtendCycle(chain) => {
+                write!(
+                    f,
+                    "Circular `extend` chain {chain}",
+                    chain = display_chain(chain)
+                )
+            }
         }
     }
 }
 
+fn display_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
 /// Return the path to the `air.toml` or `.air.toml` file in a given directory.
 pub fn find_air_toml_in_directory<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
     // Check for `air.toml` first, as we prioritize the "visible" one.
@@ -68,6 +144,25 @@ pub fn find_air_toml_in_directory<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
     None
 }
 
+/// Return the directory of the user level configuration
+///
+/// This is `$AIR_CONFIG_DIR` if set, otherwise `$XDG_CONFIG_HOME/air`, where
+/// `$XDG_CONFIG_HOME` defaults to `~/.config`.
+pub fn user_config_directory() -> Option<PathBuf> {
+    let non_empty_var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    if let Some(directory) = non_empty_var("AIR_CONFIG_DIR") {
+        return Some(PathBuf::from(directory));
+    }
+
+    let config_home = match non_empty_var("XDG_CONFIG_HOME") {
+        Some(config_home) => PathBuf::from(config_home),
+        None => std::env::home_dir()?.join(".config"),
+    };
+
+    Some(config_home.join("air"))
+}
+
 /// Find the path to the closest `air.toml` or `.air.toml` if one exists, walking up the filesystem
 pub fn find_air_toml<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
     for directory in path.as_ref().ancestors() {
@@ -86,23 +181,146 @@ pub fn is_air_toml<P: AsRef<Path>>(path: P) -> bool {
     path.ends_with("air.toml") || path.ends_with(".air.toml")
 }
 
+/// Serialize the resolved format `options` as the contents of an `air.toml`
+///
+/// Formatting a file with the resulting `air.toml` reproduces `options`, which makes it
+/// possible to reproduce a formatting result outside of the project it came from. All
+/// options are written out, including the ones at their default value, and `table`
+/// already includes the default table. The options that apply to files rather than
+/// to the formatter, like `encoding`, are taken from the `settings` that `options`
+/// were resolved from.
+pub fn format_options_to_air_toml(options: &RFormatOptions, settings: &FormatSettings) -> String {
+    let mut format = toml::Table::new();
+
+    let indent_style = match options.indent_style() {
+        biome_formatter::IndentStyle::Tab => "tab",
+        biome_formatter::IndentStyle::Space => "space",
+    };
+    let line_ending = match options.line_ending() {
+        biome_formatter::LineEnding::Lf => "lf",
+        biome_formatter::LineEnding::Crlf => "crlf",
+        biome_formatter::LineEnding::Cr => unreachable!("Unsupported `Cr` line endings."),
+    };
+
+    format.insert("indent-style".into(), indent_style.into());
+    format.insert(
+        "indent-width".into(),
+        i64::from(options.indent_width().value()).into(),
+    );
+    format.insert("line-ending".into(), line_ending.into());
+    format.insert(
+        "line-width".into(),
+        i64::from(options.line_width().value()).into(),
+    );
+    format.insert("encoding".into(), settings.encoding.to_string().into());
+    format.insert(
+        "persistent-line-breaks".into(),
+        options.persistent_line_breaks().is_respect().into(),
+    );
+    format.insert(
+        "assignment-style".into(),
+        to_toml_value(options.assignment_style()),
+    );
+
+    if let Some(skip) = options.skip() {
+        format.insert("skip".into(), skip.as_slice().to_vec().into());
+    }
+
+    // The table is already merged with the default table
+    format.insert("default-table".into(), false.into());
+    if let Some(table) = options.table() {
+        format.insert("table".into(), table.as_slice().to_vec().into());
+    }
+
+    if let Some(table_alignment) = options.table_alignment() {
+        let table_alignment: toml::Table = table_alignment
+            .iter()
+            .map(|(name, alignment)| {
+                let alignment = alignment
+                    .as_slice()
+                    .iter()
+                    .map(|column| match column {
+                        ColumnAlignment::Left => "l",
+                        ColumnAlignment::Right => "r",
+                        ColumnAlignment::Center => "c",
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                (name.clone(), alignment.into())
+            })
+            .collect();
+        format.insert("table-alignment".into(), table_alignment.into());
+    }
+
+    format.insert(
+        "vector-layout".into(),
+        to_toml_value(options.vector_layout()),
+    );
+    format.insert(
+        "section-comments".into(),
+        to_toml_value(options.section_comments()),
+    );
+    format.insert(
+        "wrap-comments".into(),
+        options.wrap_comments().is_wrap().into(),
+    );
+    format.insert(
+        "normalize-names".into(),
+        options.normalize_names().is_normalize().into(),
+    );
+    format.insert(
+        "ascii-strings".into(),
+        options.ascii_strings().is_escape().into(),
+    );
+
+    if let Some(prefer_raw_strings) = options.prefer_raw_strings() {
+        format.insert(
+            "prefer-raw-strings".into(),
+            to_toml_value(prefer_raw_strings),
+        );
+    }
+
+    let mut root = toml::Table::new();
+
+    if let Some(minimum_r_version) = options.minimum_r_version() {
+        root.insert(
+            "minimum-r-version".into(),
+            minimum_r_version.to_string().into(),
+        );
+    }
+
+    root.insert("format".into(), format.into());
+
+    root.to_string()
+}
+
+/// Serialize an option with a `serde` representation, like `assignment-style`
+fn to_toml_value<T: serde::Serialize>(value: T) -> toml::Value {
+    toml::Value::try_from(value).expect("Options serialize to TOML values")
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::{Context, Result};
     use std::fs;
     use tempfile::TempDir;
 
+    use crate::settings::Encoding;
     use crate::settings::LineEnding;
     use crate::toml::find_air_toml;
+    use crate::toml::format_options_to_air_toml;
+    use crate::toml::parse_air_inline_toml;
     use crate::toml::parse_air_toml;
     use crate::toml_options::GlobalTomlOptions;
     use crate::toml_options::TomlOptions;
+    use settings::AsciiStrings;
     use settings::LineWidth;
+    use settings::RVersion;
 
     #[test]
     fn deserialize_empty() -> Result<()> {
         let options: TomlOptions = toml::from_str(r"")?;
-        assert_eq!(options.global, GlobalTomlOptions {});
+        assert_eq!(options.global, GlobalTomlOptions::default());
         assert_eq!(options.format, None);
         Ok(())
     }
@@ -198,4 +416,131 @@ indent-width = 4
 
         Ok(())
     }
+
+    #[test]
+    fn ascii_strings_defaults_to_true_in_packages() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let package = Some(tempdir.path());
+
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), None)?;
+        assert_eq!(settings.format.ascii_strings, AsciiStrings::Preserve);
+
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), package)?;
+        assert_eq!(settings.format.ascii_strings, AsciiStrings::Escape);
+
+        // Explicitly opting out wins over the package default
+        let options: TomlOptions = toml::from_str(
+            r#"
+[format]
+ascii-strings = false
+"#,
+        )?;
+        let settings = options.into_settings(tempdir.path(), package)?;
+        assert_eq!(settings.format.ascii_strings, AsciiStrings::Preserve);
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimum_r_version_is_inferred_from_description() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let package = Some(tempdir.path());
+
+        fs::write(
+            tempdir.path().join("DESCRIPTION"),
+            "Package: test\nDepends:\n    R (>= 4.1),\n    methods\n",
+        )?;
+
+        // Only the `DESCRIPTION` of the package is used
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), None)?;
+        assert_eq!(settings.format.minimum_r_version, None);
+
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), package)?;
+        assert_eq!(
+            settings.format.minimum_r_version,
+            Some(RVersion::new(4, 1, 0))
+        );
+
+        // An explicit version wins over the `DESCRIPTION`
+        let options: TomlOptions = toml::from_str(
+            r#"
+minimum-r-version = "4.2.1"
+"#,
+        )?;
+        let settings = options.into_settings(tempdir.path(), package)?;
+        assert_eq!(
+            settings.format.minimum_r_version,
+            Some(RVersion::new(4, 2, 1))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encoding_is_inferred_from_description() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let package = Some(tempdir.path());
+        let latin1 = Encoding::for_label("latin1");
+
+        fs::write(
+            tempdir.path().join("DESCRIPTION"),
+            "Package: test\nEncoding: latin1\n",
+        )?;
+
+        // Only the `DESCRIPTION` of the package is used
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), None)?;
+        assert_eq!(settings.format.encoding, Encoding::UTF_8);
+
+        let options: TomlOptions = toml::from_str("")?;
+        let settings = options.into_settings(tempdir.path(), package)?;
+        assert_eq!(Some(settings.format.encoding), latin1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn format_options_round_trip_through_air_toml() -> Result<()> {
+        let tempdir = TempDir::new()?;
+
+        let options = parse_air_inline_toml(
+            r#"
+minimum-r-version = "4.1"
+
+[format]
+indent-style = "tab"
+line-width = 100
+line-ending = "crlf"
+encoding = "latin1"
+persistent-line-breaks = false
+assignment-style = "equal"
+skip = ["tribble"]
+table = ["fcase"]
+table-alignment = { fcase = "l,r" }
+vector-layout = "fill"
+wrap-comments = true
+normalize-names = true
+ascii-strings = true
+prefer-raw-strings = 2
+"#,
+        )?;
+        let expected = options.into_settings(tempdir.path(), None)?.format;
+        let expected_options = expected.to_format_options("");
+
+        let air_toml = format_options_to_air_toml(&expected_options, &expected);
+        let actual = parse_air_inline_toml(&air_toml)?
+            .into_settings(tempdir.path(), None)?
+            .format;
+        let actual_options = actual.to_format_options("");
+
+        assert_eq!(actual_options.to_string(), expected_options.to_string());
+        assert_eq!(actual.encoding, expected.encoding);
+        assert_eq!(actual.encoding, Encoding::for_label("latin1").unwrap());
+
+        Ok(())
+    }
 }
@@ -6,28 +6,44 @@
 //! Every field is optional at this point, nothing is "finalized".
 //! Finalization is done in [TomlOptions::into_settings].
 //!
-//! Global options are specified at top level in the TOML file (though we don't have
-//! any of those at the moment). All other options are nested within their own `[table]`.
+//! Global options are specified at top level in the TOML file. All other options are
+//! nested within their own `[table]`.
 //!
 //! Note that the doc comments in this file directly influence `air.schema.json`, which
 //! is generated with `just gen-schema`.
 
 use std::path::Path;
+use std::path::PathBuf;
 
+use crate::description::Description;
+use crate::file_patterns::FilePatterns;
+use crate::rbuildignore::RBuildIgnore;
 use crate::settings::DEFAULT_TABLE;
 use crate::settings::DefaultExcludePatterns;
 use crate::settings::DefaultIncludePatterns;
+use crate::settings::Encoding;
 use crate::settings::ExcludePatterns;
+use crate::settings::ExplicitFormatOptions;
+use crate::settings::FormatOverride;
 use crate::settings::FormatSettings;
+use crate::settings::IncludePatterns;
 use crate::settings::LineEnding;
 use crate::settings::Settings;
+use settings::AsciiStrings;
 use settings::AssignmentStyle;
 use settings::IndentStyle;
 use settings::IndentWidth;
 use settings::LineWidth;
+use settings::NormalizeNames;
 use settings::PersistentLineBreaks;
+use settings::PreferRawStrings;
+use settings::RVersion;
+use settings::SectionComments;
 use settings::Skip;
 use settings::Table;
+use settings::TableAlignments;
+use settings::VectorLayout;
+use settings::WrapComments;
 
 /// Configuration for Air
 #[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize)]
@@ -39,12 +55,34 @@ pub struct TomlOptions {
     pub format: Option<FormatTomlOptions>,
 }
 
-// NOTE: Just a placeholder for now, we don't currently have any global settings
 /// Global options affecting multiple commands.
 #[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
-pub struct GlobalTomlOptions {}
+pub struct GlobalTomlOptions {
+    /// # A configuration file to inherit settings from
+    ///
+    /// A path to another `air.toml`, like `"../air.toml"`, relative to the directory of
+    /// the file that declares `extend`. Options that aren't set in this file are taken
+    /// from the extended file, field by field. The extended file may itself `extend`
+    /// another file.
+    ///
+    /// Patterns in options like `exclude` remain relative to the directory of the
+    /// closest `air.toml`, rather than to the directory of the extended file.
+    pub extend: Option<PathBuf>,
+
+    /// # The minimum R version that the code must support
+    ///
+    /// A version like `"4.1"`. Syntax that requires a more recent version of R is
+    /// reported as a parse error, like the `|>` pipe and `\(x)` lambdas (R 4.1.0),
+    /// raw strings (R 4.0.0), or the `_` placeholder (R 4.2.0). Formatter options
+    /// that would introduce such syntax, like `prefer-raw-strings`, are disabled.
+    ///
+    /// When unset, this is inferred from the `Depends: R (>= x.y)` field of the
+    /// `DESCRIPTION` file of the R package that a file belongs to. Otherwise, any
+    /// syntax is allowed.
+    pub minimum_r_version: Option<RVersion>,
+}
 
 /// Options to configure code formatting.
 #[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize)]
@@ -113,6 +151,19 @@ pub struct FormatTomlOptions {
     /// - `native`: Line endings will be converted to `\n` on Unix and `\r\n` on Windows.
     pub line_ending: Option<LineEnding>,
 
+    /// # The character encoding of R files
+    ///
+    /// An encoding like `"latin1"`, for older R packages whose files aren't encoded in
+    /// UTF-8. Files are decoded for formatting, and the formatted code is written back
+    /// in the same encoding. Formatting fails if the formatted code contains characters
+    /// that can't be represented in the encoding.
+    ///
+    /// When unset, this is inferred from the `Encoding` field of the `DESCRIPTION` file
+    /// of the R package that a file belongs to, and defaults to `"UTF-8"` otherwise.
+    /// Files that start with a byte order mark, like UTF-8 or UTF-16 files written on
+    /// Windows, are always decoded according to it.
+    pub encoding: Option<Encoding>,
+
     /// # Whether or not to respect persistent line breaks
     ///
     /// Air respects a small set of persistent line breaks as an indication that certain
@@ -191,6 +242,45 @@ pub struct FormatTomlOptions {
     /// - `import-standalone-*.R`
     pub default_exclude: Option<bool>,
 
+    /// # Patterns of files to format
+    ///
+    /// By default, Air formats files with an `.R` or `.r` extension, along with files
+    /// without an extension that start with an `Rscript` shebang, like
+    /// `#!/usr/bin/env Rscript`. Supplying `include` replaces these defaults, including
+    /// the `Rscript` shebang. Patterns follow the same rules as `exclude`.
+    ///
+    /// Prefer `extend-include` to format additional files on top of the defaults.
+    pub include: Option<Vec<String>>,
+
+    /// # Additional patterns of files to format
+    ///
+    /// Patterns of files to format on top of `include` (or the default `.R` and `.r`
+    /// patterns), like `extend-include = [".Rprofile", "Rprofile.site", "*.R.in"]`.
+    /// Patterns follow the same rules as `exclude`.
+    ///
+    /// Hidden files, like `.Rprofile`, are only formatted when they match one of the
+    /// patterns of `include` or `extend-include`.
+    pub extend_include: Option<Vec<String>>,
+
+    /// # Whether to respect `.gitignore` files
+    ///
+    /// Whether or not files and folders ignored by git, through `.gitignore`,
+    /// `.git/info/exclude`, and the global gitignore file, are skipped when Air looks
+    /// for files in a directory. Defaults to `true`.
+    pub respect_gitignore: Option<bool>,
+
+    /// # Whether to respect `.Rbuildignore` files
+    ///
+    /// Whether or not files and folders matched by the `.Rbuildignore` file of each R
+    /// package, next to its `DESCRIPTION`, are skipped when Air looks for files to
+    /// format. Defaults to `false`, as `.Rbuildignore` often lists folders like
+    /// `data-raw/` that contain R code worth formatting.
+    ///
+    /// Each line of an `.Rbuildignore` is a Perl regular expression that is matched
+    /// case insensitively against paths relative to the package root, like `^data-raw$`.
+    /// Look-arounds and backreferences are not supported, and are reported as errors.
+    pub respect_rbuildignore: Option<bool>,
+
     /// # Function calls to skip formatting for
     ///
     /// Air typically formats every function call it comes across. To skip formatting of
@@ -232,19 +322,237 @@ pub struct FormatTomlOptions {
     /// - `tribble()` from tibble
     /// - `fcase()` from data.table
     pub default_table: Option<bool>,
+
+    /// # Column alignment of function calls formatted as tables
+    ///
+    /// By default, numeric columns of a table are right aligned (or aligned on their
+    /// decimal point), and all other columns are left aligned. Use this option to
+    /// choose the alignment of each column of calls to particular functions. Each
+    /// column is aligned to the left (`l`), to the right (`r`), or centered (`c`).
+    ///
+    /// For example, using `table-alignment = { my_table = "l,r,c" }` would left align
+    /// the first column of `my_table()` calls, right align the second column, and
+    /// center the third column. A single alignment, such as `"r"`, applies to all
+    /// columns. Columns without an alignment use the default alignment.
+    ///
+    /// This option only has an effect on calls formatted as tables, see `table`. For a
+    /// single one-off function call, use a `# fmt: table align=l,r,c` comment instead.
+    pub table_alignment: Option<TableAlignments>,
+
+    /// # The layout of atomic vectors that don't fit on a single line
+    ///
+    /// - `expand` (default): Place each element on its own line.
+    ///
+    /// - `fill`: Pack as many elements per line as fit within the `line-width`.
+    ///
+    /// The `fill` layout only applies to `c()` calls whose arguments are all short
+    /// unnamed literals (such as `1`, `"a"`, or `TRUE`) or identifiers. It avoids
+    /// spreading long vectors like `c(1, 2, 3, ..., 200)` over hundreds of lines.
+    pub vector_layout: Option<VectorLayout>,
+
+    /// # How to format RStudio-style section comments
+    ///
+    /// RStudio treats comments ending in four or more `-`, `=`, or `#`, such as
+    /// `# Load data ----`, as section headers.
+    ///
+    /// - `preserve` (default): Leave section comments as they are written.
+    ///
+    /// - `pad`: Pad section comments to the `line-width` by repeating their trailing
+    ///   character, and ensure each section is preceded by a blank line.
+    pub section_comments: Option<SectionComments>,
+
+    /// # Whether or not to wrap comments to the line width
+    ///
+    /// If `true`, standalone comment blocks (including roxygen `#'` blocks) with
+    /// lines that exceed the `line-width` are rewrapped at word boundaries so that
+    /// they fit. Comments following code on the same line are never wrapped.
+    ///
+    /// Each paragraph of a comment block is wrapped separately, and lines that look
+    /// like code, list items, URLs, and `# fmt:` directives are left untouched.
+    pub wrap_comments: Option<bool>,
+
+    /// # Whether or not to normalize the quoting of names
+    ///
+    /// If `true`, needless backticks around syntactic names are removed, so that
+    /// `` `x` <- 1 `` becomes `x <- 1`. Argument names written as strings, like
+    /// `list("a" = 1)`, are converted to bare names, like `list(a = 1)`.
+    ///
+    /// Names that aren't syntactic, such as reserved words or names containing spaces,
+    /// are always quoted with backticks, like `` list(`my name` = 1) ``.
+    pub normalize_names: Option<bool>,
+
+    /// # Whether or not to escape non-ASCII characters in strings
+    ///
+    /// If `true`, non-ASCII characters in strings are rewritten as `\uXXXX` (or
+    /// `\U{XXXXXX}`) escapes, so that `"café"` becomes `"caf\u00e9"`. `R CMD check`
+    /// warns about non-ASCII characters in the R code of packages, so this defaults to
+    /// `true` for files in an R package (a directory with a `DESCRIPTION` file), and to
+    /// `false` otherwise.
+    ///
+    /// Raw strings, comments, and strings that already contain `\x` or octal escapes
+    /// (which R doesn't allow to be mixed with `\u` escapes) are left untouched.
+    pub ascii_strings: Option<bool>,
+
+    /// # Rewrite heavily escaped strings as raw strings
+    ///
+    /// The minimum number of backslash escapes (`\\`) a string must contain to be
+    /// rewritten as a raw string. For example, with `prefer-raw-strings = 2`,
+    /// `"\\d+\\.\\d+"` is rewritten as `r"(\d+\.\d+)"`. Unset by default, which
+    /// never rewrites strings.
+    ///
+    /// Strings containing escapes that raw strings can't express, such as `\n` or
+    /// `é`, are left untouched. Raw strings require R 4.0.0, so this option is
+    /// disabled when `minimum-r-version` is older than that.
+    pub prefer_raw_strings: Option<PreferRawStrings>,
+
+    /// # Options that apply to a subset of files
+    ///
+    /// Each override selects files with `include` and `exclude` patterns, which follow
+    /// the same rules as the top level `exclude` option, relative to the directory of
+    /// the `air.toml`. The options set in an override take precedence over the options
+    /// of the `[format]` table for the selected files. When multiple overrides select a
+    /// file, they are applied in order, so later overrides take precedence.
+    ///
+    /// ```toml
+    /// [[format.overrides]]
+    /// include = ["data-raw/"]
+    /// line-width = 200
+    /// ```
+    pub overrides: Option<Vec<FormatOverrideTomlOptions>>,
+}
+
+/// Format options that apply to the files selected by `include` and `exclude`.
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FormatOverrideTomlOptions {
+    /// # Patterns of the files this override applies to
+    ///
+    /// Follows the same rules as the top level `exclude` option. When unset, the
+    /// override applies to all files.
+    pub include: Option<Vec<String>>,
+
+    /// # Patterns of the files this override doesn't apply to
+    ///
+    /// Follows the same rules as the top level `exclude` option, and takes precedence
+    /// over `include`.
+    pub exclude: Option<Vec<String>>,
+
+    /// Overrides `line-width`.
+    pub line_width: Option<LineWidth>,
+
+    /// Overrides `indent-width`.
+    pub indent_width: Option<IndentWidth>,
+
+    /// Overrides `indent-style`.
+    pub indent_style: Option<IndentStyle>,
+
+    /// Overrides `line-ending`.
+    pub line_ending: Option<LineEnding>,
+
+    /// Overrides `persistent-line-breaks`.
+    pub persistent_line_breaks: Option<bool>,
+
+    /// Overrides `assignment-style`.
+    pub assignment_style: Option<AssignmentStyle>,
+
+    /// Overrides `skip`.
+    pub skip: Option<Skip>,
+
+    /// Overrides `table`.
+    pub table: Option<Table>,
+
+    /// Overrides `default-table`.
+    pub default_table: Option<bool>,
+
+    /// Overrides `table-alignment`.
+    pub table_alignment: Option<TableAlignments>,
+
+    /// Overrides `vector-layout`.
+    pub vector_layout: Option<VectorLayout>,
+
+    /// Overrides `section-comments`.
+    pub section_comments: Option<SectionComments>,
+
+    /// Overrides `wrap-comments`.
+    pub wrap_comments: Option<bool>,
+
+    /// Overrides `normalize-names`.
+    pub normalize_names: Option<bool>,
+
+    /// Overrides `ascii-strings`.
+    pub ascii_strings: Option<bool>,
+
+    /// Overrides `prefer-raw-strings`.
+    pub prefer_raw_strings: Option<PreferRawStrings>,
 }
 
 impl TomlOptions {
-    pub fn into_settings(self, root: &Path) -> anyhow::Result<Settings> {
+    /// Combine these options with the `base` options they extend
+    ///
+    /// Options set in `self` take precedence over options set in `base`. The `extend`
+    /// option itself is not inherited, as it has already been resolved.
+    pub fn combine(self, base: Self) -> Self {
+        let format = match (self.format, base.format) {
+            (Some(format), Some(base)) => Some(format.combine(base)),
+            (format, base) => format.or(base),
+        };
+
+        Self {
+            global: self.global.combine(base.global),
+            format,
+        }
+    }
+
+    /// Finalize these options into [Settings]
+    ///
+    /// `root` is the directory of the configuration, which patterns are relative to.
+    /// `package` is the R package directory that the settings apply to, if any, which
+    /// determines the defaults of some options, like `ascii-strings`,
+    /// `minimum-r-version`, or `encoding`, and holds the `.Rbuildignore` file.
+    pub fn into_settings(self, root: &Path, package: Option<&Path>) -> anyhow::Result<Settings> {
         let format = self.format.unwrap_or_default();
 
-        let table = if format.default_table.unwrap_or(true) {
-            Some(match format.table {
-                Some(table) => table.merge(&DEFAULT_TABLE),
-                None => DEFAULT_TABLE.clone(),
-            })
-        } else {
-            format.table
+        let description = package.and_then(|package| {
+            let path = package.join("DESCRIPTION");
+            let description = Description::read(&path)?;
+            Some((path, description))
+        });
+
+        let minimum_r_version = self.global.minimum_r_version.or_else(|| {
+            let (path, description) = description.as_ref()?;
+            let minimum_r_version = description.minimum_r_version()?;
+            tracing::debug!(
+                "Using `minimum-r-version` = `{minimum_r_version}` from `Depends` in '{path}'",
+                path = path.display()
+            );
+            Some(minimum_r_version)
+        });
+
+        let encoding = format.encoding.or_else(|| {
+            let (path, description) = description.as_ref()?;
+            let encoding = description.encoding()?;
+            tracing::debug!(
+                "Using `encoding` = `{encoding}` from `Encoding` in '{path}'",
+                path = path.display()
+            );
+            Some(encoding)
+        });
+
+        let overrides = format
+            .overrides
+            .iter()
+            .flatten()
+            .map(|format_override| format_override.to_format_override(&format, root))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let table = resolve_table(format.table, format.default_table);
+
+        let explicit = ExplicitFormatOptions {
+            indent_style: format.indent_style.is_some(),
+            indent_width: format.indent_width.is_some(),
+            line_ending: format.line_ending.is_some(),
+            line_width: format.line_width.is_some(),
         };
 
         let format = FormatSettings {
@@ -252,6 +560,7 @@ impl TomlOptions {
             indent_width: format.indent_width.unwrap_or_default(),
             line_ending: format.line_ending.unwrap_or_default(),
             line_width: format.line_width.unwrap_or_default(),
+            encoding: encoding.unwrap_or_default(),
             persistent_line_breaks: match format.persistent_line_breaks {
                 Some(persistent_line_breaks) => {
                     if persistent_line_breaks {
@@ -274,14 +583,181 @@ impl TomlOptions {
                 true => Some(DefaultExcludePatterns::default()),
                 false => None,
             },
-            // `include` and `default_include` are not currently exposed as toml options.
-            // Theoretically could be for consistency, but there aren't any motivating use
-            // cases right now.
-            default_include: Some(DefaultIncludePatterns::default()),
+            include: match (&format.include, &format.extend_include) {
+                (None, None) => None,
+                (include, extend_include) => {
+                    let include = include.iter().chain(extend_include).flatten();
+                    let include = include.map(String::as_str);
+                    Some(IncludePatterns::try_from_iter(root, include)?)
+                }
+            },
+            default_include: match format.include {
+                Some(_) => None,
+                None => Some(DefaultIncludePatterns::default()),
+            },
+            respect_gitignore: format.respect_gitignore.unwrap_or(true),
+            rbuildignore: match (format.respect_rbuildignore.unwrap_or(false), package) {
+                (true, Some(package)) => RBuildIgnore::read(package)?,
+                _ => None,
+            },
             skip: format.skip,
             table,
+            table_alignment: format.table_alignment,
+            vector_layout: format.vector_layout.unwrap_or_default(),
+            section_comments: format.section_comments.unwrap_or_default(),
+            wrap_comments: match format.wrap_comments {
+                Some(true) => WrapComments::Wrap,
+                Some(false) | None => WrapComments::Preserve,
+            },
+            normalize_names: match format.normalize_names {
+                Some(true) => NormalizeNames::Normalize,
+                Some(false) | None => NormalizeNames::Preserve,
+            },
+            ascii_strings: match format.ascii_strings {
+                Some(true) => AsciiStrings::Escape,
+                Some(false) => AsciiStrings::Preserve,
+                // `R CMD check` warns about non-ASCII characters in package code
+                None => match package {
+                    Some(package) => {
+                        tracing::debug!(
+                            "Using `ascii-strings` = `true` due to 'DESCRIPTION' in '{package}'",
+                            package = package.display()
+                        );
+                        AsciiStrings::Escape
+                    }
+                    None => AsciiStrings::Preserve,
+                },
+            },
+            prefer_raw_strings: format.prefer_raw_strings,
+            minimum_r_version,
+            explicit,
+            overrides,
         };
 
-        Ok(Settings { format })
+        Ok(Settings {
+            format,
+            extended: Vec::new(),
+            from_config: false,
+        })
+    }
+}
+
+impl GlobalTomlOptions {
+    fn combine(self, base: Self) -> Self {
+        Self {
+            extend: self.extend,
+            minimum_r_version: self.minimum_r_version.or(base.minimum_r_version),
+        }
+    }
+}
+
+impl FormatTomlOptions {
+    fn combine(self, base: Self) -> Self {
+        Self {
+            line_width: self.line_width.or(base.line_width),
+            indent_width: self.indent_width.or(base.indent_width),
+            indent_style: self.indent_style.or(base.indent_style),
+            line_ending: self.line_ending.or(base.line_ending),
+            encoding: self.encoding.or(base.encoding),
+            persistent_line_breaks: self.persistent_line_breaks.or(base.persistent_line_breaks),
+            assignment_style: self.assignment_style.or(base.assignment_style),
+            exclude: self.exclude.or(base.exclude),
+            default_exclude: self.default_exclude.or(base.default_exclude),
+            include: self.include.or(base.include),
+            extend_include: self.extend_include.or(base.extend_include),
+            respect_gitignore: self.respect_gitignore.or(base.respect_gitignore),
+            respect_rbuildignore: self.respect_rbuildignore.or(base.respect_rbuildignore),
+            skip: self.skip.or(base.skip),
+            table: self.table.or(base.table),
+            default_table: self.default_table.or(base.default_table),
+            table_alignment: self.table_alignment.or(base.table_alignment),
+            vector_layout: self.vector_layout.or(base.vector_layout),
+            section_comments: self.section_comments.or(base.section_comments),
+            wrap_comments: self.wrap_comments.or(base.wrap_comments),
+            normalize_names: self.normalize_names.or(base.normalize_names),
+            ascii_strings: self.ascii_strings.or(base.ascii_strings),
+            prefer_raw_strings: self.prefer_raw_strings.or(base.prefer_raw_strings),
+            overrides: self.overrides.or(base.overrides),
+        }
+    }
+}
+
+impl FormatOverrideTomlOptions {
+    fn to_format_override(
+        &self,
+        format: &FormatTomlOptions,
+        root: &Path,
+    ) -> anyhow::Result<FormatOverride> {
+        // Either `table` or `default-table` may be overridden, in which case the other
+        // one comes from the `[format]` table
+        let table = if self.table.is_some() || self.default_table.is_some() {
+            Some(resolve_table(
+                self.table.clone().or_else(|| format.table.clone()),
+                self.default_table.or(format.default_table),
+            ))
+        } else {
+            None
+        };
+
+        Ok(FormatOverride {
+            root: root.to_path_buf(),
+            include: file_patterns(root, self.include.as_deref())?,
+            exclude: file_patterns(root, self.exclude.as_deref())?,
+            indent_style: self.indent_style,
+            indent_width: self.indent_width,
+            line_ending: self.line_ending,
+            line_width: self.line_width,
+            persistent_line_breaks: self.persistent_line_breaks.map(|persistent_line_breaks| {
+                if persistent_line_breaks {
+                    PersistentLineBreaks::Respect
+                } else {
+                    PersistentLineBreaks::Ignore
+                }
+            }),
+            assignment_style: self.assignment_style,
+            skip: self.skip.clone(),
+            table,
+            table_alignment: self.table_alignment.clone(),
+            vector_layout: self.vector_layout,
+            section_comments: self.section_comments,
+            wrap_comments: self.wrap_comments.map(|wrap_comments| match wrap_comments {
+                true => WrapComments::Wrap,
+                false => WrapComments::Preserve,
+            }),
+            normalize_names: self
+                .normalize_names
+                .map(|normalize_names| match normalize_names {
+                    true => NormalizeNames::Normalize,
+                    false => NormalizeNames::Preserve,
+                }),
+            ascii_strings: self.ascii_strings.map(|ascii_strings| match ascii_strings {
+                true => AsciiStrings::Escape,
+                false => AsciiStrings::Preserve,
+            }),
+            prefer_raw_strings: self.prefer_raw_strings,
+        })
+    }
+}
+
+/// Combine the user supplied `table` with the default table, unless `default-table` is
+/// `false`
+fn resolve_table(table: Option<Table>, default_table: Option<bool>) -> Option<Table> {
+    if default_table.unwrap_or(true) {
+        Some(match table {
+            Some(table) => table.merge(&DEFAULT_TABLE),
+            None => DEFAULT_TABLE.clone(),
+        })
+    } else {
+        table
+    }
+}
+
+fn file_patterns(root: &Path, patterns: Option<&[String]>) -> anyhow::Result<Option<FilePatterns>> {
+    match patterns {
+        Some(patterns) => {
+            let patterns = patterns.iter().map(String::as_str);
+            Ok(Some(FilePatterns::try_from_iter(root, patterns)?))
+        }
+        None => Ok(None),
     }
 }